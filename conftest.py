@@ -11,7 +11,7 @@ from click.testing import CliRunner
 
 from rose.cache import CACHE_SCHEMA_PATH, process_string_for_fts, update_cache
 from rose.common import VERSION
-from rose.config import Config
+from rose.config import Config, LockTimeouts
 from rose.templates import PathTemplateConfig
 
 logger = logging.getLogger(__name__)
@@ -73,6 +73,7 @@ def config(isolated_dir: Path) -> Config:
         fuse_mount_dir=mount_dir,
         cache_dir=cache_dir,
         max_proc=2,
+        cache_migration_mode="nuke",
         artist_aliases_map={},
         artist_aliases_parents_map={},
         fuse_artists_whitelist=None,
@@ -83,9 +84,29 @@ def config(isolated_dir: Path) -> Config:
         fuse_labels_blacklist=None,
         cover_art_stems=["cover", "folder", "art", "front"],
         valid_art_exts=["jpg", "jpeg", "png"],
+        write_cover_art_stem="cover",
+        convert_unsupported_covers=False,
+        extract_embedded_covers=False,
         path_templates=PathTemplateConfig.with_defaults(),
+        rename_source_dirs=False,
         rename_source_files=False,
+        combined_artist_tag_fallback=False,
+        derive_albumartist_from_tracks=False,
+        va_artist_threshold=10,
+        seed_added_at_from_fs=False,
+        added_at_format="datetime",
+        id_uuid_version=7,
+        compute_audio_sha256=False,
+        follow_symlinks=False,
+        min_track_duration_seconds=None,
+        lock_timeout_seconds=LockTimeouts(),
+        artist_role_order=["djmixer", "composer", "main", "guest", "producer"],
         ignore_release_directories=[],
+        collages_dirname="!collages",
+        playlists_dirname="!playlists",
+        collage_missing_grace_updates=0,
+        collage_description_mark_new=False,
+        collage_playlist_parallel_threshold=None,
         stored_metadata_rules=[],
     )
 
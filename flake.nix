@@ -32,6 +32,7 @@
         jinja2
         llfuse
         mutagen
+        pillow
         send2trash
         setuptools
         tomli-w
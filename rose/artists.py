@@ -0,0 +1,125 @@
+"""
+The artists module provides functions for interacting with artists across the library.
+"""
+
+from __future__ import annotations
+
+import logging
+import unicodedata
+from collections import defaultdict
+from pathlib import Path
+
+from rose.audiotags import AudioTags
+from rose.cache import (
+    connect,
+    list_releases,
+    lock,
+    release_lock_name,
+    update_cache_for_releases,
+)
+from rose.common import Artist, ArtistMapping, uniq
+from rose.config import Config
+from rose.releases import UnknownArtistRoleError
+
+logger = logging.getLogger(__name__)
+
+
+def rename_artist(c: Config, old: str, new: str, roles: list[str] | None = None) -> int:
+    """
+    Rename an artist across the whole library, in both the release and track artist tags. If roles
+    are passed, only credits in those roles are renamed. Returns the number of files modified.
+    """
+    all_roles = [role for role, _ in ArtistMapping().items()]
+    roles = roles if roles is not None else all_roles
+    for role in roles:
+        if role not in all_roles:
+            raise UnknownArtistRoleError(
+                f"Unknown artist role {role}: must be one of {', '.join(all_roles)}"
+            )
+
+    # Find the tracks whose tags credit the artist, either on the track or on its release.
+    roles_placeholder = ",".join(["?"] * len(roles))
+    with connect(c) as conn:
+        cursor = conn.execute(
+            f"""
+            SELECT source_path, release_id
+            FROM tracks
+            WHERE id IN (
+                SELECT track_id
+                FROM tracks_artists
+                WHERE artist = ? AND role IN ({roles_placeholder})
+            ) OR release_id IN (
+                SELECT release_id
+                FROM releases_artists
+                WHERE artist = ? AND role IN ({roles_placeholder})
+            )
+            ORDER BY source_path
+            """,
+            [old, *roles, old, *roles],
+        )
+        matches: dict[str, list[Path]] = defaultdict(list)
+        for row in cursor:
+            matches[row["release_id"]].append(Path(row["source_path"]))
+
+    changed_release_ids: set[str] = set()
+    num_changed_files = 0
+    for release_id, paths in matches.items():
+        with lock(c, release_lock_name(release_id)):
+            for path in paths:
+                tags = AudioTags.from_file(
+                    path, combined_artist_tag_fallback=c.combined_artist_tag_fallback
+                )
+                changed = False
+                for mapping in [tags.albumartists, tags.trackartists]:
+                    for role in roles:
+                        artists: list[Artist] = getattr(mapping, role)
+                        if not any(a.name == old for a in artists):
+                            continue
+                        renamed = [Artist(new) if a.name == old else a for a in artists]
+                        setattr(mapping, role, uniq(renamed))
+                        changed = True
+                if not changed:
+                    continue
+                tags.flush()
+                logger.info(f"Renamed artist {old} to {new} in {path}")
+                changed_release_ids.add(release_id)
+                num_changed_files += 1
+
+    if changed_release_ids:
+        source_paths = [
+            r.source_path for r in list_releases(c, list(changed_release_ids), include_hidden=True)
+        ]
+        update_cache_for_releases(c, source_paths)
+    return num_changed_files
+
+
+def normalize_artist_name(name: str) -> str:
+    """
+    Normalize an artist name for fuzzy comparison: fold the case and strip diacritics, so that e.g.
+    `Björk` and `BJORK` normalize equal.
+    """
+    decomposed = unicodedata.normalize("NFKD", name)
+    stripped = "".join(ch for ch in decomposed if not unicodedata.combining(ch))
+    return unicodedata.normalize("NFC", stripped).casefold()
+
+
+def find_artist_name_variants(c: Config) -> list[list[str]]:
+    """
+    Find clusters of artists in the library whose names normalize equal but are not identical, e.g.
+    `Björk` and `Bjork`. Nothing is modified: the clusters are meant to be reviewed and then merged
+    with `rename_artist`. Each cluster is sorted, and the clusters are sorted by their first name.
+    """
+    with connect(c) as conn:
+        cursor = conn.execute(
+            """
+            SELECT artist FROM releases_artists
+            UNION
+            SELECT artist FROM tracks_artists
+            """
+        )
+        artists = [row["artist"] for row in cursor]
+
+    clusters: dict[str, list[str]] = defaultdict(list)
+    for artist in artists:
+        clusters[normalize_artist_name(artist)].append(artist)
+    return sorted(sorted(names) for names in clusters.values() if len(names) > 1)
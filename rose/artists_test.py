@@ -0,0 +1,92 @@
+from pathlib import Path
+
+import pytest
+
+from rose.artists import (
+    find_artist_name_variants,
+    normalize_artist_name,
+    rename_artist,
+)
+from rose.audiotags import AudioTags
+from rose.cache import connect, update_cache
+from rose.common import Artist, ArtistMapping
+from rose.config import Config
+from rose.releases import UnknownArtistRoleError
+
+
+def _credit_bass_man(source_dir: Path) -> None:
+    for f in ["01.m4a", "02.m4a"]:
+        af = AudioTags.from_file(source_dir / "Test Release 1" / f)
+        af.albumartists = ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")])
+        af.flush()
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    af.trackartists = ArtistMapping(main=[Artist("Bass Man")], guest=[Artist("Techno Man")])
+    af.flush()
+    af = AudioTags.from_file(source_dir / "Test Release 2" / "01.m4a")
+    af.trackartists = ArtistMapping(main=[Artist("Techno Man")], guest=[Artist("Bass Man")])
+    af.flush()
+
+
+def test_rename_artist(config: Config, source_dir: Path) -> None:
+    _credit_bass_man(source_dir)
+    update_cache(config)
+
+    assert rename_artist(config, "Bass Man", "Bass Person") == 3
+
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    assert af.albumartists.main == [Artist("Techno Man"), Artist("Bass Person")]
+    assert af.trackartists.main == [Artist("Bass Person")]
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "02.m4a")
+    assert af.albumartists.main == [Artist("Techno Man"), Artist("Bass Person")]
+    af = AudioTags.from_file(source_dir / "Test Release 2" / "01.m4a")
+    assert af.trackartists.guest == [Artist("Bass Person")]
+
+    with connect(config) as conn:
+        cursor = conn.execute(
+            """
+            SELECT EXISTS(SELECT * FROM releases_artists WHERE artist = 'Bass Man')
+                OR EXISTS(SELECT * FROM tracks_artists WHERE artist = 'Bass Man')
+            """
+        )
+        assert not cursor.fetchone()[0]
+        cursor = conn.execute("SELECT COUNT(*) FROM releases_artists WHERE artist = 'Bass Person'")
+        assert cursor.fetchone()[0] == 1
+        cursor = conn.execute("SELECT COUNT(*) FROM tracks_artists WHERE artist = 'Bass Person'")
+        assert cursor.fetchone()[0] == 2
+
+
+def test_rename_artist_roles(config: Config, source_dir: Path) -> None:
+    _credit_bass_man(source_dir)
+    update_cache(config)
+
+    assert rename_artist(config, "Bass Man", "Bass Person", roles=["guest"]) == 1
+
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    assert af.albumartists.main == [Artist("Techno Man"), Artist("Bass Man")]
+    assert af.trackartists.main == [Artist("Bass Man")]
+    af = AudioTags.from_file(source_dir / "Test Release 2" / "01.m4a")
+    assert af.trackartists.guest == [Artist("Bass Person")]
+
+
+def test_rename_artist_invalid_role(config: Config) -> None:
+    with pytest.raises(UnknownArtistRoleError):
+        rename_artist(config, "Bass Man", "Bass Person", roles=["lalala"])
+
+
+def test_normalize_artist_name() -> None:
+    assert normalize_artist_name("Björk") == "bjork"
+    # Decomposed diacritics normalize the same as precomposed diacritics.
+    assert normalize_artist_name("Bjo\u0308rk") == "bjork"
+    assert normalize_artist_name("BJORK") == "bjork"
+
+
+def test_find_artist_name_variants(config: Config, source_dir: Path) -> None:
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    af.trackartists = ArtistMapping(main=[Artist("Björk")])
+    af.flush()
+    af = AudioTags.from_file(source_dir / "Test Release 2" / "01.m4a")
+    af.trackartists = ArtistMapping(main=[Artist("Bjork")], guest=[Artist("bjork")])
+    af.flush()
+    update_cache(config)
+
+    assert find_artist_name_variants(config) == [["Bjork", "Björk", "bjork"]]
@@ -8,20 +8,24 @@ normalization, artist formatting, and enum validation.
 
 from __future__ import annotations
 
+import base64
 import contextlib
+import hashlib
 import logging
+import os
 import re
 import sys
 import typing
 from dataclasses import dataclass
 from pathlib import Path
-from typing import Any, no_type_check
+from typing import Any, BinaryIO, Literal, no_type_check
 
 import mutagen
 import mutagen.flac
 import mutagen.id3
 import mutagen.mp3
 import mutagen.mp4
+import mutagen.ogg
 import mutagen.oggopus
 import mutagen.oggvorbis
 
@@ -35,7 +39,8 @@ logger = logging.getLogger(__name__)
 
 TAG_SPLITTER_REGEX = re.compile(r" \\\\ | / |; ?| vs\. ")
 YEAR_REGEX = re.compile(r"\d{4}$")
-DATE_REGEX = re.compile(r"(\d{4})-\d{2}-\d{2}")
+# Matches full dates and partial dates with a year, such as `2021-05` and `2021--05`.
+DATE_REGEX = re.compile(r"(\d{4})-")
 
 SUPPORTED_AUDIO_EXTENSIONS = [
     ".mp3",
@@ -45,6 +50,26 @@ SUPPORTED_AUDIO_EXTENSIONS = [
     ".flac",
 ]
 
+Advisory = Literal["explicit", "clean"]
+
+# The iTunes advisory values: 1 (or the legacy 4) is explicit and 2 is clean. 0 means unrated.
+ITUNES_ADVISORY_VALUES: dict[Advisory, int] = {"explicit": 1, "clean": 2}
+
+
+def is_supported_audio_file(p: Path) -> bool:
+    """Whether the file has a supported audio extension, compared case-insensitively."""
+    return p.suffix.lower() in SUPPORTED_AUDIO_EXTENSIONS
+
+
+def _parse_advisory(x: int | str | None) -> Advisory | None:
+    value = _parse_int(x) if isinstance(x, str) else x
+    if value in (1, 4):
+        return "explicit"
+    if value == 2:
+        return "clean"
+    return None
+
+
 SUPPORTED_RELEASE_TYPES = [
     "album",
     "single",
@@ -86,17 +111,38 @@ class AudioTags:
     id: str | None
     release_id: str | None
     title: str | None
+    # The version of the track, e.g. "Radio Edit".
+    subtitle: str | None
     year: int | None
     tracknumber: str | None
     tracktotal: int | None
     discnumber: str | None
     disctotal: int | None
+    # The name of the disc the track is on, e.g. "The Studio Album" in a box set.
+    discsubtitle: str | None
     album: str | None
     genre: list[str]
     label: list[str]
     releasetype: str
+    # Whether the track is part of a gapless album, e.g. iTunes' `pgap`. Players use this hint to
+    # omit the gap between tracks.
+    gapless: bool
+    # The software that encoded the audio and the person or organization that encoded it.
+    encoder: str | None
+    encoded_by: str | None
+    comment: str | None
+    # The parental advisory rating of the track, e.g. iTunes' `rtng`. None when unrated.
+    advisory: Advisory | None
+    # The International Standard Recording Code of the track's recording.
+    isrc: str | None
+    # Play and skip counts written by players such as iTunes. Rosé never changes these itself, but
+    # preserves them so that they survive migrations.
+    play_count: int | None
+    skip_count: int | None
 
+    # Read from the ALBUMARTIST tags.
     albumartists: ArtistMapping
+    # Read from the ARTIST tags and the other per-track role tags.
     trackartists: ArtistMapping
 
     duration_sec: int
@@ -104,9 +150,12 @@ class AudioTags:
     path: Path
 
     @classmethod
-    def from_file(cls, p: Path) -> AudioTags:
-        """Read the tags of an audio file on disk."""
-        if not any(p.suffix.lower() == ext for ext in SUPPORTED_AUDIO_EXTENSIONS):
+    def from_file(cls, p: Path, *, combined_artist_tag_fallback: bool = False) -> AudioTags:
+        """
+        Read the tags of an audio file on disk. See `parse_artist_string` for
+        `combined_artist_tag_fallback`.
+        """
+        if not is_supported_audio_file(p):
             raise UnsupportedFiletypeError(f"{p.suffix} not a supported filetype")
         try:
             m = mutagen.File(p)  # type: ignore
@@ -139,19 +188,39 @@ class AudioTags:
                     return r" \\ ".join([p[1] for p in frame.people if p[0].lower() == x.lower()])
                 return None
 
+            def _get_comment() -> str | None:
+                # Only read the comment with an empty description. Software stores its own data in
+                # comments with descriptions (e.g. iTunes' iTunNORM), which are not user comments.
+                if not m.tags:
+                    return None
+                for frame in m.tags.getall("COMM"):
+                    if frame.desc == "":
+                        return r" \\ ".join(frame.text) or None
+                return None
+
             return AudioTags(
                 id=_get_tag(m.tags, ["TXXX:ROSEID"]),
                 release_id=_get_tag(m.tags, ["TXXX:ROSERELEASEID"]),
                 title=_get_tag(m.tags, ["TIT2"]),
+                subtitle=_get_tag(m.tags, ["TIT3"]),
                 year=_parse_year(_get_tag(m.tags, ["TDRC", "TYER"])),
                 tracknumber=tracknumber,
                 tracktotal=tracktotal,
                 discnumber=discnumber,
                 disctotal=disctotal,
+                discsubtitle=_get_tag(m.tags, ["TSST"]),
                 album=_get_tag(m.tags, ["TALB"]),
                 genre=_split_tag(_get_tag(m.tags, ["TCON"], split=True)),
                 label=_split_tag(_get_tag(m.tags, ["TPUB"], split=True)),
                 releasetype=_normalize_rtype(_get_tag(m.tags, ["TXXX:RELEASETYPE"], first=True)),
+                gapless=_parse_bool(_get_tag(m.tags, ["TXXX:ITUNESGAPLESS"], first=True)),
+                encoder=_get_tag(m.tags, ["TSSE"]),
+                encoded_by=_get_tag(m.tags, ["TENC"]),
+                comment=_get_comment(),
+                advisory=_parse_advisory(_get_tag(m.tags, ["TXXX:ITUNESADVISORY"], first=True)),
+                isrc=_get_tag(m.tags, ["TSRC"], first=True),
+                play_count=m.tags["PCNT"].count if m.tags and "PCNT" in m.tags else None,
+                skip_count=_parse_int(_get_tag(m.tags, ["TXXX:SKIPCOUNT"], first=True)),
                 albumartists=parse_artist_string(main=_get_tag(m.tags, ["TPE2"], split=True)),
                 trackartists=parse_artist_string(
                     main=_get_tag(m.tags, ["TPE1"], split=True),
@@ -160,8 +229,9 @@ class AudioTags:
                     conductor=_get_tag(m.tags, ["TPE3"], split=True),
                     producer=_get_paired_frame("producer"),
                     dj=_get_paired_frame("DJ-mix"),
+                    fallback_only=combined_artist_tag_fallback,
                 ),
-                duration_sec=round(m.info.length),
+                duration_sec=_mp3_duration_sec(p, m.info.length),
                 path=p,
             )
         if isinstance(m, mutagen.mp4.MP4):
@@ -177,17 +247,31 @@ class AudioTags:
                 id=_get_tag(m.tags, ["----:net.sunsetglow.rose:ID"]),
                 release_id=_get_tag(m.tags, ["----:net.sunsetglow.rose:RELEASEID"]),
                 title=_get_tag(m.tags, ["\xa9nam"]),
+                subtitle=_get_tag(m.tags, ["----:com.apple.iTunes:SUBTITLE"]),
                 year=_parse_year(_get_tag(m.tags, ["\xa9day"])),
                 tracknumber=str(tracknumber),
                 tracktotal=tracktotal,
                 discnumber=str(discnumber),
                 disctotal=disctotal,
+                discsubtitle=_get_tag(m.tags, ["----:com.apple.iTunes:DISCSUBTITLE"]),
                 album=_get_tag(m.tags, ["\xa9alb"]),
                 genre=_split_tag(_get_tag(m.tags, ["\xa9gen"], split=True)),
                 label=_split_tag(_get_tag(m.tags, ["----:com.apple.iTunes:LABEL"], split=True)),
                 releasetype=_normalize_rtype(
                     _get_tag(m.tags, ["----:com.apple.iTunes:RELEASETYPE"], first=True)
                 ),
+                gapless=bool(m.tags and m.tags.get("pgap", False)),
+                encoder=_get_tag(m.tags, ["\xa9too"]),
+                encoded_by=_get_tag(m.tags, ["\xa9enc"]),
+                comment=_get_tag(m.tags, ["\xa9cmt"]),
+                advisory=_parse_advisory(m.tags.get("rtng", [None])[0] if m.tags else None),
+                isrc=_get_tag(m.tags, ["----:com.apple.iTunes:ISRC"], first=True),
+                play_count=_parse_int(
+                    _get_tag(m.tags, ["----:com.apple.iTunes:PLAYCOUNT"], first=True)
+                ),
+                skip_count=_parse_int(
+                    _get_tag(m.tags, ["----:com.apple.iTunes:SKIPCOUNT"], first=True)
+                ),
                 albumartists=parse_artist_string(main=_get_tag(m.tags, ["aART"], split=True)),
                 trackartists=parse_artist_string(
                     main=_get_tag(m.tags, ["\xa9ART"], split=True),
@@ -196,6 +280,7 @@ class AudioTags:
                     composer=_get_tag(m.tags, ["\xa9wrt"], split=True),
                     conductor=_get_tag(m.tags, ["----:com.apple.iTunes:CONDUCTOR"], split=True),
                     dj=_get_tag(m.tags, ["----:com.apple.iTunes:DJMIXER"], split=True),
+                    fallback_only=combined_artist_tag_fallback,
                 ),
                 duration_sec=round(m.info.length),  # type: ignore
                 path=p,
@@ -205,17 +290,27 @@ class AudioTags:
                 id=_get_tag(m.tags, ["roseid"]),
                 release_id=_get_tag(m.tags, ["rosereleaseid"]),
                 title=_get_tag(m.tags, ["title"]),
+                subtitle=_get_tag(m.tags, ["subtitle"]),
                 year=_parse_year(_get_tag(m.tags, ["date", "year"])),
                 tracknumber=_get_tag(m.tags, ["tracknumber"], first=True),
                 tracktotal=_parse_int(_get_tag(m.tags, ["tracktotal"], first=True)),
                 discnumber=_get_tag(m.tags, ["discnumber"], first=True),
                 disctotal=_parse_int(_get_tag(m.tags, ["disctotal"], first=True)),
+                discsubtitle=_get_tag(m.tags, ["discsubtitle"]),
                 album=_get_tag(m.tags, ["album"]),
                 genre=_split_tag(_get_tag(m.tags, ["genre"], split=True)),
                 label=_split_tag(
                     _get_tag(m.tags, ["organization", "label", "recordlabel"], split=True)
                 ),
                 releasetype=_normalize_rtype(_get_tag(m.tags, ["releasetype"], first=True)),
+                gapless=_parse_bool(_get_tag(m.tags, ["itunesgapless"], first=True)),
+                encoder=_get_tag(m.tags, ["encoder"]),
+                encoded_by=_get_tag(m.tags, ["encodedby", "encoded-by"]),
+                comment=_get_tag(m.tags, ["comment"]),
+                advisory=_parse_advisory(_get_tag(m.tags, ["itunesadvisory"], first=True)),
+                isrc=_get_tag(m.tags, ["isrc"], first=True),
+                play_count=_parse_int(_get_tag(m.tags, ["playcount"], first=True)),
+                skip_count=_parse_int(_get_tag(m.tags, ["skipcount"], first=True)),
                 albumartists=parse_artist_string(
                     main=_get_tag(m.tags, ["albumartist"], split=True)
                 ),
@@ -226,12 +321,51 @@ class AudioTags:
                     composer=_get_tag(m.tags, ["composer"], split=True),
                     conductor=_get_tag(m.tags, ["conductor"], split=True),
                     dj=_get_tag(m.tags, ["djmixer"], split=True),
+                    fallback_only=combined_artist_tag_fallback,
                 ),
                 duration_sec=round(m.info.length),  # type: ignore
                 path=p,
             )
         raise UnsupportedFiletypeError(f"{p} is not a supported audio file")
 
+    def embedded_cover(self) -> bytes | None:
+        """
+        Read the image bytes of the cover art embedded in the file's tags, or None if the file has
+        no embedded cover art. If multiple images are embedded, the front cover is preferred. The
+        images are not read by `from_file`, as they are large and rarely needed.
+        """
+        try:
+            m = mutagen.File(self.path)  # type: ignore
+        except mutagen.MutagenError as e:  # type: ignore
+            raise UnsupportedFiletypeError(f"Failed to open file: {e}") from e
+
+        pictures: list[tuple[int, bytes]] = []
+        if isinstance(m, mutagen.mp3.MP3):
+            if m.tags:
+                pictures = [(f.type, f.data) for f in m.tags.getall("APIC")]
+        elif isinstance(m, mutagen.mp4.MP4):
+            # MP4 covers do not carry a picture type, so treat them all as front covers.
+            if m.tags:
+                pictures = [(3, bytes(x)) for x in m.tags.get("covr", [])]
+        elif isinstance(m, mutagen.flac.FLAC):
+            pictures = [(x.type, x.data) for x in m.pictures]
+        elif isinstance(m, (mutagen.oggvorbis.OggVorbis, mutagen.oggopus.OggOpus)):
+            # Ogg files store FLAC picture blocks as base64 in the METADATA_BLOCK_PICTURE comment.
+            for x in (m.tags or {}).get("metadata_block_picture", []):
+                with contextlib.suppress(ValueError, mutagen.flac.error):
+                    pic = mutagen.flac.Picture(base64.b64decode(x))
+                    pictures.append((pic.type, pic.data))
+        else:
+            raise RoseError(f"Impossible: unknown mutagen type: {type(m)=} ({repr(m)=})")
+
+        if not pictures:
+            return None
+        # Picture type 3 is the front cover in both ID3 and FLAC.
+        for type_, data in pictures:
+            if type_ == 3:
+                return data
+        return pictures[0][1]
+
     @no_type_check
     def flush(self, *, validate: bool = True) -> None:
         """Flush the current tags to the file on disk."""
@@ -271,13 +405,40 @@ class AudioTags:
             _write_tag_with_description("TXXX:ROSEID", self.id)
             _write_tag_with_description("TXXX:ROSERELEASEID", self.release_id)
             _write_standard_tag("TIT2", self.title)
+            _write_standard_tag("TIT3", self.subtitle)
             _write_standard_tag("TDRC", str(self.year).zfill(4))
             _write_standard_tag("TRCK", self.tracknumber)
             _write_standard_tag("TPOS", self.discnumber)
+            _write_standard_tag("TSST", self.discsubtitle)
             _write_standard_tag("TALB", self.album)
             _write_standard_tag("TCON", ";".join(self.genre))
             _write_standard_tag("TPUB", ";".join(self.label))
             _write_tag_with_description("TXXX:RELEASETYPE", self.releasetype)
+            _write_tag_with_description("TXXX:ITUNESGAPLESS", "1" if self.gapless else None)
+            _write_tag_with_description(
+                "TXXX:ITUNESADVISORY",
+                str(ITUNES_ADVISORY_VALUES[self.advisory]) if self.advisory else None,
+            )
+            _write_standard_tag("TSRC", self.isrc)
+            m.tags.delall("PCNT")
+            if self.play_count:
+                m.tags.add(mutagen.id3.PCNT(count=self.play_count))
+            _write_tag_with_description(
+                "TXXX:SKIPCOUNT", str(self.skip_count) if self.skip_count else None
+            )
+            _write_standard_tag("TSSE", self.encoder)
+            _write_standard_tag("TENC", self.encoded_by)
+            # Like the TXXX tags, only replace the comment with an empty description, and preserve
+            # the language of the existing comment.
+            comments = m.tags.getall("COMM")
+            comment_lang = next((f.lang for f in comments if f.desc == ""), "eng")
+            m.tags.delall("COMM")
+            if self.comment:
+                frame = mutagen.id3.COMM(encoding=3, lang=comment_lang, desc="", text=self.comment)
+                m.tags.add(frame)
+            for f in comments:
+                if f.desc != "":
+                    m.tags.add(f)
             _write_standard_tag("TPE2", format_artist_string(self.albumartists))
             _write_standard_tag("TPE1", format_artist_string(self.trackartists))
             # Wipe the alt. role artist tags, since we encode the full artist into the main tag.
@@ -296,11 +457,53 @@ class AudioTags:
             m.tags["----:net.sunsetglow.rose:ID"] = (self.id or "").encode()
             m.tags["----:net.sunsetglow.rose:RELEASEID"] = (self.release_id or "").encode()
             m.tags["\xa9nam"] = self.title or ""
+            if self.subtitle:
+                m.tags["----:com.apple.iTunes:SUBTITLE"] = self.subtitle.encode()
+            else:
+                with contextlib.suppress(KeyError):
+                    del m.tags["----:com.apple.iTunes:SUBTITLE"]
             m.tags["\xa9day"] = str(self.year).zfill(4)
+            if self.discsubtitle:
+                m.tags["----:com.apple.iTunes:DISCSUBTITLE"] = self.discsubtitle.encode()
+            else:
+                with contextlib.suppress(KeyError):
+                    del m.tags["----:com.apple.iTunes:DISCSUBTITLE"]
             m.tags["\xa9alb"] = self.album or ""
             m.tags["\xa9gen"] = ";".join(self.genre)
             m.tags["----:com.apple.iTunes:LABEL"] = ";".join(self.label).encode()
             m.tags["----:com.apple.iTunes:RELEASETYPE"] = self.releasetype.encode()
+            if self.gapless:
+                m.tags["pgap"] = True
+            else:
+                with contextlib.suppress(KeyError):
+                    del m.tags["pgap"]
+            if self.advisory:
+                m.tags["rtng"] = [ITUNES_ADVISORY_VALUES[self.advisory]]
+            else:
+                with contextlib.suppress(KeyError):
+                    del m.tags["rtng"]
+            if self.isrc:
+                m.tags["----:com.apple.iTunes:ISRC"] = self.isrc.encode()
+            else:
+                with contextlib.suppress(KeyError):
+                    del m.tags["----:com.apple.iTunes:ISRC"]
+            for key, count in [("PLAYCOUNT", self.play_count), ("SKIPCOUNT", self.skip_count)]:
+                if count:
+                    m.tags[f"----:com.apple.iTunes:{key}"] = str(count).encode()
+                else:
+                    with contextlib.suppress(KeyError):
+                        del m.tags[f"----:com.apple.iTunes:{key}"]
+            for key, value in [("\xa9too", self.encoder), ("\xa9enc", self.encoded_by)]:
+                if value:
+                    m.tags[key] = value
+                else:
+                    with contextlib.suppress(KeyError):
+                        del m.tags[key]
+            if self.comment:
+                m.tags["\xa9cmt"] = self.comment
+            else:
+                with contextlib.suppress(KeyError):
+                    del m.tags["\xa9cmt"]
             m.tags["aART"] = format_artist_string(self.albumartists)
             m.tags["\xa9ART"] = format_artist_string(self.trackartists)
             # Wipe the alt. role artist tags, since we encode the full artist into the main tag.
@@ -350,13 +553,57 @@ class AudioTags:
             m.tags["roseid"] = self.id or ""
             m.tags["rosereleaseid"] = self.release_id or ""
             m.tags["title"] = self.title or ""
+            if self.subtitle:
+                m.tags["subtitle"] = self.subtitle
+            else:
+                with contextlib.suppress(KeyError):
+                    del m.tags["subtitle"]
             m.tags["date"] = str(self.year).zfill(4)
             m.tags["tracknumber"] = self.tracknumber or ""
             m.tags["discnumber"] = self.discnumber or ""
+            if self.discsubtitle:
+                m.tags["discsubtitle"] = self.discsubtitle
+            else:
+                with contextlib.suppress(KeyError):
+                    del m.tags["discsubtitle"]
             m.tags["album"] = self.album or ""
             m.tags["genre"] = ";".join(self.genre)
             m.tags["organization"] = ";".join(self.label)
             m.tags["releasetype"] = self.releasetype
+            if self.gapless:
+                m.tags["itunesgapless"] = "1"
+            else:
+                with contextlib.suppress(KeyError):
+                    del m.tags["itunesgapless"]
+            if self.advisory:
+                m.tags["itunesadvisory"] = str(ITUNES_ADVISORY_VALUES[self.advisory])
+            else:
+                with contextlib.suppress(KeyError):
+                    del m.tags["itunesadvisory"]
+            if self.isrc:
+                m.tags["isrc"] = self.isrc
+            else:
+                with contextlib.suppress(KeyError):
+                    del m.tags["isrc"]
+            for key, count in [("playcount", self.play_count), ("skipcount", self.skip_count)]:
+                if count:
+                    m.tags[key] = str(count)
+                else:
+                    with contextlib.suppress(KeyError):
+                        del m.tags[key]
+            for key, value in [("encoder", self.encoder), ("encodedby", self.encoded_by)]:
+                if value:
+                    m.tags[key] = value
+                else:
+                    with contextlib.suppress(KeyError):
+                        del m.tags[key]
+            with contextlib.suppress(KeyError):
+                del m.tags["encoded-by"]
+            if self.comment:
+                m.tags["comment"] = self.comment
+            else:
+                with contextlib.suppress(KeyError):
+                    del m.tags["comment"]
             m.tags["albumartist"] = format_artist_string(self.albumartists)
             m.tags["artist"] = format_artist_string(self.trackartists)
             # Wipe the alt. role artist tags, since we encode the full artist into the main tag.
@@ -376,6 +623,248 @@ class AudioTags:
         raise RoseError(f"Impossible: unknown mutagen type: {type(m)=} ({repr(m)=})")
 
 
+def embedded_cover_art_ext(data: bytes) -> str:
+    """
+    Detect the file extension of embedded cover art from its magic bytes. Nearly all embedded cover
+    art is JPEG or PNG, so anything unrecognized is assumed to be JPEG.
+    """
+    if data.startswith(b"\x89PNG"):
+        return "png"
+    if data.startswith(b"GIF8"):
+        return "gif"
+    if data[:4] == b"RIFF" and data[8:12] == b"WEBP":
+        return "webp"
+    return "jpg"
+
+
+def compute_audio_sha256(p: Path) -> str:
+    """
+    Compute a sha256 checksum of the audio stream of an audio file. The tags are excluded from the
+    checksum, so that tag edits do not change it, as is the Xing/Info header frame of MP3 files.
+    Note that we checksum the encoded audio stream and do not decode it, so two different encodes
+    of the same recording have different checksums.
+    """
+    if not is_supported_audio_file(p):
+        raise UnsupportedFiletypeError(f"{p.suffix} not a supported filetype")
+    suffix = p.suffix.lower()
+    h = hashlib.sha256()
+    with p.open("rb") as fp:
+        if suffix == ".mp3":
+            _skip_id3v2(fp)
+            _skip_mp3_info_frame(fp)
+            _hash_range(fp, h, _id3v1_start(fp) - fp.tell())
+        elif suffix == ".flac":
+            _skip_id3v2(fp)
+            if fp.read(4) != b"fLaC":
+                raise UnsupportedFiletypeError(f"Failed to open file: {p} is not a valid FLAC file")
+            # Skip the metadata blocks. Each block header has a last-block flag in its first bit and
+            # the block length in the next three bytes.
+            while True:
+                header = fp.read(4)
+                if len(header) != 4:
+                    raise UnsupportedFiletypeError(f"Failed to open file: {p} is truncated")
+                fp.seek(int.from_bytes(header[1:4], "big"), os.SEEK_CUR)
+                if header[0] & 0x80:
+                    break
+            _hash_range(fp, h, _id3v1_start(fp) - fp.tell())
+        elif suffix == ".m4a":
+            # The audio data lives in the top-level mdat atoms; the tags live in the moov atom.
+            while header := fp.read(8):
+                size, headersize = int.from_bytes(header[:4], "big"), 8
+                if size == 1:
+                    size, headersize = int.from_bytes(fp.read(8), "big"), 16
+                if len(header) != 8 or (size != 0 and size < headersize):
+                    raise UnsupportedFiletypeError(f"Failed to open file: {p} has a malformed atom")
+                # A size of zero means that the atom extends to the end of the file.
+                length = size - headersize if size != 0 else None
+                if header[4:8] == b"mdat":
+                    _hash_range(fp, h, length)
+                elif length is None:
+                    break
+                else:
+                    fp.seek(length, os.SEEK_CUR)
+        else:
+            # Ogg streams begin with header packets (which contain the tags) followed by the audio
+            # packets. Vorbis has three header packets and Opus has two.
+            num_headers: int | None = None
+            packetno = 0
+            while True:
+                try:
+                    page = mutagen.ogg.OggPage(fp)
+                except EOFError:
+                    break
+                if num_headers is None:
+                    is_opus = bool(page.packets) and page.packets[0].startswith(b"OpusHead")
+                    num_headers = 2 if is_opus else 3
+                for i, packet in enumerate(page.packets):
+                    if packetno >= num_headers:
+                        h.update(packet)
+                    # The last packet on an incomplete page continues onto the next page.
+                    if i < len(page.packets) - 1 or page.complete:
+                        packetno += 1
+    return h.hexdigest()
+
+
+# Durations longer than this are assumed to be misreported.
+MAX_SANE_DURATION_SEC = 24 * 60 * 60
+
+# fmt: off
+# Bitrates in kbps, indexed by [is MPEG-1][layer][bitrate index]. Index 0 is the "free" bitrate,
+# which we cannot compute frame lengths for.
+MP3_BITRATES = {
+    True: {
+        1: [0, 32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448],
+        2: [0, 32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384],
+        3: [0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320],
+    },
+    False: {
+        1: [0, 32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256],
+        2: [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+        3: [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160],
+    },
+}
+# Sample rates in Hz, indexed by the header's version bits.
+MP3_SAMPLE_RATES = {
+    0: [11025, 12000, 8000],  # MPEG-2.5
+    2: [22050, 24000, 16000],  # MPEG-2
+    3: [44100, 48000, 32000],  # MPEG-1
+}
+# fmt: on
+
+
+def _mp3_duration_sec(p: Path, reported: float) -> int:
+    """
+    Mutagen estimates the duration of a VBR MP3 without a Xing/VBRI header from the bitrate of its
+    first frame, which can be zero or wildly off. In that case, compute the duration by scanning
+    the frame headers instead.
+    """
+    if round(reported) > 0 and reported <= MAX_SANE_DURATION_SEC:
+        return round(reported)
+    scanned = _scan_mp3_duration(p)
+    logger.debug(f"Scanned MP3 frames of {p} for duration: reported {reported}, scanned {scanned}")
+    if scanned is None:
+        return round(reported) if reported <= MAX_SANE_DURATION_SEC else 0
+    return round(scanned)
+
+
+def _scan_mp3_duration(p: Path) -> float | None:
+    """
+    Compute the duration of an MP3 file by summing the samples of every MPEG audio frame. Returns
+    None if no frames were found.
+    """
+    with p.open("rb") as fp:
+        _skip_id3v2(fp)
+        data = fp.read(_id3v1_start(fp) - fp.tell())
+
+    duration = 0.0
+    found = False
+    pos = 0
+    while pos + 4 <= len(data):
+        frame = _parse_mp3_frame_header(data[pos : pos + 4])
+        # Resynchronize byte-by-byte if this is not a valid frame header.
+        if frame is None:
+            pos += 1
+            continue
+        framelen, samples, samplerate = frame
+        duration += samples / samplerate
+        found = True
+        pos += framelen
+    return duration if found else None
+
+
+def _parse_mp3_frame_header(header: bytes) -> tuple[int, int, int] | None:
+    """
+    Parse a 4-byte MPEG audio frame header into the frame's (length in bytes, sample count, sample
+    rate). Returns None if the bytes are not a valid frame header.
+    """
+    b1, b2 = header[1], header[2]
+    version, layer = (b1 >> 3) & 0x3, 4 - ((b1 >> 1) & 0x3)
+    bitrate_idx, samplerate_idx = b2 >> 4, (b2 >> 2) & 0x3
+    if (
+        header[0] != 0xFF
+        or b1 & 0xE0 != 0xE0
+        or version == 1
+        or layer == 4
+        or bitrate_idx in (0, 15)
+        or samplerate_idx == 3
+    ):
+        return None
+    mpeg1 = version == 3
+    bitrate = MP3_BITRATES[mpeg1][layer][bitrate_idx] * 1000
+    samplerate = MP3_SAMPLE_RATES[version][samplerate_idx]
+    padding = (b2 >> 1) & 0x1
+    if layer == 1:
+        samples = 384
+        framelen = (12 * bitrate // samplerate + padding) * 4
+    else:
+        samples = 1152 if layer == 2 or mpeg1 else 576
+        framelen = samples // 8 * bitrate // samplerate + padding
+    return framelen, samples, samplerate
+
+
+def _skip_mp3_info_frame(fp: BinaryIO) -> None:
+    """
+    Seek past the Xing/Info or VBRI header frame at the current position of the file, if there is
+    one. The header frame carries no audio, only stream metadata (such as the frame count and the
+    LAME encoder tag) that tools like mp3val rewrite without touching the audio.
+    """
+    pos = fp.tell()
+    data = fp.read(4)
+    frame = _parse_mp3_frame_header(data) if len(data) == 4 else None
+    if frame is None:
+        fp.seek(pos)
+        return
+    framelen = frame[0]
+    data += fp.read(framelen - 4)
+    # The Xing/Info tag follows the side information, whose size depends on the MPEG version and
+    # whether the stream is mono. The VBRI tag is always at a fixed offset.
+    mpeg1, mono, crc = (data[1] >> 3) & 0x3 == 3, data[3] >> 6 == 3, not data[1] & 0x1
+    sideinfo = (17 if mono else 32) if mpeg1 else (9 if mono else 17)
+    xing = 4 + (2 if crc else 0) + sideinfo
+    if data[xing : xing + 4] in (b"Xing", b"Info") or data[36:40] == b"VBRI":
+        fp.seek(pos + framelen)
+    else:
+        fp.seek(pos)
+
+
+def _skip_id3v2(fp: BinaryIO) -> None:
+    """Seek past any ID3v2 tags at the current position of the file."""
+    while True:
+        header = fp.read(10)
+        if len(header) != 10 or header[:3] != b"ID3":
+            fp.seek(-len(header), os.SEEK_CUR)
+            return
+        # The tag size is a 28-bit "syncsafe" integer: the high bit of each byte is unset.
+        size = 0
+        for b in header[6:10]:
+            size = (size << 7) | (b & 0x7F)
+        footersize = 10 if header[5] & 0x10 else 0
+        fp.seek(size + footersize, os.SEEK_CUR)
+
+
+def _id3v1_start(fp: BinaryIO) -> int:
+    """Return the offset of the trailing ID3v1 tag, or the file size if there is no ID3v1 tag."""
+    pos = fp.tell()
+    end = fp.seek(0, os.SEEK_END)
+    if end - pos >= 128:
+        fp.seek(-128, os.SEEK_END)
+        if fp.read(3) == b"TAG":
+            end -= 128
+    fp.seek(pos)
+    return end
+
+
+def _hash_range(fp: BinaryIO, h: Any, length: int | None) -> None:
+    """Feed the next `length` bytes of the file into the hash. Pass None to read to the end."""
+    while length is None or length > 0:
+        chunk = fp.read(1 << 20 if length is None else min(length, 1 << 20))
+        if not chunk:
+            return
+        h.update(chunk)
+        if length is not None:
+            length -= len(chunk)
+
+
 def _split_tag(t: str | None) -> list[str]:
     return TAG_SPLITTER_REGEX.split(t) if t else []
 
@@ -433,7 +922,16 @@ def _parse_int(x: str | None) -> int | None:
         return None
 
 
+def _parse_bool(x: str | None) -> bool:
+    return x is not None and x.strip().lower() in ["1", "true", "yes"]
+
+
 def _parse_year(value: str | None) -> int | None:
+    """
+    Parse the year out of a date tag. Rose only stores the year, so the year is returned from any
+    date that leads with one, even if the month or day is missing. Dates without a year, such as
+    `--02-05`, return None rather than a guessed year.
+    """
     if not value:
         return None
     if YEAR_REGEX.match(value):
@@ -455,7 +953,27 @@ def parse_artist_string(
     conductor: str | None = None,
     producer: str | None = None,
     dj: str | None = None,
+    fallback_only: bool = False,
 ) -> ArtistMapping:
+    """
+    Parse the artist tags of a file into an ArtistMapping. The role-specific tags are passed as
+    keyword arguments. The `main` tag may also encode the other roles in a single string, which is
+    how many files without role-specific tags store their artists:
+
+    - `A feat. B`: B is a guest.
+    - `A remixed by B`: B is a remixer.
+    - `A produced by B`: B is a producer.
+    - `A pres. B`: A is a DJ mixer and B the main artist.
+    - `A performed by B`: A is a composer and B the main artist.
+
+    These combine in the order written by `format_artist_string`, e.g.
+    `A pres. B performed by C feat. D remixed by E produced by F`. Multiple artists within a role
+    are separated by ` \\\\ `, ` / `, `;`, or ` vs. `. Artists parsed from the `main` string are
+    merged with those from the role-specific tags. If `fallback_only` is set, the roles are only
+    parsed out of the `main` string when none of the role-specific tags are set; otherwise, the
+    `main` string is only split into main artists.
+    """
+
     def _split_tag(t: str | None) -> list[str]:
         return TAG_SPLITTER_REGEX.split(t) if t else []
 
@@ -465,6 +983,9 @@ def parse_artist_string(
     li_composer = _split_tag(composer)
     li_producer = _split_tag(producer)
     li_dj = _split_tag(dj)
+    if fallback_only and any([remixer, composer, conductor, producer, dj]):
+        li_main.extend(_split_tag(main))
+        main = None
     if main and "produced by " in main:
         main, producer = re.split(r" ?produced by ", main, maxsplit=1)
         li_producer.extend(_split_tag(producer))
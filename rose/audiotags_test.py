@@ -1,13 +1,21 @@
 import shutil
 from pathlib import Path
 
+import mutagen
+import mutagen.id3
+import mutagen.mp4
 import pytest
 
 from conftest import TEST_TAGGER
 from rose.audiotags import (
     AudioTags,
     UnsupportedTagValueTypeError,
+    _mp3_duration_sec,
+    _parse_year,
+    _scan_mp3_duration,
     _split_tag,
+    compute_audio_sha256,
+    embedded_cover_art_ext,
     format_artist_string,
     parse_artist_string,
 )
@@ -119,6 +127,374 @@ def test_id_assignment(isolated_dir: Path, filename: str) -> None:
     assert af.release_id == "bahaha"
 
 
+@pytest.mark.parametrize(
+    "filename",
+    ["track1.flac", "track2.m4a", "track3.mp3", "track4.vorbis.ogg", "track5.opus.ogg"],
+)
+def test_gapless_roundtrip(isolated_dir: Path, filename: str) -> None:
+    fpath = isolated_dir / filename
+    shutil.copyfile(TEST_TAGGER / filename, fpath)
+
+    af = AudioTags.from_file(fpath)
+    assert not af.gapless
+    af.gapless = True
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.gapless
+    af.gapless = False
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert not af.gapless
+
+
+@pytest.mark.parametrize(
+    "filename",
+    ["track1.flac", "track2.m4a", "track3.mp3", "track4.vorbis.ogg", "track5.opus.ogg"],
+)
+def test_advisory_roundtrip(isolated_dir: Path, filename: str) -> None:
+    fpath = isolated_dir / filename
+    shutil.copyfile(TEST_TAGGER / filename, fpath)
+
+    af = AudioTags.from_file(fpath)
+    assert af.advisory is None
+    af.advisory = "explicit"
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.advisory == "explicit"
+    af.advisory = "clean"
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.advisory == "clean"
+    af.advisory = None
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.advisory is None
+
+
+@pytest.mark.parametrize(
+    "filename",
+    ["track1.flac", "track2.m4a", "track3.mp3", "track4.vorbis.ogg", "track5.opus.ogg"],
+)
+def test_isrc_roundtrip(isolated_dir: Path, filename: str) -> None:
+    fpath = isolated_dir / filename
+    shutil.copyfile(TEST_TAGGER / filename, fpath)
+
+    af = AudioTags.from_file(fpath)
+    assert af.isrc is None
+    af.isrc = "USRC17607839"
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.isrc == "USRC17607839"
+    af.isrc = None
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.isrc is None
+
+
+@pytest.mark.parametrize(
+    "filename",
+    ["track1.flac", "track2.m4a", "track3.mp3", "track4.vorbis.ogg", "track5.opus.ogg"],
+)
+def test_play_count_roundtrip(isolated_dir: Path, filename: str) -> None:
+    fpath = isolated_dir / filename
+    shutil.copyfile(TEST_TAGGER / filename, fpath)
+
+    af = AudioTags.from_file(fpath)
+    assert af.play_count is None
+    assert af.skip_count is None
+    af.play_count = 42
+    af.skip_count = 3
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.play_count == 42
+    assert af.skip_count == 3
+    # The counts are preserved when other tags are changed.
+    af.title = "Changed"
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.play_count == 42
+    assert af.skip_count == 3
+
+
+def test_play_count_itunes(isolated_dir: Path) -> None:
+    fpath = isolated_dir / "track2.m4a"
+    shutil.copyfile(TEST_TAGGER / "track2.m4a", fpath)
+    m = mutagen.mp4.MP4(fpath)
+    m.tags["----:com.apple.iTunes:PLAYCOUNT"] = b"17"
+    m.save()
+    assert AudioTags.from_file(fpath).play_count == 17
+
+
+@pytest.mark.parametrize(
+    ("filename", "has_cover_art"),
+    [
+        ("track1.flac", False),
+        ("track2.m4a", True),
+        ("track3.mp3", True),
+        ("track4.vorbis.ogg", False),
+        ("track5.opus.ogg", False),
+    ],
+)
+def test_embedded_cover(filename: str, has_cover_art: bool) -> None:
+    art = AudioTags.from_file(TEST_TAGGER / filename).embedded_cover()
+    if has_cover_art:
+        assert art is not None
+        assert art.startswith(b"\x89PNG")
+        assert embedded_cover_art_ext(art) == "png"
+    else:
+        assert art is None
+
+
+def test_mp3_duration_vbr_fallback(isolated_dir: Path) -> None:
+    # Write a VBR MPEG-1 Layer III stream of alternating 128kbps and 320kbps frames at 44.1kHz.
+    # Each frame holds 1152 samples, so 766 frames last 766 * 1152 / 44100 = ~20.01 seconds.
+    frames = b""
+    for i in range(766):
+        bitrate_idx, bitrate = (9, 128000) if i % 2 == 0 else (14, 320000)
+        framelen = 144 * bitrate // 44100
+        frames += bytes([0xFF, 0xFB, bitrate_idx << 4, 0x00]) + b"\x00" * (framelen - 4)
+    fpath = isolated_dir / "vbr.mp3"
+    fpath.write_bytes(frames)
+
+    assert _scan_mp3_duration(fpath) == pytest.approx(766 * 1152 / 44100)
+    # A zero or absurd reported duration falls back to the scanned duration.
+    assert _mp3_duration_sec(fpath, 0.0) == 20
+    assert _mp3_duration_sec(fpath, 1e9) == 20
+    # A sane reported duration is trusted.
+    assert _mp3_duration_sec(fpath, 183.4) == 183
+
+
+def test_advisory_m4a_rtng_atom(isolated_dir: Path) -> None:
+    fpath = isolated_dir / "track2.m4a"
+    shutil.copyfile(TEST_TAGGER / "track2.m4a", fpath)
+
+    # The legacy explicit value is read as explicit too.
+    m = mutagen.mp4.MP4(fpath)
+    m.tags["rtng"] = [4]
+    m.save()
+    af = AudioTags.from_file(fpath)
+    assert af.advisory == "explicit"
+
+    af.advisory = "clean"
+    af.flush()
+    m = mutagen.mp4.MP4(fpath)
+    assert m.tags["rtng"] == [2]
+
+
+@pytest.mark.parametrize(
+    "filename",
+    ["track1.flac", "track2.m4a", "track3.mp3", "track4.vorbis.ogg", "track5.opus.ogg"],
+)
+def test_encoder_roundtrip(isolated_dir: Path, filename: str) -> None:
+    fpath = isolated_dir / filename
+    shutil.copyfile(TEST_TAGGER / filename, fpath)
+
+    af = AudioTags.from_file(fpath)
+    af.encoder = "LAME 3.100"
+    af.encoded_by = "Rose Encoding Co."
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.encoder == "LAME 3.100"
+    assert af.encoded_by == "Rose Encoding Co."
+
+    # Check that the encoder tags are preserved when other tags are edited.
+    af.title = "New Title"
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.encoder == "LAME 3.100"
+    assert af.encoded_by == "Rose Encoding Co."
+
+    # Check that unsetting the encoder tags removes them rather than writing empty tags.
+    af.encoder = None
+    af.encoded_by = None
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.encoder is None
+    assert af.encoded_by is None
+
+
+@pytest.mark.parametrize(
+    "filename",
+    ["track1.flac", "track2.m4a", "track3.mp3", "track4.vorbis.ogg", "track5.opus.ogg"],
+)
+def test_comment_roundtrip(isolated_dir: Path, filename: str) -> None:
+    fpath = isolated_dir / filename
+    shutil.copyfile(TEST_TAGGER / filename, fpath)
+
+    af = AudioTags.from_file(fpath)
+    assert af.comment == "A Good Album ~reviewer"
+    af.comment = "Ripped from the 2009 vinyl reissue"
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.comment == "Ripped from the 2009 vinyl reissue"
+
+    # Check that the comment is preserved when other tags are edited.
+    af.title = "New Title"
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.comment == "Ripped from the 2009 vinyl reissue"
+
+    af.comment = None
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.comment is None
+
+
+@pytest.mark.parametrize(
+    "filename",
+    ["track1.flac", "track2.m4a", "track3.mp3", "track4.vorbis.ogg", "track5.opus.ogg"],
+)
+def test_subtitle_roundtrip(isolated_dir: Path, filename: str) -> None:
+    fpath = isolated_dir / filename
+    shutil.copyfile(TEST_TAGGER / filename, fpath)
+
+    af = AudioTags.from_file(fpath)
+    assert af.subtitle is None
+    af.subtitle = "Radio Edit"
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.subtitle == "Radio Edit"
+
+    # Check that the subtitle is preserved when other tags are edited.
+    af.title = "New Title"
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.subtitle == "Radio Edit"
+
+    af.subtitle = None
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.subtitle is None
+
+
+@pytest.mark.parametrize(
+    "filename",
+    ["track1.flac", "track2.m4a", "track3.mp3", "track4.vorbis.ogg", "track5.opus.ogg"],
+)
+def test_multiple_labels_roundtrip(isolated_dir: Path, filename: str) -> None:
+    fpath = isolated_dir / filename
+    shutil.copyfile(TEST_TAGGER / filename, fpath)
+
+    af = AudioTags.from_file(fpath)
+    af.label = ["A Cool Label", "Another Label"]
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.label == ["A Cool Label", "Another Label"]
+
+
+@pytest.mark.parametrize("filename", ["track1.flac", "track2.m4a", "track3.mp3"])
+def test_multiple_label_frames(isolated_dir: Path, filename: str) -> None:
+    """Test that labels written by other programs as one frame per value are all read."""
+    fpath = isolated_dir / filename
+    shutil.copyfile(TEST_TAGGER / filename, fpath)
+
+    m = mutagen.File(fpath)  # type: ignore
+    if filename.endswith(".mp3"):
+        m.tags.delall("TPUB")
+        m.tags.add(mutagen.id3.TPUB(text=["A Cool Label", "Another Label"]))
+    elif filename.endswith(".m4a"):
+        m.tags["----:com.apple.iTunes:LABEL"] = [b"A Cool Label", b"Another Label"]
+    else:
+        m.tags["organization"] = ["A Cool Label", "Another Label"]
+    m.save()
+
+    af = AudioTags.from_file(fpath)
+    assert af.label == ["A Cool Label", "Another Label"]
+
+
+def test_comment_mp3_preserves_described_comments(isolated_dir: Path) -> None:
+    fpath = isolated_dir / "track3.mp3"
+    shutil.copyfile(TEST_TAGGER / "track3.mp3", fpath)
+    m = mutagen.File(fpath)  # type: ignore
+    m.tags.add(mutagen.id3.COMM(encoding=3, lang="eng", desc="iTunNORM", text="00000A"))
+    m.save()
+
+    af = AudioTags.from_file(fpath)
+    assert af.comment == "A Good Album ~reviewer"
+    af.comment = "hello"
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert af.comment == "hello"
+    # The described comment is untouched, and the language of the default comment is preserved.
+    m = mutagen.File(fpath)  # type: ignore
+    assert {(f.desc, f.lang, str(f)) for f in m.tags.getall("COMM")} == {
+        ("", "XXX", "hello"),
+        ("iTunNORM", "eng", "00000A"),
+    }
+
+
+@pytest.mark.parametrize(
+    "filename",
+    ["track1.flac", "track2.m4a", "track3.mp3", "track4.vorbis.ogg", "track5.opus.ogg"],
+)
+def test_albumartists_and_trackartists_are_distinct(isolated_dir: Path, filename: str) -> None:
+    """Test that the album artist tags and the artist tags land in their own mappings."""
+    fpath = isolated_dir / filename
+    shutil.copyfile(TEST_TAGGER / filename, fpath)
+    af = AudioTags.from_file(fpath)
+    af.albumartists = ArtistMapping(main=[Artist("Album Artist")])
+    af.trackartists = ArtistMapping(main=[Artist("Track Artist")])
+    af.flush()
+
+    af = AudioTags.from_file(fpath)
+    assert af.albumartists == ArtistMapping(main=[Artist("Album Artist")])
+    assert af.trackartists == ArtistMapping(main=[Artist("Track Artist")])
+
+
+def test_trackartists_do_not_fall_back_to_albumartists(isolated_dir: Path) -> None:
+    """Test that a file without artist tags is read as is and does not gain them on flush."""
+    fpath = isolated_dir / "track1.flac"
+    shutil.copyfile(TEST_TAGGER / "track1.flac", fpath)
+    m = mutagen.File(fpath)  # type: ignore
+    for key in ["artist", "remixer", "producer", "composer", "conductor", "djmixer"]:
+        if key in m.tags:
+            del m.tags[key]
+    m.save()
+
+    af = AudioTags.from_file(fpath)
+    assert af.albumartists.main == [Artist("Artist A"), Artist("Artist B")]
+    assert not af.trackartists.all
+    af.flush()
+    af = AudioTags.from_file(fpath)
+    assert not af.trackartists.all
+
+
+@pytest.mark.parametrize(
+    "filename",
+    ["track1.flac", "track2.m4a", "track3.mp3", "track4.vorbis.ogg", "track5.opus.ogg"],
+)
+def test_audio_sha256_ignores_tags(isolated_dir: Path, filename: str) -> None:
+    """Test that the audio checksum does not change when the tags are edited."""
+    fpath = isolated_dir / filename
+    shutil.copyfile(TEST_TAGGER / filename, fpath)
+    checksum = compute_audio_sha256(fpath)
+
+    af = AudioTags.from_file(fpath)
+    af.title = "A much, much longer title that should resize the tag block"
+    af.genre = ["Classical"]
+    af.flush()
+    assert compute_audio_sha256(fpath) == checksum
+
+
+def test_audio_sha256_ignores_mp3_info_frame(isolated_dir: Path) -> None:
+    """Test that the audio checksum skips the Xing/Info frame but covers the audio frames."""
+    fpath = isolated_dir / "track3.mp3"
+    shutil.copyfile(TEST_TAGGER / "track3.mp3", fpath)
+    checksum = compute_audio_sha256(fpath)
+
+    # Rewrite the frame count of the Info tag, as tools that repair the header frame do.
+    data = bytearray(fpath.read_bytes())
+    info = data.index(b"Info")
+    data[info + 8 : info + 12] = b"\x00\x00\x00\x01"
+    fpath.write_bytes(data)
+    assert compute_audio_sha256(fpath) == checksum
+
+    # The byte before the trailing ID3v1 tag is part of the audio frames.
+    data[-129] ^= 0xFF
+    fpath.write_bytes(data)
+    assert compute_audio_sha256(fpath) != checksum
+
+
 @pytest.mark.parametrize(
     "filename",
     ["track1.flac", "track2.m4a", "track3.mp3", "track4.vorbis.ogg", "track5.opus.ogg"],
@@ -147,6 +523,20 @@ def test_releasetype_normalization(isolated_dir: Path, filename: str) -> None:
     assert af.releasetype == "album"
 
 
+def test_parse_year() -> None:
+    assert _parse_year("2021") == 2021
+    assert _parse_year("2021-02-05") == 2021
+    assert _parse_year("2021-02-05T10:00:00") == 2021
+    # Partial dates with a year keep the year.
+    assert _parse_year("2021-05") == 2021
+    assert _parse_year("2021--05") == 2021
+    # Partial dates without a year are rejected.
+    assert _parse_year("--02-05") is None
+    assert _parse_year("02-05") is None
+    assert _parse_year("") is None
+    assert _parse_year(None) is None
+
+
 def test_split_tag() -> None:
     assert _split_tag(r"a \\ b") == ["a", "b"]
     assert _split_tag(r"a \ b") == [r"a \ b"]
@@ -174,6 +564,27 @@ def test_parse_artist_string() -> None:
         main=[Artist("B"), Artist("C")],
         guest=[Artist("D"), Artist("E")],
     )
+    assert parse_artist_string("A feat. B remixed by C produced by D;E") == ArtistMapping(
+        main=[Artist("A")],
+        guest=[Artist("B")],
+        remixer=[Artist("C")],
+        producer=[Artist("D"), Artist("E")],
+    )
+    # Role-specific tags are merged with the roles parsed from the main string.
+    assert parse_artist_string("A feat. B", remixer="C") == ArtistMapping(
+        main=[Artist("A")],
+        guest=[Artist("B")],
+        remixer=[Artist("C")],
+    )
+    # Unless the main string is only parsed as a fallback for files without role-specific tags.
+    assert parse_artist_string("A feat. B", remixer="C", fallback_only=True) == ArtistMapping(
+        main=[Artist("A feat. B")],
+        remixer=[Artist("C")],
+    )
+    assert parse_artist_string("A feat. B", fallback_only=True) == ArtistMapping(
+        main=[Artist("A")],
+        guest=[Artist("B")],
+    )
     # Test the deduplication handling.
     assert parse_artist_string("A pres. B", dj="A") == ArtistMapping(
         djmixer=[Artist("A")],
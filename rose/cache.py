@@ -33,24 +33,33 @@ import json
 import logging
 import math
 import multiprocessing
+import multiprocessing.pool
 import os
 import os.path
 import re
 import sqlite3
 import time
-from collections import Counter, defaultdict
-from collections.abc import Iterator
+import uuid
+from collections import Counter, OrderedDict, defaultdict
+from collections.abc import Callable, Iterator
 from dataclasses import dataclass
 from datetime import datetime
 from hashlib import sha256
 from pathlib import Path
-from typing import Any, TypeVar
+from typing import Any, Literal, TypeVar
 
+import PIL.Image
 import tomli_w
 import tomllib
 import uuid6
 
-from rose.audiotags import SUPPORTED_AUDIO_EXTENSIONS, AudioTags
+from rose.audiotags import (
+    AudioTags,
+    UnsupportedFiletypeError,
+    compute_audio_sha256,
+    embedded_cover_art_ext,
+    is_supported_audio_file,
+)
 from rose.common import (
     VERSION,
     Artist,
@@ -61,7 +70,12 @@ from rose.common import (
     uniq,
 )
 from rose.config import Config
-from rose.templates import artistsfmt, eval_release_template, eval_track_template
+from rose.templates import (
+    DEFAULT_ARTIST_ROLE_ORDER,
+    artistsfmt,
+    eval_release_template,
+    eval_track_template,
+)
 
 logger = logging.getLogger(__name__)
 
@@ -69,6 +83,15 @@ T = TypeVar("T")
 
 CACHE_SCHEMA_PATH = Path(__file__).resolve().parent / "cache.sql"
 
+# Forward migrations of the cache database, used when `cache_migration_mode` is `try_migrate`. Maps
+# the hash of an old `cache.sql` to the hash of the `cache.sql` it migrates to and the SQL script
+# that performs the migration. Only register migrations that leave the cache equivalent to a fresh
+# rebuild, e.g. additive columns and indexes.
+#
+# This is only the scaffolding: no migrations ship yet, so `try_migrate` currently always falls back
+# to a rebuild.
+CACHE_MIGRATIONS: dict[str, tuple[str, str]] = {}
+
 
 @contextlib.contextmanager
 def connect(c: Config) -> Iterator[sqlite3.Connection]:
@@ -107,7 +130,16 @@ def maybe_invalidate_cache_database(c: Config) -> None:
         "cache_dir": str(c.cache_dir),
         "cover_art_stems": c.cover_art_stems,
         "valid_art_exts": c.valid_art_exts,
+        "convert_unsupported_covers": c.convert_unsupported_covers,
+        "extract_embedded_covers": c.extract_embedded_covers,
         "ignore_release_directories": c.ignore_release_directories,
+        "combined_artist_tag_fallback": c.combined_artist_tag_fallback,
+        "derive_albumartist_from_tracks": c.derive_albumartist_from_tracks,
+        "va_artist_threshold": c.va_artist_threshold,
+        "compute_audio_sha256": c.compute_audio_sha256,
+        "min_track_duration_seconds": c.min_track_duration_seconds,
+        "collages_dirname": c.collages_dirname,
+        "playlists_dirname": c.playlists_dirname,
     }
     config_hash = sha256(json.dumps(config_hash_fields).encode()).hexdigest()
 
@@ -131,6 +163,17 @@ def maybe_invalidate_cache_database(c: Config) -> None:
             ):
                 # Everything matches! Exit!
                 return
+            if (
+                row
+                and c.cache_migration_mode == "try_migrate"
+                and row["schema_hash"] != schema_hash
+                and row["config_hash"] == config_hash
+                and _try_migrate_cache_database(conn, row["schema_hash"], schema_hash)
+            ):
+                conn.execute(
+                    "UPDATE _schema_hash SET schema_hash = ?, version = ?", (schema_hash, VERSION)
+                )
+                return
 
     c.cache_database_path.unlink(missing_ok=True)
     with connect(c) as conn:
@@ -152,8 +195,39 @@ def maybe_invalidate_cache_database(c: Config) -> None:
         )
 
 
+def _try_migrate_cache_database(conn: sqlite3.Connection, from_hash: str, to_hash: str) -> bool:
+    """
+    Apply the chain of registered migrations that leads from the `from_hash` schema to the
+    `to_hash` schema. Returns False if no chain exists or a migration fails, in which case the
+    caller should rebuild the cache.
+    """
+    scripts: list[str] = []
+    current = from_hash
+    while current != to_hash:
+        # Also bail on cycles, which can only exist from a faulty registry.
+        if current not in CACHE_MIGRATIONS or len(scripts) > len(CACHE_MIGRATIONS):
+            logger.debug(f"No cache migration registered from schema {current}")
+            return False
+        current, script = CACHE_MIGRATIONS[current]
+        scripts.append(script)
+    try:
+        for script in scripts:
+            conn.executescript(script)
+    except sqlite3.Error as e:
+        logger.info(f"Failed to migrate the cache database, rebuilding it instead: {e}")
+        return False
+    logger.info(f"Migrated the cache database from schema {from_hash} to {to_hash}")
+    return True
+
+
 @contextlib.contextmanager
-def lock(c: Config, name: str, timeout: float = 1.0) -> Iterator[None]:
+def lock(c: Config, name: str, timeout: float | None = None) -> Iterator[None]:
+    """
+    Acquire the named lock. Without an explicit timeout, the timeout configured for the lock's
+    class of operation (e.g. release, collage) is used.
+    """
+    if timeout is None:
+        timeout = _configured_lock_timeout(c, name)
     try:
         while True:
             with connect(c) as conn:
@@ -187,6 +261,16 @@ def lock(c: Config, name: str, timeout: float = 1.0) -> Iterator[None]:
             conn.execute("DELETE FROM locks WHERE name = ?", (name,))
 
 
+def _configured_lock_timeout(c: Config, name: str) -> float:
+    if name.startswith("release-"):
+        return c.lock_timeout_seconds.release
+    if name.startswith("collage-"):
+        return c.lock_timeout_seconds.collage
+    if name.startswith("playlist-"):
+        return c.lock_timeout_seconds.playlist
+    return 1.0
+
+
 def release_lock_name(release_id: str) -> str:
     return f"release-{release_id}"
 
@@ -199,18 +283,42 @@ def playlist_lock_name(playlist_name: str) -> str:
     return f"playlist-{playlist_name}"
 
 
+def mint_id(c: Config) -> str:
+    """Mint a new release or track ID of the configured UUID version."""
+    if c.id_uuid_version == 4:
+        return str(uuid.uuid4())
+    return str(uuid6.uuid7())
+
+
+def _format_added_at(c: Config, added_at: datetime) -> str:
+    """Format an `added_at` for a datafile in the configured format. Both formats sort correctly."""
+    if c.added_at_format == "date":
+        return added_at.date().isoformat()
+    return added_at.astimezone().replace(microsecond=0).isoformat()
+
+
 @dataclass(slots=True)
 class CachedRelease:
     id: str
     source_path: Path
     cover_image_path: Path | None
+    # A sha256 of the cover image file's bytes. Detects covers replaced in place.
+    cover_hash: str | None
+    # The size and mtime of the cover image file when it was hashed, so that unchanged covers are
+    # not rehashed.
+    cover_size: int | None
+    cover_mtime: str | None
     added_at: str  # ISO8601 timestamp
     datafile_mtime: str
     albumtitle: str
     releasetype: str
     year: int | None
     new: bool
+    # Hidden releases are excluded from browsing by default, without deleting their files.
+    hidden: bool
     disctotal: int
+    # A map of disc number to disc title, for discs whose tracks carry a disc subtitle.
+    disc_titles: dict[str, str]
     genres: list[str]
     labels: list[str]
     albumartists: ArtistMapping
@@ -222,13 +330,24 @@ class CachedRelease:
             id=row["id"],
             source_path=Path(row["source_path"]),
             cover_image_path=Path(row["cover_image_path"]) if row["cover_image_path"] else None,
+            cover_hash=row["cover_hash"],
+            cover_size=row["cover_size"],
+            cover_mtime=row["cover_mtime"],
             added_at=row["added_at"],
             datafile_mtime=row["datafile_mtime"],
             albumtitle=row["albumtitle"],
             releasetype=row["releasetype"],
             year=row["year"],
             disctotal=row["disctotal"],
+            disc_titles=dict(
+                zip(
+                    _split(row["disc_title_discnumbers"]),
+                    _split(row["disc_title_subtitles"]),
+                    strict=True,
+                )
+            ),
             new=bool(row["new"]),
+            hidden=bool(row["hidden"]),
             genres=_split(row["genres"]) if row["genres"] else [],
             labels=_split(row["labels"]) if row["labels"] else [],
             albumartists=_unpack_artists(
@@ -249,7 +368,9 @@ class CachedRelease:
             "releasetype": self.releasetype,
             "year": self.year,
             "new": self.new,
+            "hidden": self.hidden,
             "disctotal": self.disctotal,
+            "disc_titles": self.disc_titles,
             "genres": self.genres,
             "labels": self.labels,
             "albumartists": self.albumartists.dump(),
@@ -262,11 +383,18 @@ class CachedTrack:
     source_path: Path
     source_mtime: str
     tracktitle: str
+    tracksubtitle: str | None
     tracknumber: str
     tracktotal: int
     discnumber: str
+    discsubtitle: str | None
     disctotal: int
     duration_seconds: int
+    # Only computed if `compute_audio_sha256` is enabled.
+    audio_sha256: str | None
+    isrc: str | None
+    # The path of the file, relative to the release directory, before Rosé first renamed it.
+    original_filename: str | None
     trackartists: ArtistMapping
     metahash: str
 
@@ -281,11 +409,16 @@ class CachedTrack:
             source_path=Path(row["source_path"]),
             source_mtime=row["source_mtime"],
             tracktitle=row["tracktitle"],
+            tracksubtitle=row["tracksubtitle"],
             tracknumber=row["tracknumber"],
             tracktotal=row["tracktotal"],
             discnumber=row["discnumber"],
+            discsubtitle=row["discsubtitle"],
             disctotal=row["disctotal"],
             duration_seconds=row["duration_seconds"],
+            audio_sha256=row["audio_sha256"],
+            isrc=row["isrc"],
+            original_filename=row["original_filename"],
             trackartists=_unpack_artists(
                 c,
                 row["trackartist_names"],
@@ -301,9 +434,11 @@ class CachedTrack:
             "id": self.id,
             "source_path": str(self.source_path.resolve()),
             "tracktitle": self.tracktitle,
+            "tracksubtitle": self.tracksubtitle,
             "tracknumber": self.tracknumber,
             "tracktotal": self.tracktotal,
             "discnumber": self.discnumber,
+            "discsubtitle": self.discsubtitle,
             "disctotal": self.disctotal,
             "duration_seconds": self.duration_seconds,
             "trackartists": self.trackartists.dump(),
@@ -340,47 +475,251 @@ class CachedPlaylist:
     track_ids: list[str]
 
 
+@dataclass(slots=True)
+class CachedArtistSingles:
+    """
+    A synthetic "Singles" release that groups an artist's single and loose track releases for
+    browsing. It exists only in the read layer; the grouped releases are not moved on disk.
+    """
+
+    artist: str
+    releases: list[CachedRelease]
+    tracks: list[CachedTrack]
+
+
 @dataclass(slots=True)
 class StoredDataFile:
     new: bool
     added_at: str  # ISO8601 timestamp
+    hidden: bool = False
+    # The filenames, relative to the release directory, of the tracks renamed by
+    # `rename_source_files` before their first rename. Keyed by track ID. Stored here rather than
+    # only in the read cache so that they survive a cache rebuild.
+    original_filenames: dict[str, str] = dataclasses.field(default_factory=dict)
+
+    def dump(self) -> dict[str, Any]:
+        r = dataclasses.asdict(self)
+        # Keep the datafiles of never-renamed releases free of an empty table.
+        if not self.original_filenames:
+            del r["original_filenames"]
+        return r
 
 
 STORED_DATA_FILE_REGEX = re.compile(r"\.rose\.([^.]+)\.toml")
 
 
+def write_stored_data_file(path: Path, data: dict[str, Any]) -> bool:
+    """
+    Write a release's stored data file. The write is skipped if the serialized content is
+    byte-identical to the file on disk, so that the file's mtime (and any version control diff) only
+    changes when its content does. Returns whether the file was written.
+    """
+    content = tomli_w.dumps(data).encode()
+    with contextlib.suppress(FileNotFoundError):
+        if path.read_bytes() == content:
+            return False
+    with path.open("wb") as fp:
+        fp.write(content)
+    return True
+
+
+@dataclass
+class CacheUpdateReport:
+    """The changes made by a release cache update, or the changes that a dry run would make."""
+
+    # Stored data files that were created or rewritten.
+    written_datafiles: list[Path] = dataclasses.field(default_factory=list)
+    # Tracks that had their track and release IDs written to their tags.
+    tagged_tracks: list[Path] = dataclasses.field(default_factory=list)
+    # Source directories and files that were renamed, as (old path, new path) pairs.
+    renamed_paths: list[tuple[Path, Path]] = dataclasses.field(default_factory=list)
+    # Releases and tracks whose cache entries were inserted or updated.
+    updated_releases: list[Path] = dataclasses.field(default_factory=list)
+    updated_tracks: list[Path] = dataclasses.field(default_factory=list)
+    # Releases that were evicted from the cache.
+    evicted_releases: list[Path] = dataclasses.field(default_factory=list)
+    # Unsupported cover art files that were converted to JPEGs in the cache directory, as (source
+    # path, converted path) pairs.
+    converted_covers: list[tuple[Path, Path]] = dataclasses.field(default_factory=list)
+
+    def extend(self, other: CacheUpdateReport) -> None:
+        self.written_datafiles.extend(other.written_datafiles)
+        self.tagged_tracks.extend(other.tagged_tracks)
+        self.renamed_paths.extend(other.renamed_paths)
+        self.updated_releases.extend(other.updated_releases)
+        self.updated_tracks.extend(other.updated_tracks)
+        self.evicted_releases.extend(other.evicted_releases)
+        self.converted_covers.extend(other.converted_covers)
+
+
+FileKind = Literal["audio", "cover", "datafile", "ignored", "unsupported"]
+
+
+@dataclass
+class FileClassification:
+    """How the cache updater treats a file in a release directory, and why."""
+
+    path: Path
+    kind: FileKind
+    reason: str
+
+
+def classify_directory(c: Config, path: Path) -> list[FileClassification]:
+    """
+    Classify every file in a release directory the way the cache updater would. This is a debugging
+    aid for files that are unexpectedly not indexed, such as audio files with an unsupported
+    extension or cover art that is not named like the configured cover art stems.
+    """
+    path = path.resolve()
+    ignored_dir = (
+        path.name in (c.collages_dirname, c.playlists_dirname)
+        or path.name in c.ignore_release_directories
+    )
+    rval: list[FileClassification] = []
+    for root, subfiles in _walk_release_dir(c, path):
+        for sf in sorted(subfiles):
+            f = root / sf
+            if ignored_dir:
+                rval.append(FileClassification(f, "ignored", "the directory is not a release"))
+            elif STORED_DATA_FILE_REGEX.match(sf):
+                rval.append(FileClassification(f, "datafile", "matches .rose.{uuid}.toml"))
+            elif c.is_valid_cover_art(f):
+                rval.append(FileClassification(f, "cover", "matches a cover art filename"))
+            elif not is_supported_audio_file(f):
+                rval.append(FileClassification(f, "unsupported", "not a supported audio file"))
+            elif c.min_track_duration_seconds is None:
+                rval.append(FileClassification(f, "audio", "has a supported audio extension"))
+            else:
+                try:
+                    duration = AudioTags.from_file(f).duration_sec
+                except UnsupportedFiletypeError as e:
+                    rval.append(FileClassification(f, "unsupported", f"failed to read tags: {e}"))
+                    continue
+                if duration < c.min_track_duration_seconds:
+                    reason = f"shorter than min_track_duration_seconds ({duration}s)"
+                    rval.append(FileClassification(f, "ignored", reason))
+                else:
+                    rval.append(FileClassification(f, "audio", "has a supported audio extension"))
+    return rval
+
+
+def _list_release_dirs(c: Config) -> list[Path]:
+    """
+    List the directories in the music source directory. Symlinked directories are only listed if
+    `follow_symlinks` is enabled.
+    """
+    return [
+        Path(d.path)
+        for d in os.scandir(c.music_source_dir)
+        if d.is_dir(follow_symlinks=c.follow_symlinks)
+    ]
+
+
+def _walk_release_dir(c: Config, path: Path) -> Iterator[tuple[Path, list[str]]]:
+    """
+    Walk a release directory, yielding each directory with the names of the files in it. Symlinked
+    directories are followed if `follow_symlinks` is enabled. Each directory is walked at most once
+    by its resolved path, so that symlink cycles are not followed forever.
+    """
+    visited: set[str] = set()
+    for root, subdirs, subfiles in os.walk(str(path), followlinks=c.follow_symlinks):
+        resolved = os.path.realpath(root)
+        if resolved in visited:
+            subdirs.clear()
+            continue
+        visited.add(resolved)
+        yield Path(root), subfiles
+
+
 def update_cache(
     c: Config,
     force: bool = False,
     # For testing.
     force_multiprocessing: bool = False,
-) -> None:
+    dry_run: bool = False,
+) -> CacheUpdateReport:
     """
     Update the read cache to match the data for all releases in the music source directory. Delete
     any cached releases that are no longer present on disk.
+
+    If `dry_run` is true, detect the changes without making them: no datafiles are written, no tags
+    are flushed, no files are renamed, and the database is not written to. The collage and playlist
+    updates are skipped in a dry run. Returns a report of the release changes.
     """
-    update_cache_for_releases(c, None, force, force_multiprocessing=force_multiprocessing)
-    update_cache_evict_nonexistent_releases(c)
+    report = update_cache_for_releases(
+        c, None, force, force_multiprocessing=force_multiprocessing, dry_run=dry_run
+    )
+    report.evicted_releases.extend(update_cache_evict_nonexistent_releases(c, dry_run=dry_run))
+    if dry_run:
+        return report
     update_cache_for_collages(c, None, force)
     update_cache_evict_nonexistent_collages(c)
     update_cache_for_playlists(c, None, force)
     update_cache_evict_nonexistent_playlists(c)
+    return report
 
 
-def update_cache_evict_nonexistent_releases(c: Config) -> None:
+def update_cache_evict_nonexistent_releases(c: Config, dry_run: bool = False) -> list[Path]:
     logger.debug("Evicting cached releases that are not on disk")
-    dirs = [Path(d.path).resolve() for d in os.scandir(c.music_source_dir) if d.is_dir()]
+    dirs = [d.resolve() for d in _list_release_dirs(c)]
+    evicted: list[Path] = []
     with connect(c) as conn:
         cursor = conn.execute(
             f"""
-            DELETE FROM releases
+            {"SELECT source_path FROM releases" if dry_run else "DELETE FROM releases"}
             WHERE source_path NOT IN ({",".join(["?"] * len(dirs))})
-            RETURNING source_path
+            {"" if dry_run else "RETURNING source_path"}
             """,
             [str(d) for d in dirs],
         )
         for row in cursor:
-            logger.info(f"Evicted missing release {row['source_path']} from cache")
+            evicted.append(Path(row["source_path"]))
+            if not dry_run:
+                logger.info(f"Evicted missing release {row['source_path']} from cache")
+        if not dry_run:
+            conn.execute(
+                f"""
+                DELETE FROM short_tracks
+                WHERE release_source_path NOT IN ({",".join(["?"] * len(dirs))})
+                """,
+                [str(d) for d in dirs],
+            )
+    return evicted
+
+
+def update_cache_for_files(c: Config, paths: list[Path], force: bool = False) -> CacheUpdateReport:
+    """
+    Update the read cache for the releases that contain the passed-in files, without scanning the
+    rest of the music source directory. This is cheap enough to call on every filesystem event.
+
+    Releases whose directories no longer exist are evicted from the cache, and releases whose last
+    audio file was removed are deleted from the cache by `update_cache_for_releases`. Paths outside
+    of the music source directory are ignored.
+    """
+    # Do not resolve the paths, as that would move the files of symlinked releases out of the music
+    # source directory.
+    source_dir = Path(os.path.abspath(c.music_source_dir))
+    release_dirs: list[Path] = []
+    for p in paths:
+        try:
+            relpath = Path(os.path.abspath(p)).relative_to(source_dir)
+        except ValueError:
+            logger.debug(f"Skipping cache update for {p}: not in the music source directory")
+            continue
+        if relpath.parts:
+            release_dirs.append(source_dir / relpath.parts[0])
+    release_dirs = uniq(release_dirs)
+
+    # Pass only existing directories: an empty list would update every release.
+    existing_dirs = [
+        d for d in release_dirs if d.is_dir() and (c.follow_symlinks or not d.is_symlink())
+    ]
+    report = CacheUpdateReport()
+    if existing_dirs:
+        report = update_cache_for_releases(c, existing_dirs, force)
+    if len(existing_dirs) != len(release_dirs):
+        report.evicted_releases.extend(update_cache_evict_nonexistent_releases(c))
+    return report
 
 
 def update_cache_for_releases(
@@ -390,7 +729,11 @@ def update_cache_for_releases(
     force: bool = False,
     # For testing.
     force_multiprocessing: bool = False,
-) -> None:
+    dry_run: bool = False,
+    # Rename the source directories and files to match the path templates even if the releases
+    # are unchanged. Only has an effect if `rename_source_dirs` or `rename_source_files` is set.
+    force_rename: bool = False,
+) -> CacheUpdateReport:
     """
     Update the read cache to match the data for any passed-in releases. If a directory lacks a
     .rose.{uuid}.toml datafile, create the datafile for the release and set it to the initial state.
@@ -406,20 +749,20 @@ def update_cache_for_releases(
        if the read data differs from the previous caches.
 
     We also shard the directories across multiple processes and execute them simultaneously.
+
+    See `update_cache` for the behavior of `dry_run`.
     """
-    release_dirs = release_dirs or [
-        Path(d.path) for d in os.scandir(c.music_source_dir) if d.is_dir()
-    ]
+    release_dirs = release_dirs or _list_release_dirs(c)
     release_dirs = [
         d
         for d in release_dirs
-        if d.name != "!collages"
-        and d.name != "!playlists"
+        if d.name != c.collages_dirname
+        and d.name != c.playlists_dirname
         and d.name not in c.ignore_release_directories
     ]
     if not release_dirs:
         logger.debug("No-Op: No whitelisted releases passed into update_cache_for_releases")
-        return
+        return CacheUpdateReport()
     logger.debug(f"Refreshing the read cache for {len(release_dirs)} releases")
     if len(release_dirs) < 10:
         logger.debug(f"Refreshing cached data for {', '.join([r.name for r in release_dirs])}")
@@ -434,14 +777,16 @@ def update_cache_for_releases(
         logger.debug(
             f"Running cache update executor in same process because {len(release_dirs)=} < 50"
         )
-        _update_cache_for_releases_executor(c, release_dirs, force)
-        return
+        return _update_cache_for_releases_executor(
+            c, release_dirs, force, dry_run=dry_run, force_rename=force_rename
+        )
 
     # Batch size defaults to equal split across all processes. However, if the number of directories
-    # is small, we shrink the # of processes to save on overhead.
+    # is small, we shrink the # of processes to save on overhead. The number of processes never
+    # exceeds `max_proc`, so that users can cap the CPU usage of cache updates.
     num_proc = c.max_proc
     if len(release_dirs) < c.max_proc * 50:
-        num_proc = max(1, math.ceil(len(release_dirs) // 50))
+        num_proc = max(1, math.ceil(len(release_dirs) / 50))
     batch_size = len(release_dirs) // num_proc + 1
 
     manager = multiprocessing.Manager()
@@ -452,15 +797,16 @@ def update_cache_for_releases(
     playlists_to_force_update = manager.list()
 
     errors: list[BaseException] = []
+    results: list[multiprocessing.pool.AsyncResult[CacheUpdateReport]] = []
 
     logger.debug("Creating multiprocessing pool to parallelize cache executors.")
-    with multiprocessing.Pool(processes=c.max_proc) as pool:
+    with multiprocessing.Pool(processes=num_proc) as pool:
         # At 0, no batch. At 1, 1 batch. At 49, 1 batch. At 50, 1 batch. At 51, 2 batches.
         for i in range(0, len(release_dirs), batch_size):
             logger.debug(
                 f"Spawning release cache update process for releases [{i}, {i+batch_size})"
             )
-            pool.apply_async(
+            result = pool.apply_async(
                 _update_cache_for_releases_executor,
                 (
                     c,
@@ -468,19 +814,58 @@ def update_cache_for_releases(
                     force,
                     collages_to_force_update,
                     playlists_to_force_update,
+                    dry_run,
+                    force_rename,
                 ),
                 error_callback=lambda e: errors.append(e),
             )
+            results.append(result)
         pool.close()
         pool.join()
 
     if errors:
         raise ExceptionGroup("Exception occurred in cache update subprocesses", errors)  # type: ignore
 
+    report = CacheUpdateReport()
+    for result in results:
+        report.extend(result.get())
+
     if collages_to_force_update:
         update_cache_for_collages(c, uniq(list(collages_to_force_update)), force=True)
     if playlists_to_force_update:
         update_cache_for_playlists(c, uniq(list(playlists_to_force_update)), force=True)
+    return report
+
+
+def apply_template_renames(c: Config, dry_run: bool = False) -> list[tuple[Path, Path]]:
+    """
+    Rename every release directory and track file to match the source path templates, regardless of
+    the `rename_source_dirs` and `rename_source_files` settings and of whether the releases changed.
+    Name collisions are handled like in the cache updater. Returns the applied `(old, new)` renames.
+    """
+    c = dataclasses.replace(c, rename_source_dirs=True, rename_source_files=True)
+    report = update_cache_for_releases(c, force=True, dry_run=dry_run, force_rename=True)
+    return report.renamed_paths
+
+
+def wanted_release_dirname(c: Config, release: CachedRelease) -> str:
+    """Evaluate the source directory name that the release should have per the path templates."""
+    wanted_dirname = eval_release_template(
+        c.path_templates.source.release,
+        release,
+        artist_role_order=c.artist_role_order,
+    )
+    return sanitize_dirname(wanted_dirname, True)
+
+
+def wanted_track_filename(c: Config, track: CachedTrack) -> str:
+    """Evaluate the source filename that the track should have per the path templates."""
+    wanted_filename = eval_track_template(
+        c.path_templates.source.track,
+        track,
+        artist_role_order=c.artist_role_order,
+    )
+    return sanitize_filename(wanted_filename, True)
 
 
 def _update_cache_for_releases_executor(
@@ -493,25 +878,43 @@ def _update_cache_for_releases_executor(
     # good enough.
     collages_to_force_update_receiver: list[str] | None = None,
     playlists_to_force_update_receiver: list[str] | None = None,
-) -> None:
+    dry_run: bool = False,
+    force_rename: bool = False,
+) -> CacheUpdateReport:
     """The implementation logic, split out for multiprocessing."""
+    report = CacheUpdateReport()
     # First, call readdir on every release directory. We store the results in a map of
     # Path Basename -> (Release ID if exists, filenames).
     dir_scan_start = time.time()
     dir_tree: list[tuple[Path, str | None, list[Path]]] = []
     release_uuids: list[str] = []
+    scanned_dirs: set[Path] = set()
+    # The resolved paths of the release directories that were reached through a symlink.
+    symlinked_dirs: set[Path] = set()
     for rd in release_dirs:
         release_id = None
         files: list[Path] = []
         if not rd.is_dir():
             logger.debug(f"Skipping scanning {rd} because it is not a directory")
             continue
-        for root, _, subfiles in os.walk(str(rd)):
+        if rd.is_symlink() and not c.follow_symlinks:
+            logger.debug(f"Skipping scanning {rd} because it is a symlink")
+            continue
+        # Multiple symlinks can point to the same release directory: only scan it once. Symlinked
+        # releases are scanned by their resolved path, as that is the release's source path.
+        resolved_rd = rd.resolve()
+        if resolved_rd in scanned_dirs:
+            logger.debug(f"Skipping scanning {rd} because its target was already scanned")
+            continue
+        scanned_dirs.add(resolved_rd)
+        if rd.is_symlink():
+            symlinked_dirs.add(resolved_rd)
+        for root, subfiles in _walk_release_dir(c, resolved_rd if rd.is_symlink() else rd):
             for sf in subfiles:
                 if m := STORED_DATA_FILE_REGEX.match(sf):
                     release_id = m[1]
-                files.append(Path(root) / sf)
-        dir_tree.append((rd.resolve(), release_id, files))
+                files.append(root / sf)
+        dir_tree.append((resolved_rd, release_id, files))
         if release_id is not None:
             release_uuids.append(release_id)
     logger.debug(f"Release update source dir scan time {time.time() - dir_scan_start=}")
@@ -555,6 +958,24 @@ def _update_cache_for_releases_executor(
             )
             num_tracks_found += 1
         logger.debug(f"Found {num_tracks_found} tracks in cache")
+
+        # Map of source path -> (mtime, duration) of the cached audio files that are too short to
+        # be tracks.
+        cached_short_tracks: dict[str, tuple[str, int]] = {}
+        if c.min_track_duration_seconds is not None:
+            cursor = conn.execute(
+                rf"""
+                SELECT source_path, source_mtime, duration_seconds
+                FROM short_tracks
+                WHERE release_source_path IN ({','.join(['?']*len(dir_tree))})
+                """,
+                [str(source_path) for source_path, _, _ in dir_tree],
+            )
+            for row in cursor:
+                cached_short_tracks[row["source_path"]] = (
+                    row["source_mtime"],
+                    row["duration_seconds"],
+                )
     logger.debug(f"Release update cache read time {time.time() - cache_read_start=}")
 
     # Now iterate over all releases in the source directory. Leverage mtime from stat to determine
@@ -569,9 +990,14 @@ def _update_cache_for_releases_executor(
     upd_release_genre_args: list[list[Any]] = []
     upd_release_label_args: list[list[Any]] = []
     upd_unknown_cached_tracks_args: list[tuple[str, list[str]]] = []
+    upd_short_track_args: list[list[Any]] = []
+    seen_short_tracks: set[str] = set()
     upd_track_args: list[list[Any]] = []
     upd_track_ids: list[str] = []
     upd_track_artist_args: list[list[Any]] = []
+    # In a dry run, nothing is renamed, so the filesystem cannot tell us whether an earlier rename
+    # has already claimed a path. Track the claimed paths here instead.
+    dry_run_claimed_paths: set[Path] = set()
     for source_path, preexisting_release_id, files in dir_tree:
         logger.debug(f"Scanning release {source_path.name}")
         # Check to see if we should even process the directory. If the directory does not have
@@ -579,7 +1005,7 @@ def _update_cache_for_releases_executor(
         # it from the cache.
         first_audio_file: Path | None = None
         for f in files:
-            if f.suffix.lower() in SUPPORTED_AUDIO_EXTENSIONS:
+            if is_supported_audio_file(f):
                 first_audio_file = f
                 break
         else:
@@ -607,12 +1033,17 @@ def _update_cache_for_releases_executor(
                 source_path=source_path,
                 datafile_mtime="",
                 cover_image_path=None,
+                cover_hash=None,
+                cover_size=None,
+                cover_mtime=None,
                 added_at="",
                 albumtitle="",
                 releasetype="",
                 year=None,
                 new=True,
+                hidden=False,
                 disctotal=0,
+                disc_titles={},
                 genres=[],
                 labels=[],
                 albumartists=ArtistMapping(),
@@ -626,6 +1057,10 @@ def _update_cache_for_releases_executor(
             release.source_path = source_path
             release_dirty = True
 
+        # The original filenames from the datafile. Only read when the datafile is read; otherwise
+        # the cached tracks carry them.
+        stored_original_filenames: dict[str, str] = {}
+
         # The directory does not have a release ID, so create the stored data file. Also, in case
         # the directory changes mid-scan, wrap this in an error handler.
         try:
@@ -651,21 +1086,26 @@ def _update_cache_for_releases_executor(
                     continue
 
                 logger.debug(f"Creating new stored data file for release {source_path}")
+                added_at = datetime.now()
+                if c.seed_added_at_from_fs:
+                    added_at = datetime.fromtimestamp(os.stat(source_path).st_mtime)
                 stored_release_data = StoredDataFile(
                     new=True,
-                    added_at=datetime.now().astimezone().replace(microsecond=0).isoformat(),
+                    added_at=_format_added_at(c, added_at),
                 )
                 # Preserve the release ID already present the first file if we can.
-                new_release_id = release_id_from_first_file or str(uuid6.uuid7())
+                new_release_id = release_id_from_first_file or mint_id(c)
                 datafile_path = source_path / f".rose.{new_release_id}.toml"
-                # No need to lock here, as since the release ID is new, there is no way there is a
-                # concurrent writer.
-                with datafile_path.open("wb") as fp:
-                    tomli_w.dump(dataclasses.asdict(stored_release_data), fp)
+                if not dry_run:
+                    # No need to lock here, as since the release ID is new, there is no way there is
+                    # a concurrent writer.
+                    write_stored_data_file(datafile_path, stored_release_data.dump())
+                    release.datafile_mtime = str(os.stat(datafile_path).st_mtime)
+                report.written_datafiles.append(datafile_path)
                 release.id = new_release_id
                 release.new = stored_release_data.new
                 release.added_at = stored_release_data.added_at
-                release.datafile_mtime = str(os.stat(datafile_path).st_mtime)
+                release.hidden = stored_release_data.hidden
                 release_dirty = True
             else:
                 # Otherwise, check to see if the mtime changed from what we know. If it has, read
@@ -682,21 +1122,27 @@ def _update_cache_for_releases_executor(
                         diskdata = tomllib.load(fp)
                     datafile = StoredDataFile(
                         new=diskdata.get("new", True),
-                        added_at=diskdata.get(
-                            "added_at",
-                            datetime.now().astimezone().replace(microsecond=0).isoformat(),
-                        ),
+                        added_at=diskdata.get("added_at", _format_added_at(c, datetime.now())),
+                        hidden=diskdata.get("hidden", False),
+                        original_filenames=diskdata.get("original_filenames", {}),
                     )
                     release.new = datafile.new
                     release.added_at = datafile.added_at
-                    new_resolved_data = dataclasses.asdict(datafile)
+                    release.hidden = datafile.hidden
+                    stored_original_filenames = datafile.original_filenames
+                    new_resolved_data = datafile.dump()
                     logger.debug(f"Updating values in stored data file for release {source_path}")
-                    if new_resolved_data != diskdata:
+                    if new_resolved_data != diskdata and dry_run:
+                        report.written_datafiles.append(datafile_path)
+                    elif not dry_run:
                         # And then write the data back to disk if it changed. This allows us to update
-                        # datafiles to contain newer default values.
+                        # datafiles to contain newer default values. `write_stored_data_file` skips
+                        # the write if the content is unchanged, so the datafile is not churned.
                         lockname = release_lock_name(preexisting_release_id)
-                        with lock(c, lockname), datafile_path.open("wb") as fp:
-                            tomli_w.dump(new_resolved_data, fp)
+                        with lock(c, lockname, timeout=c.lock_timeout_seconds.cache_update):
+                            if write_stored_data_file(datafile_path, new_resolved_data):
+                                report.written_datafiles.append(datafile_path)
+                                release.datafile_mtime = str(os.stat(datafile_path).st_mtime)
         except FileNotFoundError:
             logger.warning(f"Skipping update on {source_path}: directory no longer exists")
             continue
@@ -704,13 +1150,54 @@ def _update_cache_for_releases_executor(
         # Handle cover art change.
         cover = None
         for f in files:
-            if f.name.lower() in c.valid_cover_arts:
+            if c.is_valid_cover_art(f):
                 cover = f
                 break
+        if cover is None and c.convert_unsupported_covers:
+            cover = _find_and_convert_unsupported_cover(c, files, report, dry_run)
+        if cover is None and c.extract_embedded_covers and not dry_run:
+            # Only look for embedded cover art when the first audio file changed since the last
+            # scan, so that releases without any cover art do not reopen the file on every update.
+            cached_first_track = cached_tracks.get(str(first_audio_file), None)
+            try:
+                first_track_changed = (
+                    force
+                    or not cached_first_track
+                    or str(os.stat(first_audio_file).st_mtime) != cached_first_track.source_mtime
+                )
+            except FileNotFoundError:
+                first_track_changed = False
+            if first_track_changed:
+                lockname = release_lock_name(release.id)
+                with lock(c, lockname, timeout=c.lock_timeout_seconds.cache_update):
+                    cover = extract_embedded_cover(c, source_path, first_audio_file)
         if cover != release.cover_image_path:
             logger.debug(f"Cover art file for release {source_path} updated to path {cover}")
             release.cover_image_path = cover
             release_dirty = True
+        # Sync tools may replace the cover's bytes without changing its path, so compare the
+        # contents of the cover as well. Hashing is slow for large covers, so only rehash the
+        # cover when its size or mtime changed.
+        try:
+            cover_stat = cover.stat() if cover else None
+        except FileNotFoundError:
+            logger.warning(f"Cover art file for release {source_path} disappeared during scan")
+            cover_stat = None
+        cover_size = cover_stat.st_size if cover_stat else None
+        cover_mtime = str(cover_stat.st_mtime) if cover_stat else None
+        if (cover_size, cover_mtime) != (release.cover_size, release.cover_mtime):
+            cover_hash = None
+            if cover and cover_stat:
+                try:
+                    cover_hash = sha256(cover.read_bytes()).hexdigest()
+                except FileNotFoundError:
+                    logger.warning(f"Cover art for release {source_path} disappeared during scan")
+            if cover_hash != release.cover_hash:
+                logger.debug(f"Cover art contents for release {source_path} updated: {cover_hash}")
+                release.cover_hash = cover_hash
+            release.cover_size = cover_size
+            release.cover_mtime = cover_mtime
+            release_dirty = True
 
         # Now we'll switch over to processing some of the tracks. We need track metadata in
         # order to calculate some fields of the release, so we'll first compute the valid set of
@@ -721,6 +1208,9 @@ def _update_cache_for_releases_executor(
         # loop, this set should only contain the such tracks, which will be deleted in the
         # database execution handling step.
         unknown_cached_tracks: set[str] = set(cached_tracks.keys())
+        # Files that were moved within the release are cached under their old path, so also index
+        # the cached tracks by ID to carry over their original filenames.
+        cached_tracks_by_id = {t.id: t for t in cached_tracks.values()}
         # Next, we will construct the list of tracks that are on the release. We will also
         # leverage mtimes and such to avoid unnecessary recomputations. If a release has changed
         # and should be updated in the database, we add its ID to track_ids_to_insert, which
@@ -730,9 +1220,12 @@ def _update_cache_for_releases_executor(
         # This value is set to true if we read an AudioTags and used it to confirm the release
         # tags.
         pulled_release_tags = False
+        # This value is set to true if the release lacks albumartist tags and we are configured to
+        # derive the release artists from the track artists once all tracks have been read.
+        derive_albumartists = False
         totals_ctr: dict[str, int] = Counter()
         for f in files:
-            if f.suffix.lower() not in SUPPORTED_AUDIO_EXTENSIONS:
+            if not is_supported_audio_file(f):
                 continue
 
             cached_track = cached_tracks.get(str(f), None)
@@ -749,16 +1242,44 @@ def _update_cache_for_releases_executor(
                     tracks.append(cached_track)
                     totals_ctr[cached_track.discnumber] += 1
                     continue
+                # Likewise, skip re-reading audio files that were too short to be tracks.
+                cached_short_track = cached_short_tracks.get(str(f), None)
+                if (
+                    cached_short_track
+                    and track_mtime == cached_short_track[0]
+                    and not force
+                    and _is_too_short(c, cached_short_track[1])
+                ):
+                    logger.debug(
+                        f"Short track cache hit (mtime) for {os.path.basename(f)}, skipping"
+                    )
+                    seen_short_tracks.add(str(f))
+                    continue
 
                 # Otherwise, read tags from disk and construct a new cached_track.
                 logger.debug(f"Track cache miss for {os.path.basename(f)}, reading tags from disk")
-                tags = AudioTags.from_file(Path(f))
+                tags = AudioTags.from_file(
+                    Path(f), combined_artist_tag_fallback=c.combined_artist_tag_fallback
+                )
             except FileNotFoundError:
                 logger.warning(
                     f"Skipping track update for {os.path.basename(f)}: file no longer exists"
                 )
                 continue
 
+            # Skip audio files that are too short to be real tracks, such as silent junk files and
+            # hidden previews. If the file was previously indexed, it is removed from the cache. The
+            # file's duration is cached so that its tags are not re-read on the next update.
+            if _is_too_short(c, tags.duration_sec):
+                logger.debug(f"Skipping track {os.path.basename(f)}: below the minimum duration")
+                if cached_track:
+                    unknown_cached_tracks.add(str(f))
+                upd_short_track_args.append(
+                    [str(f), str(source_path), track_mtime, tags.duration_sec]
+                )
+                seen_short_tracks.add(str(f))
+                continue
+
             # Now that we're here, pull the release tags. We also need them to compute the
             # formatted artist string.
             if not pulled_release_tags:
@@ -790,7 +1311,8 @@ def _update_cache_for_releases_executor(
                     release.labels = uniq(tags.label)
                     release_dirty = True
 
-                if tags.albumartists != release.albumartists:
+                derive_albumartists = c.derive_albumartist_from_tracks and not tags.albumartists.all
+                if not derive_albumartists and tags.albumartists != release.albumartists:
                     logger.debug(f"Release artists change detected for {source_path}, updating")
                     release.albumartists = tags.albumartists
                     release_dirty = True
@@ -811,35 +1333,50 @@ def _update_cache_for_releases_executor(
                 # This is our first time reading this track in the system, so no cocurrent processes
                 # should be reading/writing this file. We can avoid locking. And If we have two
                 # concurrent first-time cache updates, other places will have issues too.
-                tags.id = tags.id or str(uuid6.uuid7())
+                tags.id = tags.id or mint_id(c)
                 tags.release_id = release.id
                 try:
-                    tags.flush()
-                    # And refresh the mtime because we've just written to the file.
+                    if not dry_run:
+                        tags.flush()
+                        # And refresh the mtime because we've just written to the file.
+                        track_mtime = str(os.stat(f).st_mtime)
                     track_id = tags.id
-                    track_mtime = str(os.stat(f).st_mtime)
                 except FileNotFoundError:
                     logger.warning(
                         f"Skipping track update for {os.path.basename(f)}: file no longer exists"
                     )
                     continue
+                report.tagged_tracks.append(Path(f))
 
             # And now create the cached track.
+            previous_track = cached_track or cached_tracks_by_id.get(track_id)
             track = CachedTrack(
                 id=track_id,
                 source_path=Path(f),
                 source_mtime=track_mtime,
                 tracktitle=tags.title or "Unknown Title",
+                tracksubtitle=tags.subtitle,
                 # Remove `.` here because we use `.` to parse out discno/trackno in the virtual
                 # filesystem. It should almost never happen, but better to be safe. We set the
                 # totals on all tracks the end of the loop.
                 tracknumber=(tags.tracknumber or "1").replace(".", ""),
                 tracktotal=tags.tracktotal or 1,
                 discnumber=(tags.discnumber or "1").replace(".", ""),
+                discsubtitle=tags.discsubtitle,
                 disctotal=tags.disctotal or 1,
                 # This is calculated with the virtual filename.
                 duration_seconds=tags.duration_sec,
-                trackartists=tags.trackartists,
+                audio_sha256=compute_audio_sha256(Path(f)) if c.compute_audio_sha256 else None,
+                isrc=tags.isrc,
+                original_filename=(
+                    previous_track.original_filename
+                    if previous_track
+                    else stored_original_filenames.get(track_id)
+                ),
+                # Releases commonly only tag the album artist on single-artist releases, in which
+                # case the album artists are also the track artists. The tags are left untouched so
+                # that the fallback is never written back to the file.
+                trackartists=tags.trackartists if tags.trackartists.all else tags.albumartists,
                 metahash="",
                 release=release,
             )
@@ -847,6 +1384,13 @@ def _update_cache_for_releases_executor(
             track_ids_to_insert.add(track.id)
             totals_ctr[track.discnumber] += 1
 
+        # If every audio file was skipped for being too short, the release is empty.
+        if not tracks:
+            logger.debug(f"Did not find any tracks in release {source_path}, skipping")
+            logger.debug(f"Scheduling cache deletion for empty directory release {source_path}")
+            upd_delete_source_paths.append(str(source_path))
+            continue
+
         # Now set the tracktotals and disctotals.
         disctotal = len(totals_ctr)
         if release.disctotal != disctotal:
@@ -865,46 +1409,77 @@ def _update_cache_for_releases_executor(
                 track.tracktotal = tracktotal
                 track_ids_to_insert.add(track.id)
 
-        # And now perform directory/file renames if configured.
-        if c.rename_source_files:
-            if release_dirty:
-                wanted_dirname = eval_release_template(c.path_templates.source.release, release)
-                wanted_dirname = sanitize_dirname(wanted_dirname, True)
-                # Iterate until we've either:
-                # 1. Realized that the name of the source path matches the desired dirname (which we
-                #    may not realize immediately if there are name conflicts).
-                # 2. Or renamed the source directory to match our desired name.
-                original_wanted_dirname = wanted_dirname
-                collision_no = 2
-                while wanted_dirname != release.source_path.name:
-                    new_source_path = release.source_path.with_name(wanted_dirname)
-                    # If there is a collision, bump the collision counter and retry.
-                    if new_source_path.exists():
-                        wanted_dirname = f"{original_wanted_dirname} [{collision_no}]"
-                        collision_no += 1
-                        continue
-                    # If no collision, rename the directory.
-                    old_source_path = release.source_path
+        # Aggregate the disc subtitles of the tracks into the release's disc titles. If the tracks
+        # of a disc disagree on the subtitle, pick the lowest one, like the releases view does.
+        disc_titles: dict[str, str] = {}
+        for track in tracks:
+            if track.discsubtitle and (
+                track.discnumber not in disc_titles
+                or track.discsubtitle < disc_titles[track.discnumber]
+            ):
+                disc_titles[track.discnumber] = track.discsubtitle
+        disc_titles = dict(sorted(disc_titles.items()))
+        if disc_titles != release.disc_titles:
+            logger.debug(f"Release disc titles change detected for {source_path}, updating")
+            release.disc_titles = disc_titles
+            release_dirty = True
+
+        # Derive the release artists from the track artists if the albumartist tags were missing.
+        if derive_albumartists:
+            derived_albumartists = _derive_albumartists_from_tracks(c, tracks)
+            if derived_albumartists != release.albumartists:
+                logger.debug(f"Derived release artists change for {source_path}, updating")
+                release.albumartists = derived_albumartists
+                release_dirty = True
+
+        # And now perform directory/file renames if configured. Symlinked releases are stored under
+        # the symlink's target, so renaming the directory would leave the symlink dangling.
+        if c.rename_source_dirs and source_path in symlinked_dirs:
+            logger.debug(f"Skipping renaming {source_path} because it is reached through a symlink")
+        elif c.rename_source_dirs and (release_dirty or force_rename):
+            wanted_dirname = wanted_release_dirname(c, release)
+            # Iterate until we've either:
+            # 1. Realized that the name of the source path matches the desired dirname (which we
+            #    may not realize immediately if there are name conflicts).
+            # 2. Or renamed the source directory to match our desired name.
+            original_wanted_dirname = wanted_dirname
+            collision_no = 2
+            while wanted_dirname != release.source_path.name:
+                new_source_path = release.source_path.with_name(wanted_dirname)
+                # If there is a collision, bump the collision counter and retry.
+                if new_source_path.exists() or new_source_path in dry_run_claimed_paths:
+                    wanted_dirname = f"{original_wanted_dirname} [{collision_no}]"
+                    collision_no += 1
+                    continue
+                # If no collision, rename the directory.
+                old_source_path = release.source_path
+                if dry_run:
+                    dry_run_claimed_paths.add(new_source_path)
+                else:
                     old_source_path.rename(new_source_path)
                     logger.info(
                         f"Renamed source release directory {old_source_path.name} to {new_source_path.name}"
                     )
-                    release.source_path = new_source_path
-                    # Update the cached cover image path.
-                    if release.cover_image_path:
-                        coverlocalpath = str(release.cover_image_path).removeprefix(
-                            f"{old_source_path}/"
-                        )
-                        release.cover_image_path = release.source_path / coverlocalpath
-                    # Update the cached track paths and schedule them for database insertions.
-                    for track in tracks:
-                        tracklocalpath = str(track.source_path).removeprefix(f"{old_source_path}/")
-                        track.source_path = release.source_path / tracklocalpath
+                report.renamed_paths.append((old_source_path, new_source_path))
+                release.source_path = new_source_path
+                release_dirty = True
+                # Update the cached cover image path.
+                if release.cover_image_path:
+                    coverlocalpath = str(release.cover_image_path).removeprefix(
+                        f"{old_source_path}/"
+                    )
+                    release.cover_image_path = release.source_path / coverlocalpath
+                # Update the cached track paths and schedule them for database insertions.
+                for track in tracks:
+                    tracklocalpath = str(track.source_path).removeprefix(f"{old_source_path}/")
+                    track.source_path = release.source_path / tracklocalpath
+                    if not dry_run:
                         track.source_mtime = str(os.stat(track.source_path).st_mtime)
-                        track_ids_to_insert.add(track.id)
-            for track in [t for t in tracks if t.id in track_ids_to_insert]:
-                wanted_filename = eval_track_template(c.path_templates.source.track, track)
-                wanted_filename = sanitize_filename(wanted_filename, True)
+                    track_ids_to_insert.add(track.id)
+        if c.rename_source_files:
+            new_original_filenames: dict[str, str] = {}
+            for track in [t for t in tracks if force_rename or t.id in track_ids_to_insert]:
+                wanted_filename = wanted_track_filename(c, track)
                 # And repeat a similar process to the release rename handling. Except: we can have
                 # arbitrarily nested files here, so we need to compare more than the name.
                 original_wanted_stem = Path(wanted_filename).stem
@@ -914,15 +1489,23 @@ def _update_cache_for_releases_executor(
                     relpath := str(track.source_path).removeprefix(f"{release.source_path}/")
                 ) and wanted_filename != relpath:
                     new_source_path = release.source_path / wanted_filename
-                    if new_source_path.exists():
+                    if new_source_path.exists() or new_source_path in dry_run_claimed_paths:
                         wanted_filename = (
                             f"{original_wanted_stem} [{collision_no}]{original_wanted_suffix}"
                         )
                         collision_no += 1
                         continue
                     old_source_path = track.source_path
-                    old_source_path.rename(new_source_path)
+                    report.renamed_paths.append((old_source_path, new_source_path))
                     track.source_path = new_source_path
+                    if track.original_filename is None:
+                        track.original_filename = relpath
+                        new_original_filenames[track.id] = relpath
+                    track_ids_to_insert.add(track.id)
+                    if dry_run:
+                        dry_run_claimed_paths.add(new_source_path)
+                        continue
+                    old_source_path.rename(new_source_path)
                     track.source_mtime = str(os.stat(track.source_path).st_mtime)
                     logger.info(
                         f"Renamed source file {release.source_path.name}/{relpath} to {release.source_path.name}/{wanted_filename}"
@@ -932,6 +1515,21 @@ def _update_cache_for_releases_executor(
                         relppp = release.source_path / relpath
                         if relppp.is_dir() and not list(relppp.iterdir()):
                             relppp.rmdir()
+            # Persist the original filenames of newly renamed tracks in the datafile.
+            if new_original_filenames and not dry_run:
+                datafile_path = release.source_path / f".rose.{release.id}.toml"
+                lockname = release_lock_name(release.id)
+                with lock(c, lockname, timeout=c.lock_timeout_seconds.cache_update):
+                    with datafile_path.open("rb") as fp:
+                        diskdata = tomllib.load(fp)
+                    diskdata["original_filenames"] = {
+                        **diskdata.get("original_filenames", {}),
+                        **new_original_filenames,
+                    }
+                    if write_stored_data_file(datafile_path, diskdata):
+                        report.written_datafiles.append(datafile_path)
+                        release.datafile_mtime = str(os.stat(datafile_path).st_mtime)
+                        release_dirty = True
 
         # Schedule database executions.
         if unknown_cached_tracks or release_dirty or track_ids_to_insert:
@@ -943,11 +1541,15 @@ def _update_cache_for_releases_executor(
 
         if release_dirty:
             logger.debug(f"Scheduling upsert for dirty release in database: {release.source_path}")
+            report.updated_releases.append(release.source_path)
             upd_release_args.append(
                 [
                     release.id,
                     str(release.source_path),
                     str(release.cover_image_path) if release.cover_image_path else None,
+                    release.cover_hash,
+                    release.cover_size,
+                    release.cover_mtime,
                     release.added_at,
                     release.datafile_mtime,
                     release.albumtitle,
@@ -955,6 +1557,7 @@ def _update_cache_for_releases_executor(
                     release.year,
                     release.disctotal,
                     release.new,
+                    release.hidden,
                     sha256_dataclass(release),
                 ]
             )
@@ -980,18 +1583,24 @@ def _update_cache_for_releases_executor(
                 if track.id not in track_ids_to_insert:
                     continue
                 logger.debug(f"Scheduling upsert for dirty track in database: {track.source_path}")
+                report.updated_tracks.append(track.source_path)
                 upd_track_args.append(
                     [
                         track.id,
                         str(track.source_path),
                         track.source_mtime,
                         track.tracktitle,
+                        track.tracksubtitle,
                         track.release.id,
                         track.tracknumber,
                         track.tracktotal,
                         track.discnumber,
+                        track.discsubtitle,
                         track.disctotal,
                         track.duration_seconds,
+                        track.audio_sha256,
+                        track.isrc,
+                        track.original_filename,
                         sha256_dataclass(track),
                     ]
                 )
@@ -1005,6 +1614,17 @@ def _update_cache_for_releases_executor(
                         pos += 1
     logger.debug(f"Release update scheduling loop time {time.time() - loop_start=}")
 
+    if dry_run:
+        # Only report the deletions of releases that are actually in the cache.
+        if upd_delete_source_paths:
+            with connect(c) as conn:
+                cursor = conn.execute(
+                    f"SELECT source_path FROM releases WHERE source_path IN ({','.join(['?']*len(upd_delete_source_paths))})",
+                    upd_delete_source_paths,
+                )
+                report.evicted_releases.extend(Path(row["source_path"]) for row in cursor)
+        return report
+
     exec_start = time.time()
     # During execution, identify the collages and playlists to update afterwards. We will invoke an
     # update for those collages and playlists with force=True after updating the release tables.
@@ -1012,10 +1632,11 @@ def _update_cache_for_releases_executor(
     update_playlists = None
     with connect(c) as conn:
         if upd_delete_source_paths:
-            conn.execute(
-                f"DELETE FROM releases WHERE source_path IN ({','.join(['?']*len(upd_delete_source_paths))})",
+            cursor = conn.execute(
+                f"DELETE FROM releases WHERE source_path IN ({','.join(['?']*len(upd_delete_source_paths))}) RETURNING source_path",
                 upd_delete_source_paths,
             )
+            report.evicted_releases.extend(Path(row["source_path"]) for row in cursor)
         if upd_unknown_cached_tracks_args:
             query = "DELETE FROM tracks WHERE false"
             args: list[Any] = []
@@ -1023,6 +1644,28 @@ def _update_cache_for_releases_executor(
                 query += f" OR (release_id = ? AND source_path IN ({','.join(['?']*len(utrks))}))"
                 args.extend([release_id, *utrks])
             conn.execute(query, args)
+        if upd_short_track_args:
+            conn.execute(
+                f"""
+                INSERT OR REPLACE INTO short_tracks (
+                    source_path
+                  , release_source_path
+                  , source_mtime
+                  , duration_seconds
+                ) VALUES {",".join(["(?,?,?,?)"] * len(upd_short_track_args))}
+                """,
+                _flatten(upd_short_track_args),
+            )
+        # Forget the short audio files that are gone or are no longer too short.
+        stale_short_tracks = [p for p in cached_short_tracks if p not in seen_short_tracks]
+        if stale_short_tracks:
+            conn.execute(
+                f"""
+                DELETE FROM short_tracks
+                WHERE source_path IN ({','.join(['?']*len(stale_short_tracks))})
+                """,
+                stale_short_tracks,
+            )
         if upd_release_args:
             conn.execute(
                 f"""
@@ -1030,6 +1673,9 @@ def _update_cache_for_releases_executor(
                     id
                   , source_path
                   , cover_image_path
+                  , cover_hash
+                  , cover_size
+                  , cover_mtime
                   , added_at
                   , datafile_mtime
                   , title
@@ -1037,11 +1683,15 @@ def _update_cache_for_releases_executor(
                   , year
                   , disctotal
                   , new
+                  , hidden
                   , metahash
-                ) VALUES {",".join(["(?,?,?,?,?,?,?,?,?,?,?)"] * len(upd_release_args))}
+                ) VALUES {",".join(["(?,?,?,?,?,?,?,?,?,?,?,?,?,?,?)"] * len(upd_release_args))}
                 ON CONFLICT (id) DO UPDATE SET
                     source_path      = excluded.source_path
                   , cover_image_path = excluded.cover_image_path
+                  , cover_hash       = excluded.cover_hash
+                  , cover_size       = excluded.cover_size
+                  , cover_mtime      = excluded.cover_mtime
                   , added_at         = excluded.added_at
                   , datafile_mtime   = excluded.datafile_mtime
                   , title            = excluded.title
@@ -1049,6 +1699,7 @@ def _update_cache_for_releases_executor(
                   , year             = excluded.year
                   , disctotal        = excluded.disctotal
                   , new              = excluded.new
+                  , hidden           = excluded.hidden
                   , metahash         = excluded.metahash
                 """,
                 _flatten(upd_release_args),
@@ -1106,25 +1757,35 @@ def _update_cache_for_releases_executor(
                   , source_path
                   , source_mtime
                   , title
+                  , subtitle
                   , release_id
                   , tracknumber
                   , tracktotal
                   , discnumber
+                  , discsubtitle
                   , disctotal
                   , duration_seconds
+                  , audio_sha256
+                  , isrc
+                  , original_filename
                   , metahash
                 )
-                VALUES {",".join(["(?,?,?,?,?,?,?,?,?,?,?)"]*len(upd_track_args))}
+                VALUES {",".join(["(?,?,?,?,?,?,?,?,?,?,?,?,?,?,?,?)"]*len(upd_track_args))}
                 ON CONFLICT (id) DO UPDATE SET
                     source_path                = excluded.source_path
                   , source_mtime               = excluded.source_mtime
                   , title                      = excluded.title
+                  , subtitle                   = excluded.subtitle
                   , release_id                 = excluded.release_id
                   , tracknumber                = excluded.tracknumber
                   , tracktotal                 = excluded.tracktotal
                   , discnumber                 = excluded.discnumber
+                  , discsubtitle               = excluded.discsubtitle
                   , disctotal                  = excluded.disctotal
                   , duration_seconds           = excluded.duration_seconds
+                  , audio_sha256               = excluded.audio_sha256
+                  , isrc                       = excluded.isrc
+                  , original_filename          = excluded.original_filename
                   , metahash                   = excluded.metahash
                 """,
                 _flatten(upd_track_args),
@@ -1250,6 +1911,133 @@ def _update_cache_for_releases_executor(
             update_cache_for_playlists(c, update_playlists, force=True)
 
     logger.debug(f"Database execution loop time {time.time() - exec_start=}")
+    return report
+
+
+# The release artist of releases with too many distinct track artists to credit individually.
+VARIOUS_ARTISTS = "Various Artists"
+
+
+# Image formats that are not valid cover art, but that can be converted to JPEG cover art.
+CONVERTIBLE_ART_EXTS = ["tiff", "tif", "bmp", "webp", "gif"]
+
+
+def _find_and_convert_unsupported_cover(
+    c: Config,
+    files: list[Path],
+    report: CacheUpdateReport,
+    dry_run: bool,
+) -> Path | None:
+    """
+    Find a cover art file in a convertible format and convert it to a JPEG in the cache directory.
+    The conversion is keyed by the hash of the source image, so unchanged images are not converted
+    again. Returns the path to the JPEG, or None if there is no convertible cover art. In a dry run,
+    the conversion is only reported, and None is returned if the JPEG does not exist yet.
+    """
+    for f in files:
+        ext = f.suffix[1:].lower()
+        if f.stem.lower() not in c.cover_art_stems or ext not in CONVERTIBLE_ART_EXTS:
+            continue
+        try:
+            source_hash = sha256(f.read_bytes()).hexdigest()
+        except FileNotFoundError:
+            continue
+        converted = c.cache_dir / "converted-covers" / f"{source_hash}.jpg"
+        if converted.is_file():
+            return converted
+        if dry_run:
+            report.converted_covers.append((f, converted))
+            return None
+        try:
+            with PIL.Image.open(f) as im:
+                converted.parent.mkdir(parents=True, exist_ok=True)
+                im.convert("RGB").save(converted, "JPEG")
+        except (OSError, ValueError) as e:
+            logger.warning(f"Failed to convert cover art {f} to JPEG: {e}")
+            continue
+        logger.debug(f"Converted cover art {f} to {converted}")
+        report.converted_covers.append((f, converted))
+        return converted
+    return None
+
+
+def _is_too_short(c: Config, duration_seconds: int) -> bool:
+    """Whether an audio file is shorter than `min_track_duration_seconds` and not a real track."""
+    min_duration = c.min_track_duration_seconds
+    return min_duration is not None and duration_seconds < min_duration
+
+
+def extract_embedded_cover(c: Config, source_path: Path, audio_file: Path) -> Path | None:
+    """
+    Extract the cover art embedded in an audio file into a cover art file in the release directory.
+    Returns the path to the new cover art file, or None if there is no usable embedded cover art.
+    """
+    try:
+        data = AudioTags.from_file(audio_file).embedded_cover()
+    except (UnsupportedFiletypeError, FileNotFoundError):
+        return None
+    if not data:
+        return None
+    ext = embedded_cover_art_ext(data)
+    if ext not in c.valid_art_exts:
+        logger.debug(f"Not extracting embedded {ext} cover art of {audio_file}: invalid extension")
+        return None
+    cover = source_path / f"{c.write_cover_art_stem}.{ext}"
+    cover.write_bytes(data)
+    logger.info(f"Extracted the embedded cover art of {audio_file} to {cover}")
+    return cover
+
+
+def _derive_albumartists_from_tracks(c: Config, tracks: list[CachedTrack]) -> ArtistMapping:
+    """
+    Derive the release artists from the main artists of the release's tracks. The artists are
+    ordered by the number of tracks they are credited on. If there are more distinct artists than
+    the configured threshold, the release is credited to Various Artists instead.
+    """
+    ctr: Counter[str] = Counter()
+    for track in tracks:
+        for art in track.trackartists.main:
+            ctr[art.name] += 1
+    if len(ctr) > c.va_artist_threshold:
+        return ArtistMapping(main=[Artist(name=VARIOUS_ARTISTS)])
+    return ArtistMapping(main=[Artist(name=name) for name, _ in ctr.most_common()])
+
+
+def refresh_all_descriptions(c: Config) -> None:
+    """
+    Regenerate the `description_meta` of every collage and playlist entry from the read cache. The
+    releases and tracks are not re-read from disk, so this is cheap compared to a forced cache
+    update. Use it after a change that only affects how the descriptions are formatted.
+    """
+    update_cache_for_collages(c, force=True)
+    update_cache_for_playlists(c, force=True)
+
+
+def _run_executor_in_pool(
+    c: Config,
+    executor: Callable[..., None],
+    files: list[tuple[Path, str]],
+    args: tuple[Any, ...],
+) -> None:
+    """
+    Shard the collage or playlist files across `max_proc` processes and run the executor on each
+    shard. The executors take the same per-entity locks as in the single process case.
+    """
+    num_proc = max(1, min(c.max_proc, len(files)))
+    batch_size = len(files) // num_proc + 1
+    errors: list[BaseException] = []
+    logger.debug(f"Creating multiprocessing pool to update {len(files)} files across {num_proc=}")
+    with multiprocessing.Pool(processes=num_proc) as pool:
+        for i in range(0, len(files), batch_size):
+            pool.apply_async(
+                executor,
+                (c, files[i : i + batch_size], *args),
+                error_callback=lambda e: errors.append(e),
+            )
+        pool.close()
+        pool.join()
+    if errors:
+        raise ExceptionGroup("Exception occurred in cache update subprocesses", errors)  # type: ignore
 
 
 def update_cache_for_collages(
@@ -1267,13 +2055,14 @@ def update_cache_for_collages(
     2. Skip reading a file's data if the mtime has not changed since the previous cache update.
     3. Only execute a SQLite upsert if the read data differ from the previous caches.
 
-    However, we do not batch writes to the end of the function, nor do we process the collages in
-    parallel. This is because we should have far fewer collages than releases.
+    However, we do not batch writes to the end of the function. And by default, we do not process
+    the collages in parallel, because we should have far fewer collages than releases. Libraries
+    with many collages can shard them across processes with `collage_playlist_parallel_threshold`.
     """
-    collage_dir = c.music_source_dir / "!collages"
+    collage_dir = c.collages_dir
     collage_dir.mkdir(exist_ok=True)
 
-    files: list[tuple[Path, str, os.DirEntry[str]]] = []
+    files: list[tuple[Path, str]] = []
     for f in os.scandir(str(collage_dir)):
         path = Path(f.path)
         if path.suffix != ".toml":
@@ -1282,7 +2071,7 @@ def update_cache_for_collages(
             logger.debug(f"Skipping processing collage {path.name} because it is not a file")
             continue
         if collage_names is None or path.stem in collage_names:
-            files.append((path.resolve(), path.stem, f))
+            files.append((path.resolve(), path.stem))
     logger.debug(f"Refreshing the read cache for {len(files)} collages")
 
     cached_collages: dict[str, CachedCollage] = {}
@@ -1310,9 +2099,42 @@ def update_cache_for_collages(
         cursor = conn.execute("SELECT id FROM releases")
         existing_release_ids = {row["id"] for row in cursor}
 
+        # And fetch how long the absent releases have been absent for, to apply the grace period.
+        cursor = conn.execute(
+            """
+            SELECT collage_name, release_id, absent_updates
+            FROM collages_releases
+            WHERE absent_updates > 0
+            """
+        )
+        prev_absent_updates = {
+            (row["collage_name"], row["release_id"]): row["absent_updates"] for row in cursor
+        }
+
     loop_start = time.time()
+    executor_args = (force, cached_collages, existing_release_ids, prev_absent_updates)
+    threshold = c.collage_playlist_parallel_threshold
+    if threshold is None or len(files) < threshold:
+        _update_cache_for_collages_executor(c, files, *executor_args)
+    else:
+        _run_executor_in_pool(c, _update_cache_for_collages_executor, files, executor_args)
+    logger.debug(f"Collage update loop time {time.time() - loop_start=}")
+
+
+def _update_cache_for_collages_executor(
+    c: Config,
+    files: list[tuple[Path, str]],
+    force: bool,
+    cached_collages: dict[str, CachedCollage],
+    existing_release_ids: set[str],
+    prev_absent_updates: dict[tuple[str, str], int],
+) -> None:
+    """
+    Update the read cache for the passed-in collage files. Each collage is written in its own
+    transaction, so that multiple executors can run in parallel.
+    """
     with connect(c) as conn:
-        for source_path, name, f in files:
+        for source_path, name in files:
             try:
                 cached_collage = cached_collages[name]
             except KeyError:
@@ -1324,19 +2146,26 @@ def update_cache_for_collages(
                 )
 
             try:
-                source_mtime = str(f.stat().st_mtime)
+                source_mtime = str(source_path.stat().st_mtime)
             except FileNotFoundError:
                 # Collage was deleted... continue without doing anything. It will be cleaned up by
                 # the eviction function.
                 continue
-            if source_mtime == cached_collage.source_mtime and not force:
+            # Releases that are absent but not yet flagged as missing must be counted on every
+            # update, even though the collage file does not change during the grace period.
+            pending_absent = any(
+                rid not in existing_release_ids
+                and prev_absent_updates.get((name, rid), 0) <= c.collage_missing_grace_updates
+                for rid in cached_collage.release_ids
+            )
+            if source_mtime == cached_collage.source_mtime and not force and not pending_absent:
                 logger.debug(f"Collage cache hit (mtime) for {source_path}, reusing cached data")
                 continue
 
             logger.debug(f"Collage cache miss (mtime) for {source_path}, reading data from disk")
             cached_collage.source_mtime = source_mtime
 
-            with lock(c, collage_lock_name(name)):
+            with lock(c, collage_lock_name(name), timeout=c.lock_timeout_seconds.cache_update):
                 with source_path.open("rb") as fp:
                     data = tomllib.load(fp)
                 original_releases = data.get("releases", [])
@@ -1344,9 +2173,20 @@ def update_cache_for_collages(
 
                 # Update the markings for releases that no longer exist. We will flag releases as
                 # missing/not-missing here, so that if they are re-added (maybe it was a temporary
-                # disappearance)? they are recovered in the collage.
+                # disappearance)? they are recovered in the collage. To avoid churning the collage
+                # on brief disappearances, a release is only flagged as missing once it has been
+                # absent for more than the configured number of updates.
+                absent_updates: dict[str, int] = {}
                 for rls in releases:
-                    if not rls.get("missing", False) and rls["uuid"] not in existing_release_ids:
+                    if rls["uuid"] not in existing_release_ids:
+                        absent_updates[rls["uuid"]] = (
+                            prev_absent_updates.get((cached_collage.name, rls["uuid"]), 0) + 1
+                        )
+                    if (
+                        not rls.get("missing", False)
+                        and rls["uuid"] not in existing_release_ids
+                        and absent_updates[rls["uuid"]] > c.collage_missing_grace_updates
+                    ):
                         logger.warning(
                             f"Marking missing release {rls['description_meta']} as missing in collage {cached_collage.name}"
                         )
@@ -1366,7 +2206,8 @@ def update_cache_for_collages(
                 desc_map: dict[str, str] = {}
                 cursor = conn.execute(
                     f"""
-                    SELECT id, albumtitle, year, albumartist_names, albumartist_roles FROM releases_view
+                    SELECT id, albumtitle, year, new, albumartist_names, albumartist_roles
+                    FROM releases_view
                     WHERE id IN ({','.join(['?']*len(releases))})
                     """,
                     cached_collage.release_ids,
@@ -1378,7 +2219,10 @@ def update_cache_for_collages(
                         artists=_unpack_artists(
                             c, row["albumartist_names"], row["albumartist_roles"]
                         ),
+                        artist_role_order=c.artist_role_order,
                     )
+                    if c.collage_description_mark_new and row["new"]:
+                        desc_map[row["id"]] += " [NEW]"
                 for i, rls in enumerate(releases):
                     with contextlib.suppress(KeyError):
                         releases[i]["description_meta"] = desc_map[rls["uuid"]]
@@ -1396,6 +2240,7 @@ def update_cache_for_collages(
                     cached_collage.source_mtime = str(os.stat(source_path).st_mtime)
 
                 logger.info(f"Updating cache for collage {cached_collage.name}")
+                conn.execute("BEGIN")
                 conn.execute(
                     """
                     INSERT INTO collages (name, source_mtime) VALUES (?, ?)
@@ -1410,24 +2255,37 @@ def update_cache_for_collages(
                 args: list[Any] = []
                 for position, rls in enumerate(releases):
                     args.extend(
-                        [cached_collage.name, rls["uuid"], position + 1, rls.get("missing", False)]
+                        [
+                            cached_collage.name,
+                            rls["uuid"],
+                            position + 1,
+                            rls.get("missing", False),
+                            absent_updates.get(rls["uuid"], 0),
+                            rls.get("added_at"),
+                        ]
                     )
                 if args:
                     conn.execute(
                         f"""
-                        INSERT INTO collages_releases (collage_name, release_id, position, missing)
-                        VALUES {','.join(['(?, ?, ?, ?)'] * len(releases))}
+                        INSERT INTO collages_releases (
+                            collage_name
+                          , release_id
+                          , position
+                          , missing
+                          , absent_updates
+                          , added_at
+                        )
+                        VALUES {','.join(['(?, ?, ?, ?, ?, ?)'] * len(releases))}
                         """,
                         args,
                     )
-
-    logger.debug(f"Collage update loop time {time.time() - loop_start=}")
+                conn.execute("COMMIT")
 
 
 def update_cache_evict_nonexistent_collages(c: Config) -> None:
     logger.debug("Evicting cached collages that are not on disk")
     collage_names: list[str] = []
-    for f in os.scandir(c.music_source_dir / "!collages"):
+    for f in os.scandir(c.collages_dir):
         p = Path(f.path)
         if p.is_file() and p.suffix == ".toml":
             collage_names.append(p.stem)
@@ -1460,13 +2318,14 @@ def update_cache_for_playlists(
     2. Skip reading a file's data if the mtime has not changed since the previous cache update.
     3. Only execute a SQLite upsert if the read data differ from the previous caches.
 
-    However, we do not batch writes to the end of the function, nor do we process the playlists in
-    parallel. This is because we should have far fewer playlists than releases.
+    However, we do not batch writes to the end of the function. And by default, we do not process
+    the playlists in parallel, because we should have far fewer playlists than releases. Libraries
+    with many playlists can shard them across processes with `collage_playlist_parallel_threshold`.
     """
-    playlist_dir = c.music_source_dir / "!playlists"
+    playlist_dir = c.playlists_dir
     playlist_dir.mkdir(exist_ok=True)
 
-    files: list[tuple[Path, str, os.DirEntry[str]]] = []
+    files: list[tuple[Path, str]] = []
     all_files_in_dir: list[Path] = []
     for f in os.scandir(str(playlist_dir)):
         path = Path(f.path)
@@ -1477,7 +2336,7 @@ def update_cache_for_playlists(
             logger.debug(f"Skipping processing playlist {path.name} because it is not a file")
             continue
         if playlist_names is None or path.stem in playlist_names:
-            files.append((path.resolve(), path.stem, f))
+            files.append((path.resolve(), path.stem))
     logger.debug(f"Refreshing the read cache for {len(files)} playlists")
 
     cached_playlists: dict[str, CachedPlaylist] = {}
@@ -1508,8 +2367,29 @@ def update_cache_for_playlists(
         existing_track_ids = {row["id"] for row in cursor}
 
     loop_start = time.time()
+    executor_args = (force, cached_playlists, existing_track_ids, all_files_in_dir)
+    threshold = c.collage_playlist_parallel_threshold
+    if threshold is None or len(files) < threshold:
+        _update_cache_for_playlists_executor(c, files, *executor_args)
+    else:
+        _run_executor_in_pool(c, _update_cache_for_playlists_executor, files, executor_args)
+    logger.debug(f"playlist update loop time {time.time() - loop_start=}")
+
+
+def _update_cache_for_playlists_executor(
+    c: Config,
+    files: list[tuple[Path, str]],
+    force: bool,
+    cached_playlists: dict[str, CachedPlaylist],
+    existing_track_ids: set[str],
+    all_files_in_dir: list[Path],
+) -> None:
+    """
+    Update the read cache for the passed-in playlist files. Each playlist is written in its own
+    transaction, so that multiple executors can run in parallel.
+    """
     with connect(c) as conn:
-        for source_path, name, f in files:
+        for source_path, name in files:
             try:
                 cached_playlist = cached_playlists[name]
             except KeyError:
@@ -1532,14 +2412,14 @@ def update_cache_for_playlists(
                 for potential_art_file in all_files_in_dir:
                     if (
                         potential_art_file.stem == name
-                        and potential_art_file.suffix.lower().lstrip(".") in c.valid_art_exts
+                        and c.is_valid_art_ext(potential_art_file)
                     ):
                         cached_playlist.cover_path = potential_art_file.resolve()
                         dirty = True
                         break
 
             try:
-                source_mtime = str(f.stat().st_mtime)
+                source_mtime = str(source_path.stat().st_mtime)
             except FileNotFoundError:
                 # Playlist was deleted... continue without doing anything. It will be cleaned up by
                 # the eviction function.
@@ -1553,7 +2433,7 @@ def update_cache_for_playlists(
             )
             cached_playlist.source_mtime = source_mtime
 
-            with lock(c, playlist_lock_name(name)):
+            with lock(c, playlist_lock_name(name), timeout=c.lock_timeout_seconds.cache_update):
                 with source_path.open("rb") as fp:
                     data = tomllib.load(fp)
                 original_tracks = data.get("tracks", [])
@@ -1595,6 +2475,7 @@ def update_cache_for_playlists(
                             c, row["trackartist_names"], row["trackartist_roles"]
                         ),
                         suffix=Path(row["source_path"]).suffix,
+                        artist_role_order=c.artist_role_order,
                     )
                 for i, trk in enumerate(tracks):
                     with contextlib.suppress(KeyError):
@@ -1611,6 +2492,7 @@ def update_cache_for_playlists(
                     cached_playlist.source_mtime = str(os.stat(source_path).st_mtime)
 
                 logger.info(f"Updating cache for playlist {cached_playlist.name}")
+                conn.execute("BEGIN")
                 conn.execute(
                     """
                     INSERT INTO playlists (name, source_mtime, cover_path) VALUES (?, ?, ?)
@@ -1641,14 +2523,13 @@ def update_cache_for_playlists(
                         """,
                         args,
                     )
-
-    logger.debug(f"playlist update loop time {time.time() - loop_start=}")
+                conn.execute("COMMIT")
 
 
 def update_cache_evict_nonexistent_playlists(c: Config) -> None:
     logger.debug("Evicting cached playlists that are not on disk")
     playlist_names: list[str] = []
-    for f in os.scandir(c.music_source_dir / "!playlists"):
+    for f in os.scandir(c.playlists_dir):
         p = Path(f.path)
         if p.is_file() and p.suffix == ".toml":
             playlist_names.append(p.stem)
@@ -1666,63 +2547,168 @@ def update_cache_evict_nonexistent_playlists(c: Config) -> None:
             logger.info(f"Evicted missing playlist {row['name']} from cache")
 
 
+ReleaseSort = Literal["added_at", "year", "title", "artist_then_year"]
+
+# The ORDER BY keys of each release sort. These are constants, so user input never reaches the SQL.
+RELEASE_SORT_KEYS: dict[ReleaseSort, list[str]] = {
+    "added_at": ["added_at"],
+    "year": ["year"],
+    "title": ["albumtitle COLLATE NOCASE"],
+    "artist_then_year": [
+        """
+        (SELECT ra.artist FROM releases_artists ra
+         WHERE ra.release_id = releases_view.id AND ra.role = 'main'
+         ORDER BY ra.position LIMIT 1) COLLATE NOCASE
+        """,
+        "year",
+    ],
+}
+
+
+def _release_order_by(sort: ReleaseSort | None, ascending: bool) -> str:
+    """Build the ORDER BY clause of a release listing. Sort ties are broken by source path."""
+    direction = "ASC" if ascending else "DESC"
+    order_by = [f"{key} {direction}" for key in RELEASE_SORT_KEYS[sort]] if sort else []
+    return f"ORDER BY {', '.join([*order_by, 'source_path'])}"
+
+
+def _release_filters(
+    c: Config,
+    sanitized_artist_filter: str | None,
+    sanitized_genre_filter: str | None,
+    sanitized_label_filter: str | None,
+    new: bool | None,
+    include_hidden: bool = False,
+) -> tuple[str, list[str | bool]]:
+    """
+    Build the WHERE clause (and its arguments) over `releases_view` for the given filters. Shared
+    by the listing and counting queries so that both see the same set of releases. Hidden releases
+    are excluded unless `include_hidden` is set.
+    """
+    query = "WHERE 1=1"
+    args: list[str | bool] = []
+    if sanitized_artist_filter:
+        sanitized_artists: list[str] = [sanitized_artist_filter]
+        for alias in c.sanitized_artist_aliases_map.get(sanitized_artist_filter, []):
+            sanitized_artists.append(alias)
+        query += f"""
+            AND EXISTS (
+                SELECT * FROM releases_artists
+                WHERE release_id = id AND artist_sanitized IN ({','.join(['?']*len(sanitized_artists))})
+            )
+        """
+        args.extend(sanitized_artists)
+    if sanitized_genre_filter:
+        query += """
+            AND EXISTS (
+                SELECT * FROM releases_genres
+                WHERE release_id = id AND genre_sanitized = ?
+            )
+        """
+        args.append(sanitized_genre_filter)
+    if sanitized_label_filter:
+        query += """
+            AND EXISTS (
+                SELECT * FROM releases_labels
+                WHERE release_id = id AND label_sanitized = ?
+            )
+        """
+        args.append(sanitized_label_filter)
+    if new is not None:
+        query += " AND new = ?"
+        args.append(new)
+    if not include_hidden:
+        query += " AND NOT hidden"
+    return query, args
+
+
 def list_releases_delete_this(
     c: Config,
     sanitized_artist_filter: str | None = None,
     sanitized_genre_filter: str | None = None,
     sanitized_label_filter: str | None = None,
     new: bool | None = None,
+    include_hidden: bool = False,
+    sort: ReleaseSort | None = None,
+    ascending: bool = True,
 ) -> list[CachedRelease]:
+    where, args = _release_filters(
+        c,
+        sanitized_artist_filter,
+        sanitized_genre_filter,
+        sanitized_label_filter,
+        new,
+        include_hidden,
+    )
     with connect(c) as conn:
-        query = "SELECT * FROM releases_view WHERE 1=1"
-        args: list[str | bool] = []
-        if sanitized_artist_filter:
-            sanitized_artists: list[str] = [sanitized_artist_filter]
-            for alias in c.sanitized_artist_aliases_map.get(sanitized_artist_filter, []):
-                sanitized_artists.append(alias)
-            query += f"""
-                AND EXISTS (
-                    SELECT * FROM releases_artists
-                    WHERE release_id = id AND artist_sanitized IN ({','.join(['?']*len(sanitized_artists))})
-                )
-            """
-            args.extend(sanitized_artists)
-        if sanitized_genre_filter:
-            query += """
-                AND EXISTS (
-                    SELECT * FROM releases_genres
-                    WHERE release_id = id AND genre_sanitized = ?
-                )
-            """
-            args.append(sanitized_genre_filter)
-        if sanitized_label_filter:
-            query += """
-                AND EXISTS (
-                    SELECT * FROM releases_labels
-                    WHERE release_id = id AND label_sanitized = ?
-                )
-            """
-            args.append(sanitized_label_filter)
-        if new is not None:
-            query += " AND new = ?"
-            args.append(new)
-        query += " ORDER BY source_path"
-
-        cursor = conn.execute(query, args)
+        cursor = conn.execute(
+            f"SELECT * FROM releases_view {where} {_release_order_by(sort, ascending)}", args
+        )
         releases: list[CachedRelease] = []
         for row in cursor:
             releases.append(CachedRelease.from_view(c, row))
         return releases
 
 
-def list_releases(c: Config, release_ids: list[str] | None = None) -> list[CachedRelease]:
-    """Fetch data associated with given release IDs. Pass None to fetch all."""
-    query = "SELECT * FROM releases_view"
-    args = []
-    if release_ids is not None:
-        query += f" WHERE id IN ({','.join(['?']*len(release_ids))})"
-        args = release_ids
-    query += " ORDER BY source_path"
+def list_releases_paginated(
+    c: Config,
+    limit: int,
+    offset: int = 0,
+    sanitized_artist_filter: str | None = None,
+    sanitized_genre_filter: str | None = None,
+    sanitized_label_filter: str | None = None,
+    new: bool | None = None,
+    include_hidden: bool = False,
+    sort: ReleaseSort | None = None,
+    ascending: bool = True,
+) -> tuple[list[CachedRelease], int]:
+    """
+    Fetch a page of the releases matching the filters, ordered by source path unless a sort is
+    passed. Returns the page and the total number of matching releases, so that callers can compute
+    the page count. Hidden releases are excluded unless `include_hidden` is set.
+    """
+    where, args = _release_filters(
+        c,
+        sanitized_artist_filter,
+        sanitized_genre_filter,
+        sanitized_label_filter,
+        new,
+        include_hidden,
+    )
+    with connect(c) as conn:
+        cursor = conn.execute(
+            f"""
+            SELECT * FROM releases_view {where} {_release_order_by(sort, ascending)}
+            LIMIT ? OFFSET ?
+            """,
+            [*args, limit, offset],
+        )
+        releases = [CachedRelease.from_view(c, row) for row in cursor]
+        cursor = conn.execute(f"SELECT COUNT(*) FROM releases_view {where}", args)
+        total = cursor.fetchone()[0]
+        return releases, total
+
+
+def list_releases(
+    c: Config,
+    release_ids: list[str] | None = None,
+    include_hidden: bool = False,
+    sort: ReleaseSort | None = None,
+    ascending: bool = True,
+) -> list[CachedRelease]:
+    """
+    Fetch data associated with given release IDs. Pass None to fetch all. Hidden releases are
+    excluded unless `include_hidden` is set. Releases are ordered by source path, unless a sort is
+    passed. Ties in the sort are broken by source path.
+    """
+    query = "SELECT * FROM releases_view WHERE 1=1"
+    args = []
+    if release_ids is not None:
+        query += f" AND id IN ({','.join(['?']*len(release_ids))})"
+        args = release_ids
+    if not include_hidden:
+        query += " AND NOT hidden"
+    query += f" {_release_order_by(sort, ascending)}"
     with connect(c) as conn:
         cursor = conn.execute(query, args)
         releases: list[CachedRelease] = []
@@ -1731,6 +2717,20 @@ def list_releases(c: Config, release_ids: list[str] | None = None) -> list[Cache
         return releases
 
 
+def releases_not_in_any_collage(c: Config) -> list[CachedRelease]:
+    """Fetch the releases that are in no collage, for curation. Hidden releases are excluded."""
+    with connect(c) as conn:
+        cursor = conn.execute(
+            """
+            SELECT * FROM releases_view r
+            WHERE NOT hidden
+              AND NOT EXISTS (SELECT * FROM collages_releases cr WHERE cr.release_id = r.id)
+            ORDER BY source_path
+            """
+        )
+        return [CachedRelease.from_view(c, row) for row in cursor]
+
+
 def get_release(c: Config, release_id: str) -> CachedRelease | None:
     with connect(c) as conn:
         cursor = conn.execute(
@@ -1743,6 +2743,43 @@ def get_release(c: Config, release_id: str) -> CachedRelease | None:
         return CachedRelease.from_view(c, row)
 
 
+def get_releases(c: Config, release_ids: list[str]) -> list[CachedRelease]:
+    """
+    Fetch multiple releases in one query. The releases are returned in the order of the passed-in
+    IDs, and IDs without a release are skipped. Unlike `list_releases`, hidden releases are included,
+    as they are when fetching a single release.
+    """
+    with connect(c) as conn:
+        cursor = conn.execute(
+            f"SELECT * FROM releases_view WHERE id IN ({','.join(['?']*len(release_ids))})",
+            release_ids,
+        )
+        releases_map = {row["id"]: CachedRelease.from_view(c, row) for row in cursor}
+    return [releases_map[x] for x in release_ids if x in releases_map]
+
+
+def get_release_by_path(c: Config, path: Path) -> CachedRelease | None:
+    """
+    Fetch the release whose source directory is `path`, or the release that contains the track file
+    at `path`. The path is resolved first, so relative paths and symlinks are accepted.
+    """
+    resolved = str(path.resolve())
+    with connect(c) as conn:
+        cursor = conn.execute(
+            """
+            SELECT *
+            FROM releases_view
+            WHERE source_path = ?
+               OR id = (SELECT release_id FROM tracks WHERE source_path = ?)
+            """,
+            (resolved, resolved),
+        )
+        row = cursor.fetchone()
+        if not row:
+            return None
+        return CachedRelease.from_view(c, row)
+
+
 def get_release_logtext(c: Config, release_id: str) -> str | None:
     """Get a human-readable identifier for a release suitable for logging."""
     with connect(c) as conn:
@@ -1757,6 +2794,7 @@ def get_release_logtext(c: Config, release_id: str) -> str | None:
             title=row["albumtitle"],
             year=row["year"],
             artists=_unpack_artists(c, row["albumartist_names"], row["albumartist_roles"]),
+            artist_role_order=c.artist_role_order,
         )
 
 
@@ -1764,43 +2802,82 @@ def calculate_release_logtext(
     title: str,
     year: int | None,
     artists: ArtistMapping,
+    artist_role_order: list[str] = DEFAULT_ARTIST_ROLE_ORDER,
 ) -> str:
-    logtext = f"{artistsfmt(artists)} - "
+    logtext = f"{artistsfmt(artists, artist_role_order)} - "
     if year:
         logtext += f"{year}. "
     logtext += title
     return logtext
 
 
-def list_tracks(c: Config, track_ids: list[str] | None = None) -> list[CachedTrack]:
-    """Fetch data associated with given track IDs. Pass None to fetch all."""
-    query = "SELECT * FROM tracks_view"
-    args = []
-    if track_ids is not None:
-        query += f" WHERE id IN ({','.join(['?']*len(track_ids))})"
-        args = track_ids
-    query += " ORDER BY source_path"
+def list_tracks(
+    c: Config,
+    track_ids: list[str] | None = None,
+    include_hidden: bool = False,
+) -> list[CachedTrack]:
+    """
+    Fetch data associated with given track IDs. Pass None to fetch all. Tracks of hidden releases
+    are excluded unless `include_hidden` is set.
+    """
+    where, args = _track_filters(track_ids, include_hidden)
     with connect(c) as conn:
-        cursor = conn.execute(query, args)
-        trackrows = cursor.fetchall()
+        cursor = conn.execute(f"SELECT * FROM tracks_view {where} ORDER BY source_path", args)
+        return _tracks_from_rows(c, conn, cursor.fetchall())
 
-        release_ids = [r["release_id"] for r in trackrows]
+
+def list_tracks_paginated(
+    c: Config,
+    limit: int,
+    offset: int = 0,
+    track_ids: list[str] | None = None,
+    include_hidden: bool = False,
+) -> tuple[list[CachedTrack], int]:
+    """
+    Fetch a page of the tracks, ordered by source path. Returns the page and the total number of
+    matching tracks. Only the releases of the tracks on the page are fetched.
+    """
+    where, args = _track_filters(track_ids, include_hidden)
+    with connect(c) as conn:
         cursor = conn.execute(
-            f"""
-            SELECT *
-            FROM releases_view
-            WHERE id IN ({','.join(['?']*len(release_ids))})
-            """,
-            release_ids,
+            f"SELECT * FROM tracks_view {where} ORDER BY source_path LIMIT ? OFFSET ?",
+            [*args, limit, offset],
         )
-        releases_map: dict[str, CachedRelease] = {}
-        for row in cursor:
-            releases_map[row["id"]] = CachedRelease.from_view(c, row)
+        tracks = _tracks_from_rows(c, conn, cursor.fetchall())
+        cursor = conn.execute(f"SELECT COUNT(*) FROM tracks_view {where}", args)
+        total = cursor.fetchone()[0]
+        return tracks, total
 
-        rval = []
-        for row in trackrows:
-            rval.append(CachedTrack.from_view(c, row, releases_map[row["release_id"]]))
-        return rval
+
+def _track_filters(track_ids: list[str] | None, include_hidden: bool) -> tuple[str, list[str]]:
+    query = "WHERE 1=1"
+    args: list[str] = []
+    if track_ids is not None:
+        query += f" AND id IN ({','.join(['?']*len(track_ids))})"
+        args = track_ids
+    if not include_hidden:
+        query += " AND release_id IN (SELECT id FROM releases WHERE NOT hidden)"
+    return query, args
+
+
+def _tracks_from_rows(
+    c: Config,
+    conn: sqlite3.Connection,
+    trackrows: list[sqlite3.Row],
+) -> list[CachedTrack]:
+    release_ids = list({r["release_id"] for r in trackrows})
+    cursor = conn.execute(
+        f"""
+        SELECT *
+        FROM releases_view
+        WHERE id IN ({','.join(['?']*len(release_ids))})
+        """,
+        release_ids,
+    )
+    releases_map: dict[str, CachedRelease] = {}
+    for row in cursor:
+        releases_map[row["id"]] = CachedRelease.from_view(c, row)
+    return [CachedTrack.from_view(c, row, releases_map[row["release_id"]]) for row in trackrows]
 
 
 def get_track(c: Config, uuid: str) -> CachedTrack | None:
@@ -1814,6 +2891,24 @@ def get_track(c: Config, uuid: str) -> CachedTrack | None:
         return CachedTrack.from_view(c, trackrow, release)
 
 
+def get_track_by_isrc(c: Config, isrc: str) -> CachedTrack | None:
+    """
+    Fetch a track by its ISRC. The same recording may appear on several releases, in which case the
+    track with the first source path is returned.
+    """
+    with connect(c) as conn:
+        cursor = conn.execute(
+            "SELECT * FROM tracks_view WHERE isrc = ? ORDER BY source_path LIMIT 1",
+            (isrc,),
+        )
+        trackrow = cursor.fetchone()
+        if not trackrow:
+            return None
+        cursor = conn.execute("SELECT * FROM releases_view WHERE id = ?", (trackrow["release_id"],))
+        release = CachedRelease.from_view(c, cursor.fetchone())
+        return CachedTrack.from_view(c, trackrow, release)
+
+
 def get_tracks_associated_with_release(
     c: Config,
     release: CachedRelease,
@@ -1862,6 +2957,187 @@ def get_tracks_associated_with_releases(
     return rval
 
 
+# The release types that are grouped into per-artist "Singles" releases.
+SINGLES_RELEASE_TYPES = ["single", "loosetrack"]
+
+
+def list_artist_singles(c: Config) -> list[CachedArtistSingles]:
+    """
+    Group the single and loose track releases by their first main release artist into synthetic
+    per-artist "Singles" releases, ordered by artist. Releases without a main artist and hidden
+    releases are left out.
+    """
+    with connect(c) as conn:
+        cursor = conn.execute(
+            f"""
+            SELECT * FROM releases_view
+            WHERE releasetype IN ({','.join(['?'] * len(SINGLES_RELEASE_TYPES))}) AND NOT hidden
+            ORDER BY source_path
+            """,
+            SINGLES_RELEASE_TYPES,
+        )
+        releases = [CachedRelease.from_view(c, row, aliases=False) for row in cursor]
+
+    groups: dict[str, CachedArtistSingles] = {}
+    for release, tracks in get_tracks_associated_with_releases(c, releases):
+        if not release.albumartists.main:
+            continue
+        artist = release.albumartists.main[0].name
+        if artist not in groups:
+            groups[artist] = CachedArtistSingles(artist=artist, releases=[], tracks=[])
+        groups[artist].releases.append(release)
+        groups[artist].tracks.extend(tracks)
+    return [groups[artist] for artist in sorted(groups)]
+
+
+def iter_releases_with_tracks(c: Config) -> Iterator[tuple[CachedRelease, list[CachedTrack]]]:
+    """
+    Stream every release with its tracks. Unlike `list_releases` and
+    `get_tracks_associated_with_releases`, this does not load the whole library into memory: it
+    walks a cursor over the releases and a cursor over the tracks in lockstep, both ordered by
+    release ID.
+    """
+    with connect(c) as conn:
+        release_cursor = conn.execute("SELECT * FROM releases_view ORDER BY id")
+        track_cursor = conn.execute(
+            """
+            SELECT *
+            FROM tracks_view
+            ORDER BY release_id, FORMAT('%4d.%4d', discnumber, tracknumber)
+            """
+        )
+        track_row = track_cursor.fetchone()
+        for release_row in release_cursor:
+            release = CachedRelease.from_view(c, release_row)
+            tracks: list[CachedTrack] = []
+            while track_row is not None and track_row["release_id"] == release.id:
+                tracks.append(CachedTrack.from_view(c, track_row, release))
+                track_row = track_cursor.fetchone()
+            yield release, tracks
+
+
+# The number of releases that `iter_tracks` keeps in memory for the tracks it yields.
+ITER_TRACKS_RELEASE_CACHE_SIZE = 64
+
+
+def iter_tracks(c: Config, include_hidden: bool = False) -> Iterator[CachedTrack]:
+    """
+    Stream every track, ordered by source path. Unlike `list_tracks`, this does not load the whole
+    library into memory: it walks a single cursor over the tracks and fetches each track's release
+    on demand. The most recently used releases are kept in a small LRU cache, so the tracks of one
+    release share a single release object and the release is not re-queried for each track.
+    """
+    query = "SELECT * FROM tracks_view"
+    if not include_hidden:
+        query += " WHERE release_id IN (SELECT id FROM releases WHERE NOT hidden)"
+    query += " ORDER BY source_path"
+    releases: OrderedDict[str, CachedRelease] = OrderedDict()
+    with connect(c) as conn:
+        for row in conn.execute(query):
+            release_id = row["release_id"]
+            if release_id in releases:
+                releases.move_to_end(release_id)
+            else:
+                cursor = conn.execute("SELECT * FROM releases_view WHERE id = ?", (release_id,))
+                releases[release_id] = CachedRelease.from_view(c, cursor.fetchone())
+                if len(releases) > ITER_TRACKS_RELEASE_CACHE_SIZE:
+                    releases.popitem(last=False)
+            yield CachedTrack.from_view(c, row, releases[release_id])
+
+
+def find_duplicate_tracks_by_audio(c: Config) -> list[list[CachedTrack]]:
+    """
+    Find groups of tracks whose audio streams are identical. This relies on the audio checksums, so
+    it only finds duplicates if `compute_audio_sha256` is enabled.
+    """
+    groups: dict[str, list[str]] = defaultdict(list)
+    with connect(c) as conn:
+        cursor = conn.execute(
+            """
+            SELECT id, audio_sha256
+            FROM tracks
+            WHERE audio_sha256 IN (
+                SELECT audio_sha256
+                FROM tracks
+                WHERE audio_sha256 IS NOT NULL
+                GROUP BY audio_sha256
+                HAVING COUNT(*) > 1
+            )
+            ORDER BY audio_sha256, source_path
+            """
+        )
+        for row in cursor:
+            groups[row["audio_sha256"]].append(row["id"])
+    if not groups:
+        return []
+    tracks = {
+        t.id: t for t in list_tracks(c, _flatten(list(groups.values())), include_hidden=True)
+    }
+    return [[tracks[track_id] for track_id in track_ids] for track_ids in groups.values()]
+
+
+@dataclass
+class IntegrityReport:
+    # Cached releases whose source directory no longer exists.
+    missing_release_dirs: list[Path]
+    # Cached tracks whose source file no longer exists.
+    missing_track_files: list[Path]
+    # Datafiles of existing releases that are missing or fail to parse.
+    unreadable_datafiles: list[Path]
+    # Full Text Search rows that no longer correspond to a track.
+    orphaned_fts_rowids: list[int]
+
+    @property
+    def ok(self) -> bool:
+        return not (
+            self.missing_release_dirs
+            or self.missing_track_files
+            or self.unreadable_datafiles
+            or self.orphaned_fts_rowids
+        )
+
+
+def verify_library(c: Config) -> IntegrityReport:
+    """
+    Check the read cache against the source directory and report any cache drift. This function
+    does not modify anything: most problems are resolved by running a cache update.
+    """
+    with connect(c) as conn:
+        cursor = conn.execute("SELECT id, source_path FROM releases ORDER BY source_path")
+        releases = [(row["id"], Path(row["source_path"])) for row in cursor]
+        cursor = conn.execute("SELECT source_path FROM tracks ORDER BY source_path")
+        track_paths = [Path(row["source_path"]) for row in cursor]
+        cursor = conn.execute(
+            """
+            SELECT rowid
+            FROM rules_engine_fts
+            WHERE rowid NOT IN (SELECT rowid FROM tracks)
+            ORDER BY rowid
+            """
+        )
+        orphaned_fts_rowids = [row["rowid"] for row in cursor]
+
+    missing_release_dirs: list[Path] = []
+    unreadable_datafiles: list[Path] = []
+    for release_id, source_path in releases:
+        if not source_path.is_dir():
+            missing_release_dirs.append(source_path)
+            continue
+        datafile_path = source_path / f".rose.{release_id}.toml"
+        try:
+            with datafile_path.open("rb") as fp:
+                tomllib.load(fp)
+        except (OSError, tomllib.TOMLDecodeError):
+            unreadable_datafiles.append(datafile_path)
+
+    return IntegrityReport(
+        missing_release_dirs=missing_release_dirs,
+        missing_track_files=[p for p in track_paths if not p.is_file()],
+        unreadable_datafiles=unreadable_datafiles,
+        orphaned_fts_rowids=orphaned_fts_rowids,
+    )
+
+
 def get_track_logtext(c: Config, track_id: str) -> str | None:
     """Get a human-readable identifier for a track suitable for logging."""
     with connect(c) as conn:
@@ -1876,11 +3152,17 @@ def get_track_logtext(c: Config, track_id: str) -> str | None:
             title=row["tracktitle"],
             artists=_unpack_artists(c, row["trackartist_names"], row["trackartist_roles"]),
             suffix=Path(row["source_path"]).suffix,
+            artist_role_order=c.artist_role_order,
         )
 
 
-def calculate_track_logtext(title: str, artists: ArtistMapping, suffix: str) -> str:
-    return f"{artistsfmt(artists)} - {title or 'Unknown Title'}{suffix}"
+def calculate_track_logtext(
+    title: str,
+    artists: ArtistMapping,
+    suffix: str,
+    artist_role_order: list[str] = DEFAULT_ARTIST_ROLE_ORDER,
+) -> str:
+    return f"{artistsfmt(artists, artist_role_order)} - {title or 'Unknown Title'}{suffix}"
 
 
 def list_playlists(c: Config) -> list[str]:
@@ -1889,6 +3171,49 @@ def list_playlists(c: Config) -> list[str]:
         return [r["name"] for r in cursor]
 
 
+def list_playlists_of_track(c: Config, track_id: str) -> list[str]:
+    """List the names of the playlists that contain the track, including as a missing track."""
+    with connect(c) as conn:
+        cursor = conn.execute(
+            """
+            SELECT DISTINCT playlist_name
+            FROM playlists_tracks
+            WHERE track_id = ?
+            ORDER BY playlist_name
+            """,
+            (track_id,),
+        )
+        return [r["playlist_name"] for r in cursor]
+
+
+def missing_playlist_tracks(c: Config) -> list[tuple[str, str]]:
+    """
+    List the (playlist name, track ID) pairs of every track that is marked as missing in a playlist,
+    across all playlists, so that the missing files can be tracked down.
+    """
+    with connect(c) as conn:
+        cursor = conn.execute(
+            """
+            SELECT playlist_name, track_id
+            FROM playlists_tracks
+            WHERE missing
+            ORDER BY playlist_name, position
+            """
+        )
+        return [(r["playlist_name"], r["track_id"]) for r in cursor]
+
+
+def recent_playlists(c: Config, n: int) -> list[str]:
+    """List the names of the `n` most recently modified playlists, most recent first."""
+    with connect(c) as conn:
+        # The mtime is a stringified epoch, so cast it to compare numerically.
+        cursor = conn.execute(
+            "SELECT name FROM playlists ORDER BY CAST(source_mtime AS REAL) DESC, name LIMIT ?",
+            (n,),
+        )
+        return [r["name"] for r in cursor]
+
+
 def get_playlist(c: Config, playlist_name: str) -> tuple[CachedPlaylist, list[CachedTrack]] | None:
     with connect(c) as conn:
         cursor = conn.execute(
@@ -1952,7 +3277,42 @@ def list_collages(c: Config) -> list[str]:
         return [r["name"] for r in cursor]
 
 
-def get_collage(c: Config, collage_name: str) -> tuple[CachedCollage, list[CachedRelease]] | None:
+def list_collages_of_release(c: Config, release_id: str) -> list[str]:
+    """List the names of the collages that contain the release, including as a missing release."""
+    with connect(c) as conn:
+        cursor = conn.execute(
+            """
+            SELECT DISTINCT collage_name
+            FROM collages_releases
+            WHERE release_id = ?
+            ORDER BY collage_name
+            """,
+            (release_id,),
+        )
+        return [r["collage_name"] for r in cursor]
+
+
+def recent_collages(c: Config, n: int) -> list[str]:
+    """List the names of the `n` most recently modified collages, most recent first."""
+    with connect(c) as conn:
+        # The mtime is a stringified epoch, so cast it to compare numerically.
+        cursor = conn.execute(
+            "SELECT name FROM collages ORDER BY CAST(source_mtime AS REAL) DESC, name LIMIT ?",
+            (n,),
+        )
+        return [r["name"] for r in cursor]
+
+
+def get_collage(
+    c: Config,
+    collage_name: str,
+    order_by: Literal["position", "added_at"] = "position",
+) -> tuple[CachedCollage, list[CachedRelease]] | None:
+    """
+    Fetch a collage and its non-missing releases. The releases are ordered by their position in the
+    collage, or by when they were added to the collage if `order_by` is `added_at`. Releases without
+    an added-at timestamp sort last, in position order.
+    """
     with connect(c) as conn:
         cursor = conn.execute(
             "SELECT name, source_mtime FROM collages WHERE name = ?",
@@ -1967,13 +3327,16 @@ def get_collage(c: Config, collage_name: str) -> tuple[CachedCollage, list[Cache
             # Accumulated below when we query the releases.
             release_ids=[],
         )
+        order = "cr.position ASC"
+        if order_by == "added_at":
+            order = "cr.added_at IS NULL, cr.added_at ASC, cr.position ASC"
         cursor = conn.execute(
-            """
+            f"""
             SELECT r.*
             FROM releases_view r
             JOIN collages_releases cr ON cr.release_id = r.id
             WHERE cr.collage_name = ? AND NOT cr.missing
-            ORDER BY cr.position ASC
+            ORDER BY {order}
             """,
             (collage_name,),
         )
@@ -1985,12 +3348,125 @@ def get_collage(c: Config, collage_name: str) -> tuple[CachedCollage, list[Cache
     return (collage, releases)
 
 
+def get_tracks_of_collage(c: Config, collage_name: str) -> list[CachedTrack] | None:
+    """
+    Return the tracks of every non-missing release in the collage, ordered by the releases'
+    positions in the collage and then by their disc and track numbers. Returns None if the collage
+    does not exist.
+    """
+    with connect(c) as conn:
+        cursor = conn.execute("SELECT 1 FROM collages WHERE name = ?", (collage_name,))
+        if not cursor.fetchone():
+            return None
+        cursor = conn.execute(
+            """
+            SELECT r.*
+            FROM releases_view r
+            JOIN collages_releases cr ON cr.release_id = r.id
+            WHERE cr.collage_name = ? AND NOT cr.missing
+            """,
+            (collage_name,),
+        )
+        releases_map = {row["id"]: CachedRelease.from_view(c, row) for row in cursor}
+        cursor = conn.execute(
+            """
+            SELECT t.*
+            FROM tracks_view t
+            JOIN collages_releases cr ON cr.release_id = t.release_id
+            WHERE cr.collage_name = ? AND NOT cr.missing
+            ORDER BY cr.position ASC, FORMAT('%4d.%4d', t.discnumber, t.tracknumber)
+            """,
+            (collage_name,),
+        )
+        return [CachedTrack.from_view(c, row, releases_map[row["release_id"]]) for row in cursor]
+
+
 def list_artists(c: Config) -> list[tuple[str, str]]:
     with connect(c) as conn:
         cursor = conn.execute("SELECT DISTINCT artist, artist_sanitized FROM releases_artists")
         return [(row["artist"], row["artist_sanitized"]) for row in cursor]
 
 
+def list_artists_with_counts(c: Config) -> list[tuple[str, int]]:
+    """
+    List each artist with the number of distinct releases they are credited on, in any role.
+    Releases credited to an alias also count towards the alias's parent artists.
+    """
+    artist_releases: dict[str, set[str]] = defaultdict(set)
+    with connect(c) as conn:
+        cursor = conn.execute(
+            """
+            SELECT artist, GROUP_CONCAT(DISTINCT release_id) AS release_ids
+            FROM releases_artists
+            GROUP BY artist
+            """
+        )
+        for row in cursor:
+            release_ids = row["release_ids"].split(",")
+            artist_releases[row["artist"]].update(release_ids)
+            for parent in c.artist_aliases_parents_map.get(row["artist"], []):
+                artist_releases[parent].update(release_ids)
+    return [(artist, len(release_ids)) for artist, release_ids in artist_releases.items()]
+
+
+def releases_by_year(c: Config) -> list[tuple[int | None, int]]:
+    """
+    Count the releases of each year, in ascending order of year. Undated releases are counted in a
+    final `None` bucket. Hidden releases are not counted.
+    """
+    return _count_releases_by_year_bucket(c, "year")
+
+
+def releases_by_decade(c: Config) -> list[tuple[int | None, int]]:
+    """
+    Count the releases of each decade, keyed by the decade's first year (e.g. 1990 for the 90s).
+    Undated releases are counted in a final `None` bucket. Hidden releases are not counted.
+    """
+    return _count_releases_by_year_bucket(c, "year / 10 * 10")
+
+
+def _count_releases_by_year_bucket(c: Config, bucket: str) -> list[tuple[int | None, int]]:
+    # `bucket` is one of the constant expressions above, so user input never reaches the SQL.
+    with connect(c) as conn:
+        cursor = conn.execute(
+            f"""
+            SELECT {bucket} AS bucket, COUNT(*) AS count
+            FROM releases
+            WHERE NOT hidden
+            GROUP BY bucket
+            ORDER BY bucket IS NULL, bucket
+            """
+        )
+        return [(row["bucket"], row["count"]) for row in cursor]
+
+
+def get_artist_tracks_by_role(c: Config, artist: str, role: str) -> list[CachedTrack]:
+    """
+    Fetch the tracks that credit the artist (or one of its aliases) in the given role, e.g. every
+    track an artist produced. A release-level credit applies to every track of the release.
+    """
+    names = [artist, *c.artist_aliases_map.get(artist, [])]
+    placeholders = ",".join(["?"] * len(names))
+    with connect(c) as conn:
+        cursor = conn.execute(
+            f"""
+            SELECT t.id
+            FROM tracks t
+            WHERE t.id IN (
+                SELECT track_id FROM tracks_artists
+                WHERE role = ? AND artist IN ({placeholders})
+            )
+            OR t.release_id IN (
+                SELECT release_id FROM releases_artists
+                WHERE role = ? AND artist IN ({placeholders})
+            )
+            """,
+            [role, *names, role, *names],
+        )
+        track_ids = [row["id"] for row in cursor]
+    return list_tracks(c, track_ids)
+
+
 def artist_exists(c: Config, artist_sanitized: str) -> bool:
     args: list[str] = [artist_sanitized]
     for alias in c.sanitized_artist_aliases_map.get(artist_sanitized, []):
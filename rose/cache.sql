@@ -9,6 +9,12 @@ CREATE TABLE releases (
     id TEXT PRIMARY KEY,
     source_path TEXT NOT NULL UNIQUE,
     cover_image_path TEXT,
+    -- A sha256 of the cover image file's bytes.
+    cover_hash TEXT,
+    -- The size and mtime of the cover image file when it was hashed. The cover is only rehashed
+    -- when these change.
+    cover_size INTEGER,
+    cover_mtime TEXT,
     -- ISO8601 timestamp.
     added_at TEXT NOT NULL,
     datafile_mtime TEXT NOT NULL,
@@ -20,10 +26,13 @@ CREATE TABLE releases (
     -- A sha256() of the release object, which can be used as a performant cache
     -- key.
     metahash TEXT NOT NULL UNIQUE,
-    new BOOLEAN NOT NULL DEFAULT true
+    new BOOLEAN NOT NULL DEFAULT true,
+    -- Hidden releases are excluded from browsing, but their files are kept.
+    hidden BOOLEAN NOT NULL DEFAULT false
 );
 CREATE INDEX releases_source_path ON releases(source_path);
 CREATE INDEX releases_new ON releases(new);
+CREATE INDEX releases_hidden ON releases(hidden);
 
 CREATE TABLE releases_genres (
     release_id TEXT REFERENCES releases(id) ON DELETE CASCADE,
@@ -54,14 +63,25 @@ CREATE TABLE tracks (
     source_path TEXT NOT NULL UNIQUE,
     source_mtime TEXT NOT NULL,
     title TEXT NOT NULL,
+    -- The version of the track, e.g. "Radio Edit".
+    subtitle TEXT,
     release_id TEXT NOT NULL REFERENCES releases(id) ON DELETE CASCADE,
     tracknumber TEXT NOT NULL,
     -- Per-disc track total.
     tracktotal INTEGER NOT NULL,
     discnumber TEXT NOT NULL,
+    -- The name of the disc the track is on. Aggregated per disc into the release's disc titles.
+    discsubtitle TEXT,
     -- Disctotal is also denormalized on the release.
     disctotal INTEGER NOT NULL,
     duration_seconds INTEGER NOT NULL,
+    -- A sha256 of the audio stream, excluding tags. Null unless compute_audio_sha256 is enabled.
+    audio_sha256 TEXT,
+    -- The International Standard Recording Code of the track, if tagged.
+    isrc TEXT,
+    -- The path of the file, relative to the release directory, before Rosé first renamed it. This
+    -- is only stored in the cache, so it is lost when the cache is rebuilt.
+    original_filename TEXT,
     -- A sha256 of the release object, which can be used as a performant cache
     -- key.
     metahash TEXT NOT NULL UNIQUE
@@ -72,6 +92,18 @@ CREATE INDEX tracks_ordering ON tracks(release_id, discnumber, tracknumber);
 CREATE INDEX tracks_title ON tracks(title);
 CREATE INDEX tracks_tracknumber ON tracks(tracknumber);
 CREATE INDEX tracks_discnumber ON tracks(discnumber);
+CREATE INDEX tracks_audio_sha256 ON tracks(audio_sha256);
+CREATE INDEX tracks_isrc ON tracks(isrc);
+
+-- Audio files shorter than `min_track_duration_seconds`, which are not indexed as tracks. Their
+-- durations are cached so that their tags are not re-read on every cache update.
+CREATE TABLE short_tracks (
+    source_path TEXT PRIMARY KEY,
+    release_source_path TEXT NOT NULL,
+    source_mtime TEXT NOT NULL,
+    duration_seconds INTEGER NOT NULL
+);
+CREATE INDEX short_tracks_release_source_path ON short_tracks(release_source_path);
 
 CREATE TABLE artist_role_enum (value TEXT PRIMARY KEY);
 INSERT INTO artist_role_enum (value) VALUES
@@ -120,7 +152,14 @@ CREATE TABLE collages_releases (
     -- deletion and reinsertion.
     release_id TEXT,
     position INTEGER NOT NULL,
-    missing BOOL NOT NULL
+    missing BOOL NOT NULL,
+    -- The number of consecutive collage updates that the release has been absent
+    -- for. Releases are only flagged as missing once this exceeds the configured
+    -- grace period.
+    absent_updates INTEGER NOT NULL DEFAULT 0,
+    -- ISO8601 timestamp of when the release was added to the collage. Null for
+    -- entries added before this was tracked.
+    added_at TEXT
 );
 CREATE INDEX collages_releases_collage_name ON collages_releases(collage_name);
 CREATE INDEX collages_releases_access ON collages_releases(collage_name, missing, release_id);
@@ -193,11 +232,27 @@ CREATE VIEW releases_view AS
           , GROUP_CONCAT(role, ' ¬ ') AS roles
         FROM (SELECT * FROM releases_artists ORDER BY release_id, position)
         GROUP BY release_id
+    ), disc_titles AS (
+        SELECT
+            release_id
+          , GROUP_CONCAT(discnumber, ' ¬ ') AS discnumbers
+          , GROUP_CONCAT(discsubtitle, ' ¬ ') AS discsubtitles
+        FROM (
+            SELECT release_id, discnumber, MIN(discsubtitle) AS discsubtitle
+            FROM tracks
+            WHERE COALESCE(discsubtitle, '') != ''
+            GROUP BY release_id, discnumber
+            ORDER BY release_id, discnumber
+        )
+        GROUP BY release_id
     )
     SELECT
         r.id
       , r.source_path
       , r.cover_image_path
+      , r.cover_hash
+      , r.cover_size
+      , r.cover_mtime
       , r.added_at
       , r.datafile_mtime
       , r.title AS albumtitle
@@ -205,15 +260,19 @@ CREATE VIEW releases_view AS
       , r.year
       , r.disctotal
       , r.new
+      , r.hidden
       , r.metahash
       , COALESCE(g.genres, '') AS genres
       , COALESCE(l.labels, '') AS labels
       , COALESCE(a.names, '') AS albumartist_names
       , COALESCE(a.roles, '') AS albumartist_roles
+      , COALESCE(d.discnumbers, '') AS disc_title_discnumbers
+      , COALESCE(d.discsubtitles, '') AS disc_title_subtitles
     FROM releases r
     LEFT JOIN genres g ON g.release_id = r.id
     LEFT JOIN labels l ON l.release_id = r.id
-    LEFT JOIN artists a ON a.release_id = r.id;
+    LEFT JOIN artists a ON a.release_id = r.id
+    LEFT JOIN disc_titles d ON d.release_id = r.id;
 
 CREATE VIEW tracks_view AS
     WITH artists AS (
@@ -229,12 +288,17 @@ CREATE VIEW tracks_view AS
       , t.source_path
       , t.source_mtime
       , t.title AS tracktitle
+      , t.subtitle AS tracksubtitle
       , t.release_id
       , t.tracknumber
       , t.tracktotal
       , t.discnumber
       , t.disctotal
+      , t.discsubtitle
       , t.duration_seconds
+      , t.audio_sha256
+      , t.isrc
+      , t.original_filename
       , t.metahash
       , COALESCE(a.names, '') AS trackartist_names
       , COALESCE(a.roles, '') AS trackartist_roles
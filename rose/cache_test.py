@@ -1,13 +1,27 @@
 import dataclasses
 import hashlib
+import multiprocessing
+import os
 import shutil
 import time
+import uuid
+from datetime import datetime
 from pathlib import Path
+from typing import Any
 
+import PIL.Image
 import pytest
+import tomli_w
 import tomllib
 
-from conftest import TEST_COLLAGE_1, TEST_PLAYLIST_1, TEST_RELEASE_1, TEST_RELEASE_2, TEST_RELEASE_3
+from conftest import (
+    TEST_COLLAGE_1,
+    TEST_PLAYLIST_1,
+    TEST_RELEASE_1,
+    TEST_RELEASE_2,
+    TEST_RELEASE_3,
+    TEST_TAGGER,
+)
 from rose.audiotags import AudioTags
 from rose.cache import (
     CACHE_SCHEMA_PATH,
@@ -17,33 +31,64 @@ from rose.cache import (
     CachedRelease,
     CachedTrack,
     _unpack,
+    apply_template_renames,
     artist_exists,
+    classify_directory,
     connect,
+    find_duplicate_tracks_by_audio,
     genre_exists,
+    get_artist_tracks_by_role,
     get_collage,
     get_playlist,
     get_release,
+    get_release_by_path,
     get_release_logtext,
+    get_releases,
     get_track,
+    get_track_by_isrc,
     get_track_logtext,
     get_tracks_associated_with_release,
     get_tracks_associated_with_releases,
+    get_tracks_of_collage,
+    iter_tracks,
     label_exists,
+    list_artist_singles,
     list_artists,
+    list_artists_with_counts,
     list_collages,
+    list_collages_of_release,
     list_genres,
     list_labels,
     list_playlists,
+    list_playlists_of_track,
     list_releases,
+    list_releases_delete_this,
+    list_releases_paginated,
     list_tracks,
+    list_tracks_paginated,
     lock,
     maybe_invalidate_cache_database,
+    missing_playlist_tracks,
+    recent_collages,
+    recent_playlists,
+    refresh_all_descriptions,
+    release_lock_name,
+    releases_by_decade,
+    releases_by_year,
+    releases_not_in_any_collage,
     update_cache,
     update_cache_evict_nonexistent_releases,
+    update_cache_for_collages,
+    update_cache_for_files,
     update_cache_for_releases,
+    verify_library,
+    wanted_release_dirname,
+    wanted_track_filename,
+    write_stored_data_file,
 )
 from rose.common import VERSION, Artist, ArtistMapping
-from rose.config import Config
+from rose.config import Config, LockTimeouts
+from rose.templates import PathTemplate, PathTemplatePair
 
 
 def test_schema(config: Config) -> None:
@@ -93,6 +138,42 @@ def test_migration(config: Config) -> None:
         assert cursor.fetchone()[0] == 1
 
 
+def test_migration_try_migrate(
+    config: Config, tmp_path: Path, monkeypatch: pytest.MonkeyPatch
+) -> None:
+    """Test that a registered additive migration upgrades the cache in place."""
+    maybe_invalidate_cache_database(config)
+    with connect(config) as conn:
+        conn.execute("INSERT INTO locks (name, valid_until) VALUES ('lol', 1)")
+
+    old_schema = CACHE_SCHEMA_PATH.read_text()
+    old_hash = hashlib.sha256(CACHE_SCHEMA_PATH.read_bytes()).hexdigest()
+    migration = "ALTER TABLE releases ADD COLUMN catalognumber TEXT;\n"
+    new_schema_path = tmp_path / "cache.sql"
+    new_schema_path.write_text(old_schema + migration)
+    new_hash = hashlib.sha256(new_schema_path.read_bytes()).hexdigest()
+    monkeypatch.setattr("rose.cache.CACHE_SCHEMA_PATH", new_schema_path)
+    monkeypatch.setattr("rose.cache.CACHE_MIGRATIONS", {old_hash: (new_hash, migration)})
+
+    config = dataclasses.replace(config, cache_migration_mode="try_migrate")
+    maybe_invalidate_cache_database(config)
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT name FROM locks")
+        assert [r["name"] for r in cursor] == ["lol"]
+        cursor = conn.execute("SELECT name FROM pragma_table_info('releases')")
+        assert "catalognumber" in [r["name"] for r in cursor]
+        cursor = conn.execute("SELECT schema_hash FROM _schema_hash")
+        assert cursor.fetchone()["schema_hash"] == new_hash
+
+    # Without a registered migration, the cache is rebuilt.
+    monkeypatch.setattr("rose.cache.CACHE_MIGRATIONS", {})
+    new_schema_path.write_text(old_schema)
+    maybe_invalidate_cache_database(config)
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT COUNT(*) FROM locks")
+        assert cursor.fetchone()[0] == 0
+
+
 def test_locks(config: Config) -> None:
     """Test that taking locks works. The times are a bit loose b/c GH Actions is slow."""
     lock_name = "lol"
@@ -118,6 +199,40 @@ def test_locks(config: Config) -> None:
     assert lock2_acq - lock1_acq < 0.08
 
 
+def test_lock_uses_configured_timeout(config: Config) -> None:
+    config = dataclasses.replace(config, lock_timeout_seconds=LockTimeouts(release=30.0))
+    lock_name = release_lock_name("ilovecarly")
+    with lock(config, lock_name), connect(config) as conn:
+        cursor = conn.execute("SELECT valid_until FROM locks WHERE name = ?", (lock_name,))
+        assert 29 < cursor.fetchone()[0] - time.time() <= 30
+
+
+def test_classify_directory(config: Config) -> None:
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    (release_dir / "02.m4a").rename(release_dir / "02.M4A")
+    (release_dir / "Cover.JPG").touch()
+    (release_dir / ".rose.abc.toml").touch()
+    (release_dir / "03.wav").touch()
+
+    def kinds(c: Config) -> dict[str, str]:
+        return {x.path.name: x.kind for x in classify_directory(c, release_dir)}
+
+    assert kinds(config) == {
+        "01.m4a": "audio",
+        "02.M4A": "audio",
+        "Cover.JPG": "cover",
+        ".rose.abc.toml": "datafile",
+        "03.wav": "unsupported",
+        "ignorethis.file": "unsupported",
+    }
+    # The test tracks are 2 seconds long.
+    config = dataclasses.replace(config, min_track_duration_seconds=5)
+    assert kinds(config)["01.m4a"] == "ignored"
+    config = dataclasses.replace(config, ignore_release_directories=[TEST_RELEASE_1.name])
+    assert set(kinds(config).values()) == {"ignored"}
+
+
 def test_update_cache_all(config: Config) -> None:
     """Test that the update all function works."""
     shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
@@ -153,6 +268,27 @@ def test_update_cache_multiprocessing(config: Config) -> None:
         assert cursor.fetchone()[0] == 4
 
 
+def test_update_cache_multiprocessing_max_proc(monkeypatch: Any, config: Config) -> None:
+    config = dataclasses.replace(config, max_proc=1)
+    pool_sizes: list[int] = []
+    original_pool = multiprocessing.Pool
+
+    def recording_pool(processes: int) -> Any:
+        pool_sizes.append(processes)
+        return original_pool(processes=processes)
+
+    monkeypatch.setattr("rose.cache.multiprocessing.Pool", recording_pool)
+    shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
+    shutil.copytree(TEST_RELEASE_2, config.music_source_dir / TEST_RELEASE_2.name)
+    update_cache_for_releases(config, force_multiprocessing=True)
+    assert pool_sizes == [1]
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT COUNT(*) FROM releases")
+        assert cursor.fetchone()[0] == 2
+        cursor = conn.execute("SELECT COUNT(*) FROM tracks")
+        assert cursor.fetchone()[0] == 4
+
+
 def test_update_cache_releases(config: Config) -> None:
     release_dir = config.music_source_dir / TEST_RELEASE_1.name
     shutil.copytree(TEST_RELEASE_1, release_dir)
@@ -235,6 +371,77 @@ def test_update_cache_releases(config: Config) -> None:
             }
 
 
+def test_update_cache_releases_uuid_v4(config: Config) -> None:
+    config = dataclasses.replace(config, id_uuid_version=4)
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    update_cache_for_releases(config, [release_dir])
+
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT id FROM releases")
+        release_ids = [r["id"] for r in cursor]
+        cursor = conn.execute("SELECT id FROM tracks")
+        track_ids = [r["id"] for r in cursor]
+    assert len(release_ids) == 1
+    assert len(track_ids) == 2
+    for id_ in [*release_ids, *track_ids]:
+        assert uuid.UUID(id_).version == 4
+
+
+def test_update_cache_releases_uppercase_extension(config: Config) -> None:
+    release_dir = config.music_source_dir / "Uppercase"
+    release_dir.mkdir()
+    shutil.copyfile(TEST_TAGGER / "track1.flac", release_dir / "Track.FLAC")
+    (release_dir / "Cover.JPG").touch()
+    update_cache_for_releases(config, [release_dir])
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT source_path FROM tracks")
+        assert [r["source_path"] for r in cursor] == [str(release_dir / "Track.FLAC")]
+        cursor = conn.execute("SELECT cover_image_path FROM releases")
+        assert cursor.fetchone()["cover_image_path"] == str(release_dir / "Cover.JPG")
+
+
+def test_update_cache_follow_symlinks(config: Config, tmp_path: Path) -> None:
+    external_dir = tmp_path / "external" / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, external_dir)
+    # A symlink cycle inside the release must not be followed forever.
+    (external_dir / "loop").symlink_to(external_dir, target_is_directory=True)
+    (config.music_source_dir / "Linked").symlink_to(external_dir, target_is_directory=True)
+
+    # Symlinked releases are not indexed by default.
+    update_cache(config)
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT COUNT(*) FROM releases")
+        assert cursor.fetchone()[0] == 0
+
+    config = dataclasses.replace(config, follow_symlinks=True)
+    update_cache(config)
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT source_path FROM releases")
+        assert [r["source_path"] for r in cursor] == [str(external_dir.resolve())]
+        cursor = conn.execute("SELECT COUNT(*) FROM tracks")
+        assert cursor.fetchone()[0] == 2
+
+
+def test_update_cache_follow_symlinks_does_not_rename_symlinked_release(
+    config: Config, tmp_path: Path
+) -> None:
+    config = dataclasses.replace(config, follow_symlinks=True, rename_source_dirs=True)
+    external_dir = tmp_path / "external" / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, external_dir)
+    link = config.music_source_dir / "Linked"
+    link.symlink_to(external_dir, target_is_directory=True)
+
+    update_cache(config)
+    assert external_dir.is_dir()
+    assert link.resolve() == external_dir.resolve()
+    # A second update must not evict the release through a dangling symlink.
+    update_cache(config)
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT source_path FROM releases")
+        assert [r["source_path"] for r in cursor] == [str(external_dir.resolve())]
+
+
 def test_update_cache_releases_uncached_with_existing_id(config: Config) -> None:
     """Test that IDs in filenames are read and preserved."""
     release_dir = config.music_source_dir / TEST_RELEASE_2.name
@@ -377,6 +584,153 @@ def test_update_cache_releases_disk_upgrade_old_datafile(config: Config) -> None
         assert "added_at = " in data
 
 
+def test_update_cache_releases_unchanged_datafile_not_rewritten(config: Config) -> None:
+    """Test that a forced update with no datafile content change does not rewrite the datafile."""
+    release_dir = config.music_source_dir / TEST_RELEASE_2.name
+    shutil.copytree(TEST_RELEASE_2, release_dir)
+    datafile = next(f for f in release_dir.iterdir() if STORED_DATA_FILE_REGEX.match(f.name))
+    # The first update writes the default values missing from the testdata datafile.
+    update_cache(config)
+    mtime = datafile.stat().st_mtime_ns
+
+    time.sleep(0.01)
+    update_cache(config, force=True)
+    assert datafile.stat().st_mtime_ns == mtime
+
+
+def test_update_cache_releases_cover_replaced_in_place(config: Config) -> None:
+    """Test that replacing the cover's bytes at the same path updates the cover hash."""
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    cover = release_dir / "cover.jpg"
+    cover.write_bytes(b"old cover")
+    update_cache_for_releases(config, [release_dir])
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT cover_hash, metahash FROM releases")
+        row = cursor.fetchone()
+        assert row["cover_hash"] == hashlib.sha256(b"old cover").hexdigest()
+        old_metahash = row["metahash"]
+
+    # Sync tools may preserve the mtime; the changed size triggers the rehash.
+    stat = cover.stat()
+    cover.write_bytes(b"new cover art")
+    os.utime(cover, ns=(stat.st_atime_ns, stat.st_mtime_ns))
+    update_cache_for_releases(config, [release_dir])
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT cover_image_path, cover_hash, metahash FROM releases")
+        row = cursor.fetchone()
+        assert row["cover_image_path"] == str(cover)
+        assert row["cover_hash"] == hashlib.sha256(b"new cover art").hexdigest()
+        assert row["metahash"] != old_metahash
+
+
+def test_update_cache_releases_cover_not_rehashed_when_stat_unchanged(config: Config) -> None:
+    """Test that the cover is only rehashed when its size or mtime changes."""
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    cover = release_dir / "cover.jpg"
+    cover.write_bytes(b"old cover")
+    update_cache_for_releases(config, [release_dir])
+
+    # Same size and mtime: the cached hash is kept without reading the cover.
+    stat = cover.stat()
+    cover.write_bytes(b"new cover")
+    os.utime(cover, ns=(stat.st_atime_ns, stat.st_mtime_ns))
+    update_cache_for_releases(config, [release_dir])
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT cover_hash FROM releases")
+        assert cursor.fetchone()["cover_hash"] == hashlib.sha256(b"old cover").hexdigest()
+
+    # A new mtime triggers the rehash.
+    os.utime(cover, ns=(stat.st_atime_ns, stat.st_mtime_ns + 1_000_000_000))
+    update_cache_for_releases(config, [release_dir])
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT cover_hash FROM releases")
+        assert cursor.fetchone()["cover_hash"] == hashlib.sha256(b"new cover").hexdigest()
+
+
+def test_update_cache_releases_converts_unsupported_cover(config: Config) -> None:
+    """Test that a TIFF cover is converted to a JPEG in the cache when configured."""
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    PIL.Image.new("RGB", (4, 4), "red").save(release_dir / "cover.tiff", "TIFF")
+
+    update_cache_for_releases(config, [release_dir])
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT cover_image_path FROM releases")
+        assert cursor.fetchone()["cover_image_path"] is None
+
+    config = dataclasses.replace(config, convert_unsupported_covers=True)
+    update_cache_for_releases(config, [release_dir], force=True)
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT cover_image_path FROM releases")
+        cover = Path(cursor.fetchone()["cover_image_path"])
+    assert cover.is_relative_to(config.cache_dir)
+    assert cover.suffix == ".jpg"
+    with PIL.Image.open(cover) as im:
+        assert im.format == "JPEG"
+        assert im.size == (4, 4)
+    # The source cover is left untouched.
+    assert (release_dir / "cover.tiff").is_file()
+
+
+def test_update_cache_dry_run_does_not_convert_covers(config: Config) -> None:
+    config = dataclasses.replace(config, convert_unsupported_covers=True)
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    PIL.Image.new("RGB", (4, 4), "red").save(release_dir / "cover.tiff", "TIFF")
+
+    report = update_cache(config, dry_run=True)
+    assert not (config.cache_dir / "converted-covers").exists()
+    assert len(report.converted_covers) == 1
+    source, converted = report.converted_covers[0]
+    assert source == release_dir / "cover.tiff"
+    assert converted.parent == config.cache_dir / "converted-covers"
+
+    # The real update converts the cover to the reported path.
+    report = update_cache(config)
+    assert report.converted_covers == [(source, converted)]
+    assert converted.is_file()
+
+
+def test_update_cache_extract_embedded_covers(config: Config) -> None:
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    update_cache_for_releases(config, [release_dir])
+    assert not (release_dir / "cover.png").exists()
+
+    config = dataclasses.replace(config, extract_embedded_covers=True)
+    update_cache_for_releases(config, [release_dir], force=True)
+    cover = release_dir / "cover.png"
+    assert cover.read_bytes().startswith(b"\x89PNG")
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT cover_image_path FROM releases")
+        assert Path(cursor.fetchone()["cover_image_path"]) == cover
+
+    # The embedded cover art is only looked for when the first track changed since the last scan.
+    cover.unlink()
+    update_cache_for_releases(config, [release_dir])
+    assert not cover.exists()
+    for f in release_dir.glob("*.m4a"):
+        f.touch()
+    update_cache_for_releases(config, [release_dir])
+    assert cover.read_bytes().startswith(b"\x89PNG")
+
+
+def test_write_stored_data_file(isolated_dir: Path) -> None:
+    datafile = isolated_dir / ".rose.lalala.toml"
+    data = {"new": True, "added_at": "2023-10-23T00:00:00-04:00"}
+    assert write_stored_data_file(datafile, data)
+    mtime = datafile.stat().st_mtime_ns
+
+    time.sleep(0.01)
+    assert not write_stored_data_file(datafile, data)
+    assert datafile.stat().st_mtime_ns == mtime
+    assert write_stored_data_file(datafile, {**data, "new": False})
+    with datafile.open("rb") as fp:
+        assert tomllib.load(fp)["new"] is False
+
+
 def test_update_cache_releases_source_path_renamed(config: Config) -> None:
     """Test that a cached release is updated after a directory rename."""
     release_dir = config.music_source_dir / TEST_RELEASE_1.name
@@ -513,6 +867,74 @@ def test_update_cache_releases_ignores_directories(config: Config) -> None:
         assert cursor.fetchone()[0] == 0
 
 
+def test_update_cache_releases_derives_albumartists_from_tracks(config: Config) -> None:
+    """Test that release artists are derived from the track artists if albumartists are missing."""
+    config = dataclasses.replace(config, derive_albumartist_from_tracks=True)
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    af = AudioTags.from_file(release_dir / "01.m4a")
+    af.albumartists = ArtistMapping()
+    af.flush()
+    af = AudioTags.from_file(release_dir / "02.m4a")
+    af.albumartists = ArtistMapping()
+    af.trackartists = ArtistMapping(main=[Artist("Lisa"), Artist("BLACKPINK")])
+    af.flush()
+
+    update_cache_for_releases(config, [release_dir])
+    with connect(config) as conn:
+        cursor = conn.execute(
+            "SELECT artist, role FROM releases_artists ORDER BY position",
+        )
+        artists = [(r["artist"], r["role"]) for r in cursor.fetchall()]
+        assert artists == [("BLACKPINK", "main"), ("Lisa", "main")]
+
+
+def test_update_cache_releases_derives_various_artists(config: Config) -> None:
+    """Test that a release with many distinct track artists is credited to Various Artists."""
+    config = dataclasses.replace(
+        config, derive_albumartist_from_tracks=True, va_artist_threshold=10
+    )
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    for i, fname in enumerate(["01.m4a", "02.m4a"]):
+        af = AudioTags.from_file(release_dir / fname)
+        af.albumartists = ArtistMapping()
+        af.trackartists = ArtistMapping(main=[Artist(f"Artist {i * 6 + j}") for j in range(6)])
+        af.flush()
+
+    update_cache_for_releases(config, [release_dir])
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT artist, role FROM releases_artists")
+        artists = [(r["artist"], r["role"]) for r in cursor.fetchall()]
+        assert artists == [("Various Artists", "main")]
+
+
+def test_update_cache_releases_trackartists_fall_back_to_albumartists(config: Config) -> None:
+    """Test that tracks without artist tags are credited to the release artists."""
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    af = AudioTags.from_file(release_dir / "01.m4a")
+    af.albumartists = ArtistMapping(main=[Artist("BLACKPINK")])
+    af.trackartists = ArtistMapping()
+    af.flush()
+
+    update_cache_for_releases(config, [release_dir])
+    with connect(config) as conn:
+        cursor = conn.execute(
+            """
+            SELECT ta.artist, ta.role
+            FROM tracks_artists ta JOIN tracks t ON t.id = ta.track_id
+            WHERE t.source_path = ?
+            """,
+            (str(release_dir / "01.m4a"),),
+        )
+        artists = [(r["artist"], r["role"]) for r in cursor.fetchall()]
+        assert artists == [("BLACKPINK", "main")]
+    # The fallback is not written back to the file.
+    af = AudioTags.from_file(release_dir / "01.m4a")
+    assert not af.trackartists.all
+
+
 def test_update_cache_releases_notices_deleted_track(config: Config) -> None:
     """Test that we notice when a track is deleted."""
     release_dir = config.music_source_dir / TEST_RELEASE_1.name
@@ -571,9 +993,176 @@ def test_update_cache_releases_ignores_partially_written_directory(config: Confi
         assert cursor.fetchone()[0] == 1
 
 
+@pytest.mark.parametrize("seed", [True, False])
+def test_update_cache_seed_added_at_from_fs(config: Config, seed: bool) -> None:
+    config = dataclasses.replace(config, seed_added_at_from_fs=seed)
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    old_mtime = datetime(2000, 1, 1, 12).timestamp()
+    os.utime(release_dir, (old_mtime, old_mtime))
+    update_cache(config)
+
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT added_at FROM releases")
+        added_at = cursor.fetchone()["added_at"]
+    assert added_at.startswith("2000-01-01T12:00:00") == seed
+
+
+def test_update_cache_added_at_format_date(config: Config) -> None:
+    config = dataclasses.replace(config, added_at_format="date", seed_added_at_from_fs=True)
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    mtime = datetime(2024, 1, 1, 12).timestamp()
+    os.utime(release_dir, (mtime, mtime))
+    # This release's datafile has an RFC3339 added_at of 2023-10-23.
+    shutil.copytree(TEST_RELEASE_3, config.music_source_dir / TEST_RELEASE_3.name)
+    update_cache(config)
+
+    datafile = next(release_dir.glob(".rose.*.toml"))
+    assert 'added_at = "2024-01-01"' in datafile.read_text()
+    releases = list_releases(config, sort="added_at")
+    assert [r.source_path.name for r in releases] == [TEST_RELEASE_3.name, TEST_RELEASE_1.name]
+    assert releases[1].added_at == "2024-01-01"
+
+
+def test_update_cache_min_track_duration(config: Config, monkeypatch: pytest.MonkeyPatch) -> None:
+    config = dataclasses.replace(config, min_track_duration_seconds=2)
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    # A 1 second file, which is shorter than the minimum duration.
+    shutil.copyfile(TEST_TAGGER / "track3.mp3", release_dir / "preview.mp3")
+    update_cache(config)
+
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT source_path FROM tracks")
+        assert {Path(r["source_path"]).name for r in cursor} == {"01.m4a", "02.m4a"}
+        # The release's tags were not read from the skipped file.
+        cursor = conn.execute("SELECT title FROM releases")
+        assert cursor.fetchone()["title"] == "I Love Blackpink"
+    # And the skipped file was not tagged with a Rose ID.
+    assert AudioTags.from_file(release_dir / "preview.mp3").id is None
+
+    # The skipped file's duration is cached, so its tags are not re-read on the next update.
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT source_path FROM short_tracks")
+        assert [Path(r["source_path"]).name for r in cursor] == ["preview.mp3"]
+    read_paths: list[Path] = []
+    from_file = AudioTags.from_file
+
+    def tracked_from_file(p: Path) -> AudioTags:
+        read_paths.append(p)
+        return from_file(p)
+
+    monkeypatch.setattr(AudioTags, "from_file", tracked_from_file)
+    update_cache(config)
+    assert "preview.mp3" not in [p.name for p in read_paths]
+    monkeypatch.undo()
+
+    # The cached duration is forgotten once the file is gone.
+    (release_dir / "preview.mp3").unlink()
+    update_cache(config)
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT COUNT(*) FROM short_tracks")
+        assert cursor.fetchone()[0] == 0
+
+
+def test_update_cache_min_track_duration_all_too_short(config: Config) -> None:
+    config = dataclasses.replace(config, min_track_duration_seconds=3)
+    shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
+    update_cache(config)
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT COUNT(*) FROM releases")
+        assert cursor.fetchone()[0] == 0
+
+
+def test_update_cache_disc_titles(config: Config) -> None:
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    for discnumber, discsubtitle, f in [
+        ("1", "The Studio Album", release_dir / "01.m4a"),
+        ("2", "Live at the Forum", release_dir / "02.m4a"),
+    ]:
+        tags = AudioTags.from_file(f)
+        tags.discnumber = discnumber
+        tags.discsubtitle = discsubtitle
+        tags.flush()
+    update_cache(config)
+
+    with connect(config) as conn:
+        release_id = conn.execute("SELECT id FROM releases").fetchone()["id"]
+    release = get_release(config, release_id)
+    assert release is not None
+    assert release.disctotal == 2
+    assert release.disc_titles == {"1": "The Studio Album", "2": "Live at the Forum"}
+
+
+def test_update_cache_dry_run(config: Config) -> None:
+    """Test that a dry run reports the changes without writing anything."""
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    report = update_cache(config, dry_run=True)
+
+    # No stored data file was minted and no tags were written.
+    assert not any(STORED_DATA_FILE_REGEX.match(f.name) for f in release_dir.iterdir())
+    for f in [release_dir / "01.m4a", release_dir / "02.m4a"]:
+        assert AudioTags.from_file(f).id is None
+    # And nothing was written to the database.
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT COUNT(*) FROM releases")
+        assert cursor.fetchone()[0] == 0
+        cursor = conn.execute("SELECT COUNT(*) FROM tracks")
+        assert cursor.fetchone()[0] == 0
+
+    assert len(report.written_datafiles) == 1
+    assert report.written_datafiles[0].parent == release_dir
+    assert set(report.tagged_tracks) == {release_dir / "01.m4a", release_dir / "02.m4a"}
+    assert report.updated_releases == [release_dir]
+    assert set(report.updated_tracks) == {release_dir / "01.m4a", release_dir / "02.m4a"}
+    assert report.renamed_paths == []
+    assert report.evicted_releases == []
+
+
+def test_wanted_names_match_updater(config: Config) -> None:
+    config = dataclasses.replace(config, rename_source_dirs=True, rename_source_files=True)
+    shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
+    update_cache(config)
+
+    releases = list_releases(config)
+    assert len(releases) == 1
+    release = releases[0]
+    assert wanted_release_dirname(config, release) == "BLACKPINK - 1990. I Love Blackpink [NEW]"
+    assert release.source_path.name == wanted_release_dirname(config, release)
+    tracks = get_tracks_associated_with_release(config, release)
+    assert [wanted_track_filename(config, t) for t in tracks] == [
+        "01. Track 1.m4a",
+        "02. Track 2.m4a",
+    ]
+    assert [t.source_path.name for t in tracks] == [
+        wanted_track_filename(config, t) for t in tracks
+    ]
+
+
+def test_update_cache_dry_run_renames(config: Config) -> None:
+    """Test that a dry run reports the renames without renaming anything."""
+    config = dataclasses.replace(config, rename_source_dirs=True, rename_source_files=True)
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    report = update_cache(config, dry_run=True)
+
+    expected_dir = config.music_source_dir / "BLACKPINK - 1990. I Love Blackpink [NEW]"
+    assert release_dir.is_dir()
+    assert not expected_dir.exists()
+    assert (release_dir / "01.m4a").is_file()
+    assert set(report.renamed_paths) == {
+        (release_dir, expected_dir),
+        (expected_dir / "01.m4a", expected_dir / "01. Track 1.m4a"),
+        (expected_dir / "02.m4a", expected_dir / "02. Track 2.m4a"),
+    }
+
+
 def test_update_cache_rename_source_files(config: Config) -> None:
     """Test that we properly rename the source directory on cache update."""
-    config = dataclasses.replace(config, rename_source_files=True)
+    config = dataclasses.replace(config, rename_source_dirs=True, rename_source_files=True)
     shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
     (config.music_source_dir / TEST_RELEASE_1.name / "cover.jpg").touch()
     update_cache(config)
@@ -597,9 +1186,150 @@ def test_update_cache_rename_source_files(config: Config) -> None:
         }
 
 
+def test_update_cache_for_files(config: Config) -> None:
+    release_dir_1 = config.music_source_dir / TEST_RELEASE_1.name
+    release_dir_2 = config.music_source_dir / TEST_RELEASE_2.name
+    shutil.copytree(TEST_RELEASE_1, release_dir_1)
+    shutil.copytree(TEST_RELEASE_2, release_dir_2)
+    update_cache(config)
+    # A release that is only scanned by a full cache update.
+    shutil.copytree(TEST_RELEASE_3, config.music_source_dir / TEST_RELEASE_3.name)
+
+    # Removing the last audio files of a release deletes it from the cache.
+    (release_dir_1 / "01.m4a").unlink()
+    (release_dir_1 / "02.m4a").unlink()
+    update_cache_for_files(config, [release_dir_1 / "01.m4a", release_dir_1 / "02.m4a"])
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT source_path FROM releases")
+        assert {Path(r["source_path"]) for r in cursor} == {release_dir_2}
+
+    # Removing a release directory evicts it from the cache.
+    shutil.rmtree(release_dir_2)
+    report = update_cache_for_files(config, [release_dir_2 / "01.m4a"])
+    assert report.evicted_releases == [release_dir_2]
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT COUNT(*) FROM releases")
+        assert cursor.fetchone()[0] == 0
+
+
+def test_update_cache_for_files_symlinked_release(config: Config, tmp_path: Path) -> None:
+    external_dir = tmp_path / "external" / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, external_dir)
+    (config.music_source_dir / "Linked").symlink_to(external_dir, target_is_directory=True)
+    changed_file = config.music_source_dir / "Linked" / "01.m4a"
+
+    update_cache_for_files(config, [changed_file])
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT COUNT(*) FROM releases")
+        assert cursor.fetchone()[0] == 0
+
+    config = dataclasses.replace(config, follow_symlinks=True)
+    update_cache_for_files(config, [changed_file])
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT source_path FROM releases")
+        assert [r["source_path"] for r in cursor] == [str(external_dir.resolve())]
+
+
+def test_update_cache_rename_source_files_stores_original_filename(config: Config) -> None:
+    config = dataclasses.replace(config, rename_source_files=True)
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    update_cache(config)
+
+    track_id = AudioTags.from_file(release_dir / "01. Track 1.m4a").id
+    assert track_id is not None
+    track = get_track(config, track_id)
+    assert track is not None
+    assert track.original_filename == "01.m4a"
+
+    # Later renames keep the filename from before the first rename.
+    (release_dir / "01. Track 1.m4a").rename(release_dir / "lalala.m4a")
+    update_cache(config)
+    track = get_track(config, track_id)
+    assert track is not None
+    assert track.source_path == release_dir / "01. Track 1.m4a"
+    assert track.original_filename == "01.m4a"
+
+    # The original filenames are stored in the datafile, so they survive a cache rebuild.
+    datafile = next(f for f in release_dir.iterdir() if STORED_DATA_FILE_REGEX.match(f.name))
+    with datafile.open("rb") as fp:
+        assert tomllib.load(fp)["original_filenames"][track_id] == "01.m4a"
+    config.cache_database_path.unlink()
+    maybe_invalidate_cache_database(config)
+    update_cache(config)
+    track = get_track(config, track_id)
+    assert track is not None
+    assert track.original_filename == "01.m4a"
+
+
+def test_apply_template_renames(config: Config, source_dir: Path) -> None:
+    """Test that the whole library is renamed to a new source template in one pass."""
+    source = PathTemplatePair(
+        release=PathTemplate("{{ title }}"),
+        track=PathTemplate("{{ title }}"),
+    )
+    config = dataclasses.replace(
+        config, path_templates=dataclasses.replace(config.path_templates, source=source)
+    )
+    renames = apply_template_renames(config)
+    assert len(renames) == 9
+
+    release_dirs = {
+        source_dir / "I Love Blackpink",
+        source_dir / "I Love Carly",
+        source_dir / "I Love NewJeans",
+    }
+    assert release_dirs <= set(source_dir.iterdir())
+    for d in release_dirs:
+        tracks = {f.name for f in d.iterdir() if f.suffix == ".m4a"}
+        assert tracks == {"Track 1.m4a", "Track 2.m4a"}
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT source_path FROM releases")
+        assert {Path(r["source_path"]) for r in cursor} == release_dirs
+        cursor = conn.execute("SELECT source_path FROM tracks")
+        assert all(Path(r["source_path"]).parent in release_dirs for r in cursor)
+
+    # The library now matches the template, so nothing is renamed again.
+    assert apply_template_renames(config) == []
+
+
+@pytest.mark.parametrize(
+    ("rename_dirs", "rename_files"),
+    [(False, False), (True, False), (False, True), (True, True)],
+)
+def test_update_cache_rename_source_dirs_and_files_independently(
+    config: Config, rename_dirs: bool, rename_files: bool
+) -> None:
+    """Test that the directory and file renames only happen when their option is enabled."""
+    config = dataclasses.replace(
+        config, rename_source_dirs=rename_dirs, rename_source_files=rename_files
+    )
+    shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
+    update_cache(config)
+
+    renamed_dir = config.music_source_dir / "BLACKPINK - 1990. I Love Blackpink [NEW]"
+    expected_dir = renamed_dir if rename_dirs else config.music_source_dir / TEST_RELEASE_1.name
+    release_dirs = list(config.music_source_dir.iterdir())
+    assert expected_dir in release_dirs
+    assert (renamed_dir in release_dirs) == rename_dirs
+
+    expected_files = (
+        {expected_dir / "01. Track 1.m4a", expected_dir / "02. Track 2.m4a"}
+        if rename_files
+        else {expected_dir / "01.m4a", expected_dir / "02.m4a"}
+    )
+    assert expected_files <= set(expected_dir.iterdir())
+
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT source_path FROM releases")
+        assert Path(cursor.fetchone()[0]) == expected_dir
+        cursor = conn.execute("SELECT source_path FROM tracks")
+        assert {Path(r[0]) for r in cursor} == expected_files
+
+
 def test_update_cache_rename_source_files_nested_file_directories(config: Config) -> None:
     """Test that we properly rename arbitrarily nested files and clean up the empty dirs."""
-    config = dataclasses.replace(config, rename_source_files=True)
+    config = dataclasses.replace(config, rename_source_dirs=True, rename_source_files=True)
     shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
     (config.music_source_dir / TEST_RELEASE_1.name / "lala").mkdir()
     (config.music_source_dir / TEST_RELEASE_1.name / "01.m4a").rename(
@@ -627,7 +1357,7 @@ def test_update_cache_rename_source_files_nested_file_directories(config: Config
 
 def test_update_cache_rename_source_files_collisions(config: Config) -> None:
     """Test that we properly rename arbitrarily nested files and clean up the empty dirs."""
-    config = dataclasses.replace(config, rename_source_files=True)
+    config = dataclasses.replace(config, rename_source_dirs=True, rename_source_files=True)
     # Three copies of the same directory, and two instances of Track 1.
     shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
     shutil.copyfile(
@@ -747,6 +1477,35 @@ def test_update_cache_collages(config: Config) -> None:
         assert row["position"] == 1
 
 
+def test_update_cache_collages_parallel(config: Config) -> None:
+    config = dataclasses.replace(config, collage_playlist_parallel_threshold=50)
+    shutil.copytree(TEST_RELEASE_2, config.music_source_dir / TEST_RELEASE_2.name)
+    shutil.copytree(TEST_RELEASE_3, config.music_source_dir / TEST_RELEASE_3.name)
+    collages_dir = config.music_source_dir / "!collages"
+    collages_dir.mkdir()
+    expected: dict[str, list[str]] = {}
+    for i in range(200):
+        release_ids = ["ilovecarly", "ilovenewjeans"] if i % 2 else ["ilovenewjeans", "ilovecarly"]
+        expected[f"Collage {i}"] = release_ids
+        with (collages_dir / f"Collage {i}.toml").open("wb") as fp:
+            releases = [{"uuid": x, "description_meta": x} for x in release_ids]
+            tomli_w.dump({"releases": releases}, fp)
+    update_cache(config)
+
+    with connect(config) as conn:
+        cursor = conn.execute(
+            """
+            SELECT collage_name, release_id FROM collages_releases
+            WHERE NOT missing
+            ORDER BY collage_name, position
+            """
+        )
+        actual: dict[str, list[str]] = {}
+        for row in cursor:
+            actual.setdefault(row["collage_name"], []).append(row["release_id"])
+    assert actual == expected
+
+
 def test_update_cache_collages_missing_release_id(config: Config) -> None:
     shutil.copytree(TEST_COLLAGE_1, config.music_source_dir / "!collages")
     update_cache(config)
@@ -775,6 +1534,55 @@ def test_update_cache_collages_missing_release_id(config: Config) -> None:
     assert len([r for r in data["releases"] if "missing" not in r]) == 2
 
 
+def test_update_cache_collages_missing_grace_period(config: Config) -> None:
+    config = dataclasses.replace(config, collage_missing_grace_updates=1)
+    shutil.copytree(TEST_COLLAGE_1, config.music_source_dir / "!collages")
+    update_cache(config)
+
+    # Assert that the releases are not yet flagged as missing after one absent update.
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT COUNT(*) FROM collages_releases WHERE missing")
+        assert cursor.fetchone()[0] == 0
+    with (config.music_source_dir / "!collages" / "Rose Gold.toml").open("rb") as fp:
+        data = tomllib.load(fp)
+    assert not any(r.get("missing", False) for r in data["releases"])
+
+    # But they are flagged once they are absent for longer than the grace period, even though the
+    # collage file did not change in between.
+    update_cache(config)
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT COUNT(*) FROM collages_releases WHERE missing")
+        assert cursor.fetchone()[0] == 2
+    with (config.music_source_dir / "!collages" / "Rose Gold.toml").open("rb") as fp:
+        data = tomllib.load(fp)
+    assert len([r for r in data["releases"] if r["missing"]]) == 2
+
+
+def test_update_cache_collages_description_mark_new(config: Config) -> None:
+    config = dataclasses.replace(config, collage_description_mark_new=True)
+    release_dir = config.music_source_dir / TEST_RELEASE_2.name
+    shutil.copytree(TEST_RELEASE_2, release_dir)
+    shutil.copytree(TEST_RELEASE_3, config.music_source_dir / TEST_RELEASE_3.name)
+    shutil.copytree(TEST_COLLAGE_1, config.music_source_dir / "!collages")
+    datafile = next(release_dir.glob(".rose.*.toml"))
+    datafile.write_text(datafile.read_text().replace("new = false", "new = true"))
+    update_cache(config)
+
+    # Assert that only the new release is marked as new.
+    with (config.music_source_dir / "!collages" / "Rose Gold.toml").open("rb") as fp:
+        data = tomllib.load(fp)
+    descs = {r["uuid"]: r["description_meta"] for r in data["releases"]}
+    assert descs["ilovecarly"].endswith(" [NEW]")
+    assert not descs["ilovenewjeans"].endswith(" [NEW]")
+
+    # Assert that the marker is not written without the option.
+    config = dataclasses.replace(config, collage_description_mark_new=False)
+    update_cache_for_collages(config, force=True)
+    with (config.music_source_dir / "!collages" / "Rose Gold.toml").open("rb") as fp:
+        data = tomllib.load(fp)
+    assert not any(r["description_meta"].endswith(" [NEW]") for r in data["releases"])
+
+
 def test_update_cache_collages_missing_release_id_multiprocessing(config: Config) -> None:
     shutil.copytree(TEST_COLLAGE_1, config.music_source_dir / "!collages")
     update_cache(config)
@@ -856,6 +1664,37 @@ def test_update_cache_playlists(config: Config) -> None:
         ]
 
 
+def test_update_cache_custom_collages_and_playlists_dirnames(config: Config) -> None:
+    config = dataclasses.replace(
+        config, collages_dirname="_collages", playlists_dirname="_playlists"
+    )
+    shutil.copytree(TEST_RELEASE_2, config.music_source_dir / TEST_RELEASE_2.name)
+    shutil.copytree(TEST_COLLAGE_1, config.music_source_dir / "_collages")
+    shutil.copytree(TEST_PLAYLIST_1, config.music_source_dir / "_playlists")
+    update_cache(config)
+
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT name FROM collages")
+        assert [r["name"] for r in cursor] == ["Rose Gold"]
+        cursor = conn.execute("SELECT release_id FROM collages_releases WHERE NOT missing")
+        assert [r["release_id"] for r in cursor] == ["ilovecarly"]
+        cursor = conn.execute("SELECT name, cover_path FROM playlists")
+        assert [dict(r) for r in cursor] == [
+            {
+                "name": "Lala Lisa",
+                "cover_path": str(config.music_source_dir / "_playlists" / "Lala Lisa.jpg"),
+            }
+        ]
+        cursor = conn.execute("SELECT track_id FROM playlists_tracks ORDER BY position")
+        assert [r["track_id"] for r in cursor] == ["iloveloona", "ilovetwice"]
+        # The custom directories are not indexed as releases.
+        cursor = conn.execute("SELECT COUNT(*) FROM releases")
+        assert cursor.fetchone()[0] == 1
+    # And the default directories are not created.
+    assert not (config.music_source_dir / "!collages").exists()
+    assert not (config.music_source_dir / "!playlists").exists()
+
+
 def test_update_cache_playlists_missing_track_id(config: Config) -> None:
     shutil.copytree(TEST_PLAYLIST_1, config.music_source_dir / "!playlists")
     update_cache(config)
@@ -883,6 +1722,42 @@ def test_update_cache_playlists_missing_track_id(config: Config) -> None:
     assert len([r for r in data["tracks"] if "missing" not in r]) == 2
 
 
+def test_refresh_all_descriptions(config: Config, source_dir: Path) -> None:
+    with connect(config) as conn:
+        conn.execute(
+            """
+            INSERT INTO releases_artists (release_id, artist, artist_sanitized, role, position)
+            VALUES ('ilovecarly', 'Guest Man', 'Guest Man', 'guest', 99)
+            """
+        )
+        conn.execute(
+            """
+            INSERT INTO tracks_artists (track_id, artist, artist_sanitized, role, position)
+            VALUES ('iloveloona', 'Guest Man', 'Guest Man', 'guest', 99)
+            """
+        )
+    refresh_all_descriptions(config)
+    with (source_dir / "!collages" / "Rose Gold.toml").open("rb") as fp:
+        data = tomllib.load(fp)
+    assert (
+        data["releases"][0]["description_meta"]
+        == "Carly Rae Jepsen (feat. Guest Man) - 1990. I Love Carly"
+    )
+    with (source_dir / "!playlists" / "Lala Lisa.toml").open("rb") as fp:
+        data = tomllib.load(fp)
+    assert "(feat. Guest Man)" in data["tracks"][0]["description_meta"]
+
+    # Changing the artist formatting changes the descriptions without touching the releases.
+    config = dataclasses.replace(config, artist_role_order=["main"])
+    refresh_all_descriptions(config)
+    with (source_dir / "!collages" / "Rose Gold.toml").open("rb") as fp:
+        data = tomllib.load(fp)
+    assert data["releases"][0]["description_meta"] == "Carly Rae Jepsen - 1990. I Love Carly"
+    with (source_dir / "!playlists" / "Lala Lisa.toml").open("rb") as fp:
+        data = tomllib.load(fp)
+    assert "Guest Man" not in data["tracks"][0]["description_meta"]
+
+
 @pytest.mark.parametrize("multiprocessing", [True, False])
 def test_update_releases_updates_collages_description_meta(
     config: Config, multiprocessing: bool
@@ -1023,12 +1898,17 @@ def test_list_releases(config: Config) -> None:
             id="r1",
             source_path=Path(config.music_source_dir / "r1"),
             cover_image_path=None,
+            cover_hash=None,
+            cover_size=None,
+            cover_mtime=None,
             added_at="0000-01-01T00:00:00+00:00",
             albumtitle="Release 1",
             releasetype="album",
             year=2023,
             disctotal=1,
+            disc_titles={},
             new=False,
+            hidden=False,
             genres=["Techno", "Deep House"],
             labels=["Silk Music"],
             albumartists=ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")]),
@@ -1039,12 +1919,17 @@ def test_list_releases(config: Config) -> None:
             id="r2",
             source_path=Path(config.music_source_dir / "r2"),
             cover_image_path=Path(config.music_source_dir / "r2" / "cover.jpg"),
+            cover_hash=None,
+            cover_size=None,
+            cover_mtime=None,
             added_at="0000-01-01T00:00:00+00:00",
             albumtitle="Release 2",
             releasetype="album",
             year=2021,
             disctotal=1,
+            disc_titles={},
             new=False,
+            hidden=False,
             genres=["Classical"],
             labels=["Native State"],
             albumartists=ArtistMapping(
@@ -1057,12 +1942,17 @@ def test_list_releases(config: Config) -> None:
             id="r3",
             source_path=Path(config.music_source_dir / "r3"),
             cover_image_path=None,
+            cover_hash=None,
+            cover_size=None,
+            cover_mtime=None,
             added_at="0000-01-01T00:00:00+00:00",
             albumtitle="Release 3",
             releasetype="album",
             year=2021,
             disctotal=1,
+            disc_titles={},
             new=True,
+            hidden=False,
             genres=[],
             labels=[],
             albumartists=ArtistMapping(),
@@ -1074,6 +1964,82 @@ def test_list_releases(config: Config) -> None:
     assert list_releases(config, ["r1"]) == expected[:1]
 
 
+@pytest.mark.usefixtures("seeded_cache")
+def test_list_releases_sorted(config: Config) -> None:
+    def ids(**kwargs: Any) -> list[str]:
+        return [r.id for r in list_releases(config, **kwargs)]
+
+    assert ids() == ["r1", "r2", "r3"]
+    assert ids(sort="year") == ["r2", "r3", "r1"]
+    assert ids(sort="year", ascending=False) == ["r1", "r2", "r3"]
+    assert ids(sort="title", ascending=False) == ["r3", "r2", "r1"]
+    # r3 has no artists, so it sorts first.
+    assert ids(sort="artist_then_year") == ["r3", "r1", "r2"]
+    # Sorting combines with the other filters.
+    assert ids(release_ids=["r1", "r2"], sort="year") == ["r2", "r1"]
+    with connect(config) as conn:
+        conn.execute("UPDATE releases SET added_at = '2024-01-01T00:00:00+00:00' WHERE id = 'r2'")
+    assert ids(sort="added_at", ascending=False) == ["r2", "r1", "r3"]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_list_releases_filtered_sorted(config: Config) -> None:
+    with connect(config) as conn:
+        conn.execute(
+            """
+            INSERT INTO releases_genres (release_id, genre, genre_sanitized, position)
+            VALUES ('r3', 'Classical', 'Classical', 1)
+            """
+        )
+    releases = list_releases_delete_this(config, sanitized_genre_filter="Classical", sort="title")
+    assert [r.id for r in releases] == ["r2", "r3"]
+    releases = list_releases_delete_this(
+        config, sanitized_genre_filter="Classical", sort="title", ascending=False
+    )
+    assert [r.id for r in releases] == ["r3", "r2"]
+    releases, total = list_releases_paginated(
+        config, limit=1, sanitized_genre_filter="Classical", sort="title", ascending=False
+    )
+    assert [r.id for r in releases] == ["r3"]
+    assert total == 2
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_list_releases_delete_this_hidden(config: Config) -> None:
+    with connect(config) as conn:
+        conn.execute("UPDATE releases SET hidden = true WHERE id = 'r3'")
+    releases = list_releases_delete_this(config)
+    assert [r.id for r in releases] == ["r1", "r2"]
+    releases = list_releases_delete_this(config, include_hidden=True)
+    assert [r.id for r in releases] == ["r1", "r2", "r3"]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_list_releases_paginated(config: Config) -> None:
+    releases, total = list_releases_paginated(config, limit=2)
+    assert [r.id for r in releases] == ["r1", "r2"]
+    assert total == 3
+    releases, total = list_releases_paginated(config, limit=2, offset=2)
+    assert [r.id for r in releases] == ["r3"]
+    assert total == 3
+    # The total respects the filters.
+    releases, total = list_releases_paginated(config, limit=1, sanitized_label_filter="Silk Music")
+    assert [r.id for r in releases] == ["r1"]
+    assert total == 1
+    releases, total = list_releases_paginated(config, limit=1, offset=1, new=False)
+    assert [r.id for r in releases] == ["r2"]
+    assert total == 2
+    # Hidden releases are excluded from the page and the total unless requested.
+    with connect(config) as conn:
+        conn.execute("UPDATE releases SET hidden = true WHERE id = 'r3'")
+    releases, total = list_releases_paginated(config, limit=5)
+    assert [r.id for r in releases] == ["r1", "r2"]
+    assert total == 2
+    releases, total = list_releases_paginated(config, limit=5, include_hidden=True)
+    assert [r.id for r in releases] == ["r1", "r2", "r3"]
+    assert total == 3
+
+
 @pytest.mark.usefixtures("seeded_cache")
 def test_get_release_and_associated_tracks(config: Config) -> None:
     release = get_release(config, "r1")
@@ -1083,12 +2049,17 @@ def test_get_release_and_associated_tracks(config: Config) -> None:
         id="r1",
         source_path=Path(config.music_source_dir / "r1"),
         cover_image_path=None,
+        cover_hash=None,
+        cover_size=None,
+        cover_mtime=None,
         added_at="0000-01-01T00:00:00+00:00",
         albumtitle="Release 1",
         releasetype="album",
         year=2023,
         disctotal=1,
+        disc_titles={},
         new=False,
+        hidden=False,
         genres=["Techno", "Deep House"],
         labels=["Silk Music"],
         albumartists=ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")]),
@@ -1101,11 +2072,16 @@ def test_get_release_and_associated_tracks(config: Config) -> None:
             source_path=config.music_source_dir / "r1" / "01.m4a",
             source_mtime="999",
             tracktitle="Track 1",
+            tracksubtitle=None,
             tracknumber="01",
             tracktotal=2,
             discnumber="01",
+            discsubtitle=None,
             disctotal=1,
             duration_seconds=120,
+            audio_sha256=None,
+            isrc=None,
+            original_filename=None,
             trackartists=ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")]),
             metahash="1",
             release=release,
@@ -1115,11 +2091,16 @@ def test_get_release_and_associated_tracks(config: Config) -> None:
             source_path=config.music_source_dir / "r1" / "02.m4a",
             source_mtime="999",
             tracktitle="Track 2",
+            tracksubtitle=None,
             tracknumber="02",
             tracktotal=2,
             discnumber="01",
+            discsubtitle=None,
             disctotal=1,
             duration_seconds=240,
+            audio_sha256=None,
+            isrc=None,
+            original_filename=None,
             trackartists=ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")]),
             metahash="2",
             release=release,
@@ -1149,6 +2130,37 @@ def test_get_release_applies_artist_aliases(config: Config) -> None:
         )
 
 
+def test_get_release_by_path(config: Config) -> None:
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    update_cache(config)
+    with connect(config) as conn:
+        release_id = conn.execute("SELECT id FROM releases").fetchone()["id"]
+
+    # Both the release directory and a track file inside of it resolve to the release.
+    release = get_release_by_path(config, release_dir)
+    assert release is not None
+    assert release.id == release_id
+    release = get_release_by_path(config, release_dir / "01.m4a")
+    assert release is not None
+    assert release.id == release_id
+    assert get_release_by_path(config, config.music_source_dir / "lalala") is None
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_releases_not_in_any_collage(config: Config) -> None:
+    # r1 and r2 are in the Rose Gold collage.
+    assert [r.id for r in releases_not_in_any_collage(config)] == ["r3"]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_get_releases(config: Config) -> None:
+    releases = get_releases(config, ["r3", "lalala", "r1"])
+    assert [r.id for r in releases] == ["r3", "r1"]
+    assert releases[1] == get_release(config, "r1")
+    assert get_releases(config, []) == []
+
+
 @pytest.mark.usefixtures("seeded_cache")
 def test_get_release_logtext(config: Config) -> None:
     assert get_release_logtext(config, "r1") == "Techno Man & Bass Man - 2023. Release 1"
@@ -1162,11 +2174,16 @@ def test_list_tracks(config: Config) -> None:
             source_path=config.music_source_dir / "r1" / "01.m4a",
             source_mtime="999",
             tracktitle="Track 1",
+            tracksubtitle=None,
             tracknumber="01",
             tracktotal=2,
             discnumber="01",
+            discsubtitle=None,
             disctotal=1,
             duration_seconds=120,
+            audio_sha256=None,
+            isrc=None,
+            original_filename=None,
             trackartists=ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")]),
             metahash="1",
             release=CachedRelease(
@@ -1174,12 +2191,17 @@ def test_list_tracks(config: Config) -> None:
                 id="r1",
                 source_path=Path(config.music_source_dir / "r1"),
                 cover_image_path=None,
+                cover_hash=None,
+                cover_size=None,
+                cover_mtime=None,
                 added_at="0000-01-01T00:00:00+00:00",
                 albumtitle="Release 1",
                 releasetype="album",
                 year=2023,
                 disctotal=1,
+                disc_titles={},
                 new=False,
+                hidden=False,
                 genres=["Techno", "Deep House"],
                 labels=["Silk Music"],
                 albumartists=ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")]),
@@ -1191,11 +2213,16 @@ def test_list_tracks(config: Config) -> None:
             source_path=config.music_source_dir / "r1" / "02.m4a",
             source_mtime="999",
             tracktitle="Track 2",
+            tracksubtitle=None,
             tracknumber="02",
             tracktotal=2,
             discnumber="01",
+            discsubtitle=None,
             disctotal=1,
             duration_seconds=240,
+            audio_sha256=None,
+            isrc=None,
+            original_filename=None,
             trackartists=ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")]),
             metahash="2",
             release=CachedRelease(
@@ -1203,12 +2230,17 @@ def test_list_tracks(config: Config) -> None:
                 id="r1",
                 source_path=Path(config.music_source_dir / "r1"),
                 cover_image_path=None,
+                cover_hash=None,
+                cover_size=None,
+                cover_mtime=None,
                 added_at="0000-01-01T00:00:00+00:00",
                 albumtitle="Release 1",
                 releasetype="album",
                 year=2023,
                 disctotal=1,
+                disc_titles={},
                 new=False,
+                hidden=False,
                 genres=["Techno", "Deep House"],
                 labels=["Silk Music"],
                 albumartists=ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")]),
@@ -1220,11 +2252,16 @@ def test_list_tracks(config: Config) -> None:
             source_path=config.music_source_dir / "r2" / "01.m4a",
             source_mtime="999",
             tracktitle="Track 1",
+            tracksubtitle=None,
             tracknumber="01",
             tracktotal=1,
             discnumber="01",
+            discsubtitle=None,
             disctotal=1,
             duration_seconds=120,
+            audio_sha256=None,
+            isrc=None,
+            original_filename=None,
             trackartists=ArtistMapping(
                 main=[Artist("Violin Woman")], guest=[Artist("Conductor Woman")]
             ),
@@ -1233,13 +2270,18 @@ def test_list_tracks(config: Config) -> None:
                 id="r2",
                 source_path=config.music_source_dir / "r2",
                 cover_image_path=config.music_source_dir / "r2" / "cover.jpg",
+                cover_hash=None,
+                cover_size=None,
+                cover_mtime=None,
                 added_at="0000-01-01T00:00:00+00:00",
                 datafile_mtime="999",
                 albumtitle="Release 2",
                 releasetype="album",
                 year=2021,
                 new=False,
+                hidden=False,
                 disctotal=1,
+                disc_titles={},
                 genres=["Classical"],
                 labels=["Native State"],
                 albumartists=ArtistMapping(
@@ -1253,24 +2295,34 @@ def test_list_tracks(config: Config) -> None:
             source_path=config.music_source_dir / "r3" / "01.m4a",
             source_mtime="999",
             tracktitle="Track 1",
+            tracksubtitle=None,
             tracknumber="01",
             tracktotal=1,
             discnumber="01",
+            discsubtitle=None,
             disctotal=1,
             duration_seconds=120,
+            audio_sha256=None,
+            isrc=None,
+            original_filename=None,
             trackartists=ArtistMapping(),
             metahash="4",
             release=CachedRelease(
                 id="r3",
                 source_path=config.music_source_dir / "r3",
                 cover_image_path=None,
+                cover_hash=None,
+                cover_size=None,
+                cover_mtime=None,
                 added_at="0000-01-01T00:00:00+00:00",
                 datafile_mtime="999",
                 albumtitle="Release 3",
                 releasetype="album",
                 year=2021,
                 new=True,
+                hidden=False,
                 disctotal=1,
+                disc_titles={},
                 genres=[],
                 labels=[],
                 albumartists=ArtistMapping(),
@@ -1283,6 +2335,16 @@ def test_list_tracks(config: Config) -> None:
     assert list_tracks(config, ["t1", "t2"]) == expected[:2]
 
 
+@pytest.mark.usefixtures("seeded_cache")
+def test_list_tracks_paginated(config: Config) -> None:
+    tracks, total = list_tracks_paginated(config, limit=2, offset=1)
+    assert [t.id for t in tracks] == ["t2", "t3"]
+    assert [t.release.id for t in tracks] == ["r1", "r2"]
+    assert total == 4
+    tracks, total = list_tracks_paginated(config, limit=10, track_ids=["t1", "t4"])
+    assert [t.id for t in tracks] == ["t1", "t4"]
+    assert total == 2
+
 @pytest.mark.usefixtures("seeded_cache")
 def test_get_track(config: Config) -> None:
     assert get_track(config, "t1") == CachedTrack(
@@ -1290,11 +2352,16 @@ def test_get_track(config: Config) -> None:
         source_path=config.music_source_dir / "r1" / "01.m4a",
         source_mtime="999",
         tracktitle="Track 1",
+        tracksubtitle=None,
         tracknumber="01",
         tracktotal=2,
         discnumber="01",
+        discsubtitle=None,
         disctotal=1,
         duration_seconds=120,
+        audio_sha256=None,
+        isrc=None,
+        original_filename=None,
         trackartists=ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")]),
         metahash="1",
         release=CachedRelease(
@@ -1302,12 +2369,17 @@ def test_get_track(config: Config) -> None:
             id="r1",
             source_path=Path(config.music_source_dir / "r1"),
             cover_image_path=None,
+            cover_hash=None,
+            cover_size=None,
+            cover_mtime=None,
             added_at="0000-01-01T00:00:00+00:00",
             albumtitle="Release 1",
             releasetype="album",
             year=2023,
             disctotal=1,
+            disc_titles={},
             new=False,
+            hidden=False,
             genres=["Techno", "Deep House"],
             labels=["Silk Music"],
             albumartists=ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")]),
@@ -1316,6 +2388,53 @@ def test_get_track(config: Config) -> None:
     )
 
 
+def test_get_track_by_isrc(config: Config) -> None:
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    af = AudioTags.from_file(release_dir / "02.m4a")
+    af.isrc = "USRC17607839"
+    af.flush()
+    update_cache(config)
+
+    track = get_track_by_isrc(config, "USRC17607839")
+    assert track is not None
+    assert track.source_path == release_dir / "02.m4a"
+    assert track.isrc == "USRC17607839"
+    assert get_track_by_isrc(config, "USRC17600000") is None
+
+
+def test_find_duplicate_tracks_by_audio(config: Config) -> None:
+    """Test that copies of the same audio are flagged as duplicates regardless of their tags."""
+    config = dataclasses.replace(config, compute_audio_sha256=True)
+    # Every track in Test Release 1 and Test Release 2 has the same audio but different tags.
+    shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
+    shutil.copytree(TEST_RELEASE_2, config.music_source_dir / TEST_RELEASE_2.name)
+    update_cache(config)
+
+    duplicates = find_duplicate_tracks_by_audio(config)
+    assert len(duplicates) == 1
+    assert {(t.release.albumtitle, t.tracktitle) for t in duplicates[0]} == {
+        ("I Love Blackpink", "Track 1"),
+        ("I Love Blackpink", "Track 2"),
+        ("I Love Carly", "Track 1"),
+        ("I Love Carly", "Track 2"),
+    }
+
+
+def test_find_duplicate_tracks_by_audio_disabled(config: Config) -> None:
+    shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
+    update_cache(config)
+    assert find_duplicate_tracks_by_audio(config) == []
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_iter_tracks(config: Config) -> None:
+    tracks = list(iter_tracks(config))
+    assert tracks == list_tracks(config)
+    # The tracks of a release share the release object.
+    assert tracks[0].release is tracks[1].release
+
+
 @pytest.mark.usefixtures("seeded_cache")
 def test_get_track_logtext(config: Config) -> None:
     assert get_track_logtext(config, "t1") == "Techno Man & Bass Man - Track 1.m4a"
@@ -1332,6 +2451,53 @@ def test_list_artists(config: Config) -> None:
     }
 
 
+@pytest.mark.usefixtures("seeded_cache")
+def test_list_artist_singles(config: Config) -> None:
+    assert list_artist_singles(config) == []
+    with connect(config) as conn:
+        conn.execute("UPDATE releases SET releasetype = 'single' WHERE id = 'r1'")
+        conn.execute("UPDATE releases SET releasetype = 'loosetrack' WHERE id = 'r2'")
+        conn.execute(
+            """
+            UPDATE releases_artists SET artist = 'Techno Man', artist_sanitized = 'Techno Man'
+            WHERE release_id = 'r2' AND role = 'main'
+            """
+        )
+    singles = list_artist_singles(config)
+    assert len(singles) == 1
+    assert singles[0].artist == "Techno Man"
+    assert [r.id for r in singles[0].releases] == ["r1", "r2"]
+    assert [t.id for t in singles[0].tracks] == ["t1", "t2", "t3"]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_releases_by_year_and_decade(config: Config) -> None:
+    assert releases_by_year(config) == [(2021, 2), (2023, 1)]
+    assert releases_by_decade(config) == [(2020, 3)]
+    with connect(config) as conn:
+        conn.execute("UPDATE releases SET year = NULL WHERE id = 'r1'")
+        conn.execute("UPDATE releases SET year = 1999 WHERE id = 'r2'")
+    assert releases_by_year(config) == [(1999, 1), (2021, 1), (None, 1)]
+    assert releases_by_decade(config) == [(1990, 1), (2020, 1), (None, 1)]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_list_artists_with_counts(config: Config) -> None:
+    assert set(list_artists_with_counts(config)) == {
+        ("Techno Man", 1),
+        ("Bass Man", 1),
+        ("Violin Woman", 1),
+        ("Conductor Woman", 1),
+    }
+    # Releases credited to an alias count towards the parent artist.
+    config = dataclasses.replace(
+        config,
+        artist_aliases_map={"Techno Man": ["Violin Woman"]},
+        artist_aliases_parents_map={"Violin Woman": ["Techno Man"]},
+    )
+    assert dict(list_artists_with_counts(config))["Techno Man"] == 2
+
+
 @pytest.mark.usefixtures("seeded_cache")
 def test_list_genres(config: Config) -> None:
     genres = list_genres(config)
@@ -1354,6 +2520,43 @@ def test_list_collages(config: Config) -> None:
     assert set(collages) == {"Rose Gold", "Ruby Red"}
 
 
+@pytest.mark.usefixtures("seeded_cache")
+def test_list_collages_of_release(config: Config) -> None:
+    assert list_collages_of_release(config, "r1") == ["Rose Gold"]
+    assert list_collages_of_release(config, "r3") == []
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_recent_collages(config: Config) -> None:
+    # The seeded collages share an mtime, so they tie-break by name.
+    assert recent_collages(config, 2) == ["Rose Gold", "Ruby Red"]
+    # The touched collage's mtime (a larger epoch) sorts first, even though it sorts before "999"
+    # as a string.
+    (config.music_source_dir / "!collages" / "Ruby Red.toml").touch()
+    update_cache_for_collages(config, ["Ruby Red"])
+    assert recent_collages(config, 1) == ["Ruby Red"]
+    assert recent_collages(config, 2) == ["Ruby Red", "Rose Gold"]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_list_playlists_of_track(config: Config) -> None:
+    assert list_playlists_of_track(config, "t1") == ["Lala Lisa"]
+    assert list_playlists_of_track(config, "t2") == []
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_missing_playlist_tracks(config: Config) -> None:
+    assert missing_playlist_tracks(config) == []
+    with connect(config) as conn:
+        conn.execute("UPDATE playlists_tracks SET missing = true WHERE track_id = 't3'")
+    assert missing_playlist_tracks(config) == [("Lala Lisa", "t3")]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_recent_playlists(config: Config) -> None:
+    assert recent_playlists(config, 1) == ["Lala Lisa"]
+
+
 @pytest.mark.usefixtures("seeded_cache")
 def test_get_collage(config: Config) -> None:
     cdata = get_collage(config, "Rose Gold")
@@ -1369,13 +2572,18 @@ def test_get_collage(config: Config) -> None:
             id="r1",
             source_path=config.music_source_dir / "r1",
             cover_image_path=None,
+            cover_hash=None,
+            cover_size=None,
+            cover_mtime=None,
             added_at="0000-01-01T00:00:00+00:00",
             datafile_mtime="999",
             albumtitle="Release 1",
             releasetype="album",
             year=2023,
             new=False,
+            hidden=False,
             disctotal=1,
+            disc_titles={},
             genres=["Techno", "Deep House"],
             labels=["Silk Music"],
             albumartists=ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")]),
@@ -1385,13 +2593,18 @@ def test_get_collage(config: Config) -> None:
             id="r2",
             source_path=config.music_source_dir / "r2",
             cover_image_path=config.music_source_dir / "r2" / "cover.jpg",
+            cover_hash=None,
+            cover_size=None,
+            cover_mtime=None,
             added_at="0000-01-01T00:00:00+00:00",
             datafile_mtime="999",
             albumtitle="Release 2",
             releasetype="album",
             year=2021,
             new=False,
+            hidden=False,
             disctotal=1,
+            disc_titles={},
             genres=["Classical"],
             labels=["Native State"],
             albumartists=ArtistMapping(
@@ -1435,11 +2648,16 @@ def test_get_playlist(config: Config) -> None:
             source_path=config.music_source_dir / "r1" / "01.m4a",
             source_mtime="999",
             tracktitle="Track 1",
+            tracksubtitle=None,
             tracknumber="01",
             tracktotal=2,
             discnumber="01",
+            discsubtitle=None,
             disctotal=1,
             duration_seconds=120,
+            audio_sha256=None,
+            isrc=None,
+            original_filename=None,
             trackartists=ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")]),
             metahash="1",
             release=CachedRelease(
@@ -1447,12 +2665,17 @@ def test_get_playlist(config: Config) -> None:
                 id="r1",
                 source_path=Path(config.music_source_dir / "r1"),
                 cover_image_path=None,
+                cover_hash=None,
+                cover_size=None,
+                cover_mtime=None,
                 added_at="0000-01-01T00:00:00+00:00",
                 albumtitle="Release 1",
                 releasetype="album",
                 year=2023,
                 disctotal=1,
+                disc_titles={},
                 new=False,
+                hidden=False,
                 genres=["Techno", "Deep House"],
                 labels=["Silk Music"],
                 albumartists=ArtistMapping(main=[Artist("Techno Man"), Artist("Bass Man")]),
@@ -1464,11 +2687,16 @@ def test_get_playlist(config: Config) -> None:
             source_path=config.music_source_dir / "r2" / "01.m4a",
             source_mtime="999",
             tracktitle="Track 1",
+            tracksubtitle=None,
             tracknumber="01",
             tracktotal=1,
             discnumber="01",
+            discsubtitle=None,
             disctotal=1,
             duration_seconds=120,
+            audio_sha256=None,
+            isrc=None,
+            original_filename=None,
             trackartists=ArtistMapping(
                 main=[Artist("Violin Woman")], guest=[Artist("Conductor Woman")]
             ),
@@ -1477,13 +2705,18 @@ def test_get_playlist(config: Config) -> None:
                 id="r2",
                 source_path=config.music_source_dir / "r2",
                 cover_image_path=config.music_source_dir / "r2" / "cover.jpg",
+                cover_hash=None,
+                cover_size=None,
+                cover_mtime=None,
                 added_at="0000-01-01T00:00:00+00:00",
                 datafile_mtime="999",
                 albumtitle="Release 2",
                 releasetype="album",
                 year=2021,
                 new=False,
+                hidden=False,
                 disctotal=1,
+                disc_titles={},
                 genres=["Classical"],
                 labels=["Native State"],
                 albumartists=ArtistMapping(
@@ -1495,6 +2728,16 @@ def test_get_playlist(config: Config) -> None:
     ]
 
 
+@pytest.mark.usefixtures("seeded_cache")
+def test_get_artist_tracks_by_role(config: Config) -> None:
+    assert [t.id for t in get_artist_tracks_by_role(config, "Conductor Woman", "guest")] == ["t3"]
+    assert get_artist_tracks_by_role(config, "Conductor Woman", "main") == []
+    # Tracks credited to an alias are attributed to the parent artist.
+    config = dataclasses.replace(config, artist_aliases_map={"Bass Man": ["Violin Woman"]})
+    tracks = get_artist_tracks_by_role(config, "Bass Man", "main")
+    assert {t.id for t in tracks} == {"t1", "t2", "t3"}
+
+
 @pytest.mark.usefixtures("seeded_cache")
 def test_artist_exists(config: Config) -> None:
     assert artist_exists(config, "Bass Man")
@@ -1523,6 +2766,25 @@ def test_label_exists(config: Config) -> None:
     assert not label_exists(config, "Cotton Music")
 
 
+@pytest.mark.usefixtures("seeded_cache")
+def test_get_tracks_of_collage(config: Config) -> None:
+    tracks = get_tracks_of_collage(config, "Rose Gold")
+    assert tracks is not None
+    assert [t.id for t in tracks] == ["t1", "t2", "t3"]
+    assert [t.release.id for t in tracks] == ["r1", "r1", "r2"]
+
+    # The collage position takes precedence over the track order.
+    with connect(config) as conn:
+        conn.execute(
+            "UPDATE collages_releases SET position = 3 - position WHERE collage_name = 'Rose Gold'"
+        )
+    tracks = get_tracks_of_collage(config, "Rose Gold")
+    assert tracks is not None
+    assert [t.id for t in tracks] == ["t3", "t1", "t2"]
+
+    assert get_tracks_of_collage(config, "lalala") is None
+
+
 def test_unpack() -> None:
     i = _unpack("Rose ¬ Lisa ¬ Jisoo ¬ Jennie", r"vocal ¬ dance ¬ visual ¬ vocal")
     assert list(i) == [
@@ -1532,3 +2794,35 @@ def test_unpack() -> None:
         ("Jennie", "vocal"),
     ]
     assert list(_unpack("", "")) == []
+
+
+def test_verify_library(config: Config, source_dir: Path) -> None:
+    report = verify_library(config)
+    assert report.ok
+
+    # Delete a track file behind the cache's back.
+    track_path = source_dir / "Test Release 1" / "01.m4a"
+    track_path.unlink()
+    report = verify_library(config)
+    assert not report.ok
+    assert report.missing_track_files == [track_path]
+    assert report.missing_release_dirs == []
+    assert report.unreadable_datafiles == []
+    assert report.orphaned_fts_rowids == []
+
+    # And then corrupt a datafile and delete a release directory.
+    release_dir = source_dir / "Test Release 2"
+    datafile = next(f for f in release_dir.iterdir() if STORED_DATA_FILE_REGEX.match(f.name))
+    datafile.write_text("lalala")
+    shutil.rmtree(source_dir / "Test Release 3")
+    report = verify_library(config)
+    assert report.unreadable_datafiles == [datafile]
+    assert report.missing_release_dirs == [source_dir / "Test Release 3"]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_verify_library_orphaned_fts_rows(config: Config) -> None:
+    with connect(config) as conn:
+        conn.execute("INSERT INTO rules_engine_fts (rowid, tracktitle) VALUES (999999, 'x')")
+    report = verify_library(config)
+    assert report.orphaned_fts_rowids == [999999]
@@ -84,12 +84,29 @@ def cache() -> None:
 # fmt: off
 @cache.command()
 @click.option("--force", "-f", is_flag=True, help="Force re-read all data from disk, even for unchanged files.")  
+@click.option("--dry-run", "-d", is_flag=True, help="Display intended changes without applying them.")  
 @click.pass_obj
 # fmt: on
-def update(ctx: Context, force: bool) -> None:
+def update(ctx: Context, force: bool, dry_run: bool) -> None:
     """Synchronize the read cache with new changes in the source directory."""
     from rose.cache import update_cache
-    update_cache(ctx.config, force)
+    report = update_cache(ctx.config, force, dry_run=dry_run)
+    if not dry_run:
+        return
+    for p in report.written_datafiles:
+        click.echo(f"Would write datafile: {p}")
+    for p in report.tagged_tracks:
+        click.echo(f"Would write IDs to track: {p}")
+    for old, new in report.renamed_paths:
+        click.echo(f"Would rename: {old} -> {new}")
+    for p in report.updated_releases:
+        click.echo(f"Would update cached release: {p}")
+    for p in report.updated_tracks:
+        click.echo(f"Would update cached track: {p}")
+    for p in report.evicted_releases:
+        click.echo(f"Would evict cached release: {p}")
+    for src, dst in report.converted_covers:
+        click.echo(f"Would convert cover art: {src} -> {dst}")
 
 
 # fmt: off
@@ -106,6 +123,33 @@ def watch(ctx: Context, foreground: bool) -> None:
     start_watchdog(ctx.config)
 
 
+@cache.command()
+@click.pass_obj
+def verify(ctx: Context) -> None:
+    """Check the read cache and source directory for integrity problems."""
+    from rose.cache import verify_library
+    report = verify_library(ctx.config)
+    for p in report.missing_release_dirs:
+        click.echo(f"Missing release directory: {p}")
+    for p in report.missing_track_files:
+        click.echo(f"Missing track file: {p}")
+    for p in report.unreadable_datafiles:
+        click.echo(f"Unreadable datafile: {p}")
+    for rowid in report.orphaned_fts_rowids:
+        click.echo(f"Orphaned full text search row: {rowid}")
+    if not report.ok:
+        click.echo("Run `rose cache update --force` to resynchronize the read cache.")
+        exit(1)
+
+
+@cache.command()
+@click.pass_obj
+def refresh_descriptions(ctx: Context) -> None:
+    """Regenerate the release and track descriptions in the collage and playlist files."""
+    from rose.cache import refresh_all_descriptions
+    refresh_all_descriptions(ctx.config)
+
+
 @cache.command()
 @click.pass_obj
 def unwatch(ctx: Context) -> None:
@@ -177,12 +221,20 @@ def print1(ctx: Context, release: str) -> None:
 
 
 @releases.command(name="print-all")
+# fmt: off
 @click.argument("matcher", type=str, nargs=1, required=False)
+@click.option("--jsonl", is_flag=True, help="Stream one release per line as JSON lines. Does not accept a matcher.")
+# fmt: on
 @click.pass_obj
-def print_all(ctx: Context, matcher: str | None) -> None:
+def print_all(ctx: Context, matcher: str | None, jsonl: bool) -> None:
     """Print all releases (in JSON). Accepts an optional rules matcher to filter the releases."""
-    from rose.releases import dump_releases
+    from rose.releases import dump_library_jsonl, dump_releases
     from rose.rule_parser import MetadataMatcher
+    if jsonl:
+        if matcher:
+            raise click.UsageError("--jsonl does not accept a matcher")
+        dump_library_jsonl(ctx.config, click.get_text_stream("stdout"))
+        return
     parsed_matcher = MetadataMatcher.parse(matcher) if matcher else None
     click.echo(dump_releases(ctx.config, parsed_matcher))
 
@@ -210,6 +262,26 @@ def toggle_new(ctx: Context, release: str) -> None:
     toggle_release_new(ctx.config, release)
 
 
+@releases.command()
+@click.argument("days", type=int, nargs=1)
+@click.pass_obj
+def clear_new(ctx: Context, days: int) -> None:
+    """Clear the "new"-ness of all releases added more than DAYS days ago."""
+    from rose.releases import clear_new_older_than
+    for release_id in clear_new_older_than(ctx.config, days):
+        click.echo(release_id)
+
+
+@releases.command()
+@click.argument("release", type=click.Path(), nargs=1)
+@click.pass_obj
+def toggle_hidden(ctx: Context, release: str) -> None:
+    """Toggle whether a release is hidden from browsing. Accepts a release's UUID/path."""
+    from rose.releases import toggle_release_hidden
+    release = parse_release_argument(release)
+    toggle_release_hidden(ctx.config, release)
+
+
 @releases.command(name="delete")
 @click.argument("release", type=click.Path(), nargs=1)
 @click.pass_obj
@@ -279,6 +351,17 @@ def create_single(ctx: Context, track_path: Path) -> None:
     create_single_release(ctx.config, track_path)
 
 
+@releases.command()
+@click.argument("path", type=click.Path(path_type=Path), nargs=1)
+@click.pass_obj
+def split(ctx: Context, path: Path) -> None:
+    """
+    Split a directory whose tracks belong to multiple albums into one release directory per album.
+    """
+    from rose.releases import split_release_directory
+    split_release_directory(ctx.config, path)
+
+
 @cli.group()
 def tracks() -> None:
     """Manage tracks."""
@@ -525,17 +608,26 @@ def rules() -> None:
 @click.argument("actions", type=str, nargs=-1)
 @click.option("--dry-run", "-d", is_flag=True, help="Display intended changes without applying them.") 
 @click.option("--yes", "-y", is_flag=True, help="Bypass confirmation prompts.")
+@click.option("--collage", "-c", type=str, help="Only apply the rule to the releases in this collage.")
 # fmt: on
 @click.pass_obj
-def run(ctx: Context, matcher: str, actions: list[str], dry_run: bool, yes: bool) -> None:
+def run(
+    ctx: Context,
+    matcher: str,
+    actions: list[str],
+    dry_run: bool,
+    yes: bool,
+    collage: str | None,
+) -> None:
     """Run an ad hoc rule."""
     from rose.rule_parser import MetadataRule
-    from rose.rules import execute_metadata_rule
+    from rose.rules import CollageScope, execute_metadata_rule
     if not actions:
         logger.info("No-Op: No actions passed")
         return
     rule = MetadataRule.parse(matcher, actions)
-    execute_metadata_rule(ctx.config, rule, dry_run=dry_run, confirm_yes=not yes)
+    scope = CollageScope(collage) if collage else None
+    execute_metadata_rule(ctx.config, rule, dry_run=dry_run, confirm_yes=not yes, scope=scope)
 
 
 @rules.command()
@@ -550,6 +642,22 @@ def run_stored(ctx: Context, dry_run: bool, yes: bool) -> None:
     execute_stored_metadata_rules(ctx.config, dry_run=dry_run, confirm_yes=not yes)
 
 
+@rules.command()
+# fmt: off
+@click.option("--dry-run", "-d", is_flag=True, help="Display intended changes without applying them.")
+@click.option("--yes", "-y", is_flag=True, help="Bypass confirmation prompts.")
+@click.option("--collage", "-c", type=str, help="Only normalize the releases in this collage.")
+# fmt: on
+@click.pass_obj
+def strip_featured(ctx: Context, dry_run: bool, yes: bool, collage: str | None) -> None:
+    """Move featured artists out of track titles and into the guest artists."""
+    from rose.rules import CollageScope, strip_featured_artists_from_titles
+    scope = CollageScope(collage) if collage else None
+    strip_featured_artists_from_titles(
+        ctx.config, scope=scope, dry_run=dry_run, confirm_yes=not yes
+    )
+
+
 def parse_release_argument(r: str) -> str:
     """Takes in a release argument and normalizes it to the release ID."""
     from rose.cache import STORED_DATA_FILE_REGEX
@@ -4,6 +4,7 @@ The collages module provides functions for interacting with collages.
 
 import json
 import logging
+from datetime import datetime
 from pathlib import Path
 from typing import Any
 
@@ -44,7 +45,7 @@ class ReleaseDoesNotExistError(RoseExpectedError):
 
 
 def create_collage(c: Config, name: str) -> None:
-    (c.music_source_dir / "!collages").mkdir(parents=True, exist_ok=True)
+    c.collages_dir.mkdir(parents=True, exist_ok=True)
     path = collage_path(c, name)
     with lock(c, collage_lock_name(name)):
         if path.exists():
@@ -74,7 +75,7 @@ def rename_collage(c: Config, old_name: str, new_name: str) -> None:
             raise CollageAlreadyExistsError(f"Collage {new_name} already exists")
         old_path.rename(new_path)
         # And also rename all files with the same stem (e.g. cover arts).
-        for old_adjacent_file in (c.music_source_dir / "!collages").iterdir():
+        for old_adjacent_file in c.collages_dir.iterdir():
             if old_adjacent_file.stem != old_path.stem:
                 continue
             new_adjacent_file = old_adjacent_file.with_name(
@@ -137,7 +138,13 @@ def add_release_to_collage(
             if r["uuid"] == release_id:
                 logger.info(f"No-Op: Release {release_logtext} already in collage {collage_name}")
                 return
-        data["releases"].append({"uuid": release_id, "description_meta": release_logtext})
+        data["releases"].append(
+            {
+                "uuid": release_id,
+                "description_meta": release_logtext,
+                "added_at": datetime.now().astimezone().replace(microsecond=0).isoformat(),
+            }
+        )
         with path.open("wb") as fp:
             tomli_w.dump(data, fp)
     logger.info(f"Added release {release_logtext} to collage {collage_name}")
@@ -170,7 +177,7 @@ def edit_collage_in_editor(c: Config, collage_name: str) -> None:
     path = collage_path(c, collage_name)
     if not path.exists():
         raise CollageDoesNotExistError(f"Collage {collage_name} does not exist")
-    with lock(c, collage_lock_name(collage_name), timeout=60.0):
+    with lock(c, collage_lock_name(collage_name), timeout=c.lock_timeout_seconds.editor):
         with path.open("rb") as fp:
             data = tomllib.load(fp)
         raw_releases = data.get("releases", [])
@@ -181,6 +188,7 @@ def edit_collage_in_editor(c: Config, collage_name: str) -> None:
             logger.info("Aborting: metadata file not submitted.")
             return
         uuid_mapping = {r["description_meta"]: r["uuid"] for r in raw_releases}
+        added_at_mapping = {r["uuid"]: r["added_at"] for r in raw_releases if "added_at" in r}
 
         edited_releases: list[dict[str, Any]] = []
         for desc in edited_release_descriptions.strip().split("\n"):
@@ -191,7 +199,11 @@ def edit_collage_in_editor(c: Config, collage_name: str) -> None:
                     f"Release {desc} does not match a known release in the collage. "
                     "Was the line edited?"
                 ) from e
-            edited_releases.append({"uuid": uuid, "description_meta": desc})
+            edited_release = {"uuid": uuid, "description_meta": desc}
+            # Reordering a collage should not forget when each release was added to it.
+            if uuid in added_at_mapping:
+                edited_release["added_at"] = added_at_mapping[uuid]
+            edited_releases.append(edited_release)
         data["releases"] = edited_releases
 
         with path.open("wb") as fp:
@@ -201,4 +213,4 @@ def edit_collage_in_editor(c: Config, collage_name: str) -> None:
 
 
 def collage_path(c: Config, name: str) -> Path:
-    return c.music_source_dir / "!collages" / f"{name}.toml"
+    return c.collages_dir / f"{name}.toml"
@@ -1,11 +1,12 @@
 import json
+from datetime import datetime, timezone
 from pathlib import Path
 from typing import Any
 
 import pytest
 import tomllib
 
-from rose.cache import connect, update_cache
+from rose.cache import connect, get_collage, update_cache
 from rose.collages import (
     add_release_to_collage,
     create_collage,
@@ -134,7 +135,9 @@ def test_dump_collage(config: Config) -> None:
                 "releasetype": "album",
                 "year": 2023,
                 "new": False,
+                "hidden": False,
                 "disctotal": 1,
+                "disc_titles": {},
                 "genres": ["Techno", "Deep House"],
                 "labels": ["Silk Music"],
                 "albumartists": {
@@ -159,7 +162,9 @@ def test_dump_collage(config: Config) -> None:
                 "releasetype": "album",
                 "year": 2021,
                 "new": False,
+                "hidden": False,
                 "disctotal": 1,
+                "disc_titles": {},
                 "genres": ["Classical"],
                 "labels": ["Native State"],
                 "albumartists": {
@@ -192,7 +197,9 @@ def test_dump_collages(config: Config) -> None:
                     "releasetype": "album",
                     "year": 2023,
                     "new": False,
+                    "hidden": False,
                     "disctotal": 1,
+                    "disc_titles": {},
                     "genres": ["Techno", "Deep House"],
                     "labels": ["Silk Music"],
                     "albumartists": {
@@ -217,7 +224,9 @@ def test_dump_collages(config: Config) -> None:
                     "releasetype": "album",
                     "year": 2021,
                     "new": False,
+                    "hidden": False,
                     "disctotal": 1,
+                    "disc_titles": {},
                     "genres": ["Classical"],
                     "labels": ["Native State"],
                     "albumartists": {
@@ -246,6 +255,51 @@ def test_edit_collages_ordering(monkeypatch: Any, config: Config, source_dir: Pa
     assert data["releases"][1]["uuid"] == "ilovecarly"
 
 
+def test_collage_order_by_added_at(monkeypatch: Any, config: Config, source_dir: Path) -> None:
+    filepath = source_dir / "!collages" / "All Eyes.toml"
+    create_collage(config, "All Eyes")
+    times = iter(
+        [
+            datetime(2023, 1, 1, tzinfo=timezone.utc),
+            datetime(2023, 1, 2, tzinfo=timezone.utc),
+        ]
+    )
+    monkeypatch.setattr(
+        "rose.collages.datetime",
+        type("FakeDatetime", (), {"now": staticmethod(lambda: next(times))}),
+    )
+    add_release_to_collage(config, "All Eyes", "ilovecarly")
+    add_release_to_collage(config, "All Eyes", "ilovenewjeans")
+    with filepath.open("rb") as fp:
+        data = tomllib.load(fp)
+    assert all("added_at" in r for r in data["releases"])
+
+    # Move the later-added release to the front. The added-at timestamps must survive the edit.
+    monkeypatch.setattr("rose.collages.click.edit", lambda x: "\n".join(reversed(x.split("\n"))))
+    edit_collage_in_editor(config, "All Eyes")
+
+    cdata = get_collage(config, "All Eyes")
+    assert cdata is not None
+    assert [r.id for r in cdata[1]] == ["ilovenewjeans", "ilovecarly"]
+    cdata = get_collage(config, "All Eyes", order_by="added_at")
+    assert cdata is not None
+    assert [r.id for r in cdata[1]] == ["ilovecarly", "ilovenewjeans"]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_collage_order_by_added_at_untimestamped_last(config: Config) -> None:
+    with connect(config) as conn:
+        conn.execute(
+            """
+            UPDATE collages_releases SET added_at = '2023-01-01T00:00:00+00:00'
+            WHERE collage_name = 'Rose Gold' AND release_id = 'r2'
+            """
+        )
+    cdata = get_collage(config, "Rose Gold", order_by="added_at")
+    assert cdata is not None
+    assert [r.id for r in cdata[1]] == ["r2", "r1"]
+
+
 def test_edit_collages_remove_release(monkeypatch: Any, config: Config, source_dir: Path) -> None:
     filepath = source_dir / "!collages" / "Rose Gold.toml"
     monkeypatch.setattr("rose.collages.click.edit", lambda x: x.split("\n")[0])
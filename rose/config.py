@@ -8,6 +8,7 @@ invalid configuration is detected, and emits warnings when unrecognized keys are
 from __future__ import annotations
 
 import contextlib
+import dataclasses
 import functools
 import logging
 import multiprocessing
@@ -23,6 +24,9 @@ import tomllib
 from rose.common import RoseExpectedError, sanitize_dirname
 from rose.rule_parser import MetadataRule, RuleSyntaxError
 from rose.templates import (
+    ARTIST_ROLE_PREFIXES,
+    ARTIST_ROLE_SUFFIXES,
+    DEFAULT_ARTIST_ROLE_ORDER,
     DEFAULT_TEMPLATE_PAIR,
     InvalidPathTemplateError,
     PathTemplate,
@@ -55,6 +59,19 @@ class InvalidConfigValueError(RoseExpectedError, ValueError):
     pass
 
 
+@dataclass(frozen=True)
+class LockTimeouts:
+    """How long, in seconds, each class of operation may hold its lock before it expires."""
+
+    release: float = 1.0
+    collage: float = 1.0
+    playlist: float = 1.0
+    # The cache updater takes the locks while it writes to the source files.
+    cache_update: float = 1.0
+    # Interactive edits hold the lock while the user edits the file in their $EDITOR.
+    editor: float = 60.0
+
+
 @dataclass(frozen=True)
 class Config:
     music_source_dir: Path
@@ -62,7 +79,21 @@ class Config:
     cache_dir: Path
     # Maximum parallel processes for cache updates. Defaults to nproc/2.
     max_proc: int
+    # What to do with the cache database when its schema changes: `nuke` it and rebuild from
+    # scratch, or `try_migrate` it with the registered migrations before falling back to a rebuild.
+    cache_migration_mode: str
     ignore_release_directories: list[str]
+    # The names of the directories in the music source directory that store collages and playlists.
+    collages_dirname: str
+    playlists_dirname: str
+    # The number of consecutive collage updates a release may be absent for before it is flagged as
+    # missing in the collage.
+    collage_missing_grace_updates: int
+    # Append a `[NEW]` marker to the `description_meta` of new releases in the collage files.
+    collage_description_mark_new: bool
+    # Shard the cache updates of the collages or playlists across `max_proc` processes when at least
+    # this many need to be refreshed. None updates them in a single process.
+    collage_playlist_parallel_threshold: int | None
 
     # A map from parent artist -> subartists.
     artist_aliases_map: dict[str, list[str]]
@@ -78,8 +109,38 @@ class Config:
 
     cover_art_stems: list[str]
     valid_art_exts: list[str]
-
+    # The stem of the cover art files that Rosé writes.
+    write_cover_art_stem: str
+    # Convert cover art in formats outside `valid_art_exts` (e.g. TIFF) to a JPEG in the cache dir.
+    convert_unsupported_covers: bool
+    # Extract the cover art embedded in the first track of releases without a cover art file.
+    extract_embedded_covers: bool
+
+    rename_source_dirs: bool
     rename_source_files: bool
+    # Only parse the artist roles out of a combined artist tag (e.g. `A feat. B`) when the file has
+    # no role-specific artist tags. Otherwise, the parsed roles are merged with the role tags.
+    combined_artist_tag_fallback: bool
+    # Derive the release artists from the track artists when the albumartist tags are missing.
+    derive_albumartist_from_tracks: bool
+    # When deriving release artists, releases with more distinct main track artists than this are
+    # credited to Various Artists instead.
+    va_artist_threshold: int
+    # Seed the `added_at` of newly indexed releases from their directory's mtime instead of now.
+    seed_added_at_from_fs: bool
+    # The format of the `added_at` written to new datafiles: `datetime` (RFC3339) or `date`.
+    added_at_format: str
+    # The UUID version (4 or 7) of the IDs minted for newly indexed releases and tracks.
+    id_uuid_version: int
+    # Compute a checksum of each track's audio stream during cache updates. Off by default for cost.
+    compute_audio_sha256: bool
+    # Index symlinked release directories and follow symlinks within release directories.
+    follow_symlinks: bool
+    # Audio files shorter than this many seconds are not indexed as tracks. None indexes all files.
+    min_track_duration_seconds: int | None
+    lock_timeout_seconds: LockTimeouts
+    # The order in which the artist roles are rendered by the `artistsfmt` template filter.
+    artist_role_order: list[str]
     path_templates: PathTemplateConfig
 
     stored_metadata_rules: list[MetadataRule]
@@ -148,6 +209,18 @@ class Config:
                 f"Invalid value for max_proc in configuration file ({cfgpath}): must be a positive integer"
             ) from e
 
+        try:
+            cache_migration_mode = data["cache_migration_mode"]
+            del data["cache_migration_mode"]
+            if cache_migration_mode not in ("nuke", "try_migrate"):
+                raise ValueError(f"Must be nuke or try_migrate: got {cache_migration_mode}")
+        except KeyError:
+            cache_migration_mode = "nuke"
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for cache_migration_mode in configuration file ({cfgpath}): {e}"
+            ) from e
+
         artist_aliases_map: dict[str, list[str]] = defaultdict(list)
         artist_aliases_parents_map: dict[str, list[str]] = defaultdict(list)
         try:
@@ -306,6 +379,51 @@ class Config:
         cover_art_stems = [x.lower() for x in cover_art_stems]
         valid_art_exts = [x.lower() for x in valid_art_exts]
 
+        try:
+            write_cover_art_stem = data["write_cover_art_stem"]
+            del data["write_cover_art_stem"]
+            if not isinstance(write_cover_art_stem, str):
+                raise ValueError(f"Must be a str: got {type(write_cover_art_stem)}")
+            write_cover_art_stem = write_cover_art_stem.lower()
+            if write_cover_art_stem not in cover_art_stems:
+                raise ValueError(
+                    f"Must be one of the cover_art_stems: {', '.join(cover_art_stems)}"
+                )
+        except KeyError:
+            # Written cover art must be recognized on the next scan, so fall back to a configured
+            # stem if `cover` is not one.
+            write_cover_art_stem = (
+                "cover" if "cover" in cover_art_stems or not cover_art_stems else cover_art_stems[0]
+            )
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for write_cover_art_stem in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            convert_unsupported_covers = data["convert_unsupported_covers"]
+            del data["convert_unsupported_covers"]
+            if not isinstance(convert_unsupported_covers, bool):
+                raise ValueError(f"Must be a bool: got {type(convert_unsupported_covers)}")
+        except KeyError:
+            convert_unsupported_covers = False
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for convert_unsupported_covers in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            extract_embedded_covers = data["extract_embedded_covers"]
+            del data["extract_embedded_covers"]
+            if not isinstance(extract_embedded_covers, bool):
+                raise ValueError(f"Must be a bool: got {type(extract_embedded_covers)}")
+        except KeyError:
+            extract_embedded_covers = False
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for extract_embedded_covers in configuration file ({cfgpath}): {e}"
+            ) from e
+
         try:
             rename_source_files = data["rename_source_files"]
             del data["rename_source_files"]
@@ -318,6 +436,156 @@ class Config:
                 f"Invalid value for rename_source_files in configuration file ({cfgpath}): {e}"
             ) from e
 
+        try:
+            rename_source_dirs = data["rename_source_dirs"]
+            del data["rename_source_dirs"]
+            if not isinstance(rename_source_dirs, bool):
+                raise ValueError(f"Must be a bool: got {type(rename_source_dirs)}")
+        except KeyError:
+            # Before the directory renames were split out, `rename_source_files` controlled both.
+            rename_source_dirs = rename_source_files
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for rename_source_dirs in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            combined_artist_tag_fallback = data["combined_artist_tag_fallback"]
+            del data["combined_artist_tag_fallback"]
+            if not isinstance(combined_artist_tag_fallback, bool):
+                raise ValueError(f"Must be a bool: got {type(combined_artist_tag_fallback)}")
+        except KeyError:
+            combined_artist_tag_fallback = False
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for combined_artist_tag_fallback in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            derive_albumartist_from_tracks = data["derive_albumartist_from_tracks"]
+            del data["derive_albumartist_from_tracks"]
+            if not isinstance(derive_albumartist_from_tracks, bool):
+                raise ValueError(f"Must be a bool: got {type(derive_albumartist_from_tracks)}")
+        except KeyError:
+            derive_albumartist_from_tracks = False
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for derive_albumartist_from_tracks in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            va_artist_threshold = data["va_artist_threshold"]
+            del data["va_artist_threshold"]
+            if not isinstance(va_artist_threshold, int) or va_artist_threshold < 1:
+                raise ValueError(f"Must be a positive integer: got {va_artist_threshold}")
+        except KeyError:
+            va_artist_threshold = 10
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for va_artist_threshold in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            seed_added_at_from_fs = data["seed_added_at_from_fs"]
+            del data["seed_added_at_from_fs"]
+            if not isinstance(seed_added_at_from_fs, bool):
+                raise ValueError(f"Must be a bool: got {type(seed_added_at_from_fs)}")
+        except KeyError:
+            seed_added_at_from_fs = False
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for seed_added_at_from_fs in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            added_at_format = data["added_at_format"]
+            del data["added_at_format"]
+            if added_at_format not in ("datetime", "date"):
+                raise ValueError(f"Must be datetime or date: got {added_at_format}")
+        except KeyError:
+            added_at_format = "datetime"
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for added_at_format in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            id_uuid_version = data["id_uuid_version"]
+            del data["id_uuid_version"]
+            if id_uuid_version not in (4, 7) or isinstance(id_uuid_version, bool):
+                raise ValueError(f"Must be 4 or 7: got {id_uuid_version}")
+        except KeyError:
+            id_uuid_version = 7
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for id_uuid_version in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            compute_audio_sha256 = data["compute_audio_sha256"]
+            del data["compute_audio_sha256"]
+            if not isinstance(compute_audio_sha256, bool):
+                raise ValueError(f"Must be a bool: got {type(compute_audio_sha256)}")
+        except KeyError:
+            compute_audio_sha256 = False
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for compute_audio_sha256 in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            follow_symlinks = data["follow_symlinks"]
+            del data["follow_symlinks"]
+            if not isinstance(follow_symlinks, bool):
+                raise ValueError(f"Must be a bool: got {type(follow_symlinks)}")
+        except KeyError:
+            follow_symlinks = False
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for follow_symlinks in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            min_track_duration_seconds = data["min_track_duration_seconds"]
+            del data["min_track_duration_seconds"]
+            if (
+                not isinstance(min_track_duration_seconds, int)
+                or isinstance(min_track_duration_seconds, bool)
+                or min_track_duration_seconds < 0
+            ):
+                raise ValueError(
+                    f"Must be a non-negative integer: got {min_track_duration_seconds}"
+                )
+        except KeyError:
+            min_track_duration_seconds = None
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for min_track_duration_seconds in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        lock_timeout_seconds = LockTimeouts()
+        if "lock_timeout_seconds" in data:
+            try:
+                lock_data = data["lock_timeout_seconds"]
+                if not isinstance(lock_data, dict):
+                    raise ValueError(f"Must be a table: got {type(lock_data)}")
+                for field in dataclasses.fields(LockTimeouts):
+                    if field.name not in lock_data:
+                        continue
+                    value = lock_data[field.name]
+                    del lock_data[field.name]
+                    if not isinstance(value, int | float) or isinstance(value, bool) or value <= 0:
+                        raise ValueError(f"{field.name} must be a positive number: got {value}")
+                    lock_timeout_seconds = dataclasses.replace(
+                        lock_timeout_seconds, **{field.name: float(value)}
+                    )
+                if not lock_data:
+                    del data["lock_timeout_seconds"]
+            except ValueError as e:
+                raise InvalidConfigValueError(
+                    f"Invalid value for lock_timeout_seconds in configuration file ({cfgpath}): {e}"
+                ) from e
+
         try:
             ignore_release_directories = data["ignore_release_directories"]
             del data["ignore_release_directories"]
@@ -333,6 +601,129 @@ class Config:
                 f"Invalid value for ignore_release_directories in configuration file ({cfgpath}): {e}"
             ) from e
 
+        try:
+            collages_dirname = data["collages_dirname"]
+            del data["collages_dirname"]
+            if not isinstance(collages_dirname, str):
+                raise ValueError(f"Must be a str: got {type(collages_dirname)}")
+            if (
+                collages_dirname in ("", ".", "..")
+                or "/" in collages_dirname
+                or "\\" in collages_dirname
+            ):
+                raise ValueError(
+                    "Must be a non-empty directory name other than . or .. without slashes"
+                )
+        except KeyError:
+            collages_dirname = "!collages"
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for collages_dirname in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            playlists_dirname = data["playlists_dirname"]
+            del data["playlists_dirname"]
+            if not isinstance(playlists_dirname, str):
+                raise ValueError(f"Must be a str: got {type(playlists_dirname)}")
+            if (
+                playlists_dirname in ("", ".", "..")
+                or "/" in playlists_dirname
+                or "\\" in playlists_dirname
+            ):
+                raise ValueError(
+                    "Must be a non-empty directory name other than . or .. without slashes"
+                )
+        except KeyError:
+            playlists_dirname = "!playlists"
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for playlists_dirname in configuration file ({cfgpath}): {e}"
+            ) from e
+        if collages_dirname == playlists_dirname:
+            raise ConfigDecodeError(
+                f"collages_dirname and playlists_dirname must differ in configuration file ({cfgpath}): both are {collages_dirname}"
+            )
+
+        try:
+            collage_missing_grace_updates = data["collage_missing_grace_updates"]
+            del data["collage_missing_grace_updates"]
+            if (
+                not isinstance(collage_missing_grace_updates, int)
+                or isinstance(collage_missing_grace_updates, bool)
+                or collage_missing_grace_updates < 0
+            ):
+                raise ValueError(
+                    f"Must be a non-negative integer: got {collage_missing_grace_updates}"
+                )
+        except KeyError:
+            collage_missing_grace_updates = 0
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for collage_missing_grace_updates in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            collage_description_mark_new = data["collage_description_mark_new"]
+            del data["collage_description_mark_new"]
+            if not isinstance(collage_description_mark_new, bool):
+                raise ValueError(f"Must be a bool: got {type(collage_description_mark_new)}")
+        except KeyError:
+            collage_description_mark_new = False
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for collage_description_mark_new in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            collage_playlist_parallel_threshold = data["collage_playlist_parallel_threshold"]
+            del data["collage_playlist_parallel_threshold"]
+            if (
+                not isinstance(collage_playlist_parallel_threshold, int)
+                or isinstance(collage_playlist_parallel_threshold, bool)
+                or collage_playlist_parallel_threshold <= 0
+            ):
+                raise ValueError(
+                    f"Must be a positive integer: got {collage_playlist_parallel_threshold}"
+                )
+        except KeyError:
+            collage_playlist_parallel_threshold = None
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for collage_playlist_parallel_threshold in configuration file ({cfgpath}): {e}"
+            ) from e
+
+        try:
+            artist_role_order = data["artist_role_order"]
+            del data["artist_role_order"]
+            if not isinstance(artist_role_order, list):
+                raise ValueError(f"Must be a list[str]: got {type(artist_role_order)}")
+            for role in artist_role_order:
+                if not isinstance(role, str):
+                    raise ValueError(f"Each role must be of type str: got {type(role)}")
+                if role != "main" and role not in ARTIST_ROLE_SUFFIXES:
+                    raise ValueError(
+                        f"Unknown role {role}: valid roles are main, "
+                        + ", ".join(ARTIST_ROLE_SUFFIXES)
+                    )
+            if len(set(artist_role_order)) != len(artist_role_order):
+                raise ValueError("Each role may only appear once")
+            if "main" not in artist_role_order:
+                raise ValueError("Must contain the main role")
+            for role in artist_role_order[: artist_role_order.index("main")]:
+                if role not in ARTIST_ROLE_PREFIXES:
+                    raise ValueError(
+                        f"Role {role} cannot come before main: only "
+                        + ", ".join(ARTIST_ROLE_PREFIXES)
+                        + " can"
+                    )
+        except KeyError:
+            artist_role_order = DEFAULT_ARTIST_ROLE_ORDER
+        except ValueError as e:
+            raise InvalidConfigValueError(
+                f"Invalid value for artist_role_order in configuration file ({cfgpath}): {e}"
+            ) from e
+
         stored_metadata_rules: list[MetadataRule] = []
         for d in data.get("stored_metadata_rules", []):
             if not isinstance(d, dict):
@@ -446,6 +837,7 @@ class Config:
             fuse_mount_dir=fuse_mount_dir,
             cache_dir=cache_dir,
             max_proc=max_proc,
+            cache_migration_mode=cache_migration_mode,
             artist_aliases_map=artist_aliases_map,
             artist_aliases_parents_map=artist_aliases_parents_map,
             fuse_artists_whitelist=fuse_artists_whitelist,
@@ -456,9 +848,29 @@ class Config:
             fuse_labels_blacklist=fuse_labels_blacklist,
             cover_art_stems=cover_art_stems,
             valid_art_exts=valid_art_exts,
+            write_cover_art_stem=write_cover_art_stem,
+            convert_unsupported_covers=convert_unsupported_covers,
+            extract_embedded_covers=extract_embedded_covers,
             path_templates=path_templates,
+            rename_source_dirs=rename_source_dirs,
             rename_source_files=rename_source_files,
+            combined_artist_tag_fallback=combined_artist_tag_fallback,
+            derive_albumartist_from_tracks=derive_albumartist_from_tracks,
+            va_artist_threshold=va_artist_threshold,
+            seed_added_at_from_fs=seed_added_at_from_fs,
+            added_at_format=added_at_format,
+            id_uuid_version=id_uuid_version,
+            compute_audio_sha256=compute_audio_sha256,
+            follow_symlinks=follow_symlinks,
+            min_track_duration_seconds=min_track_duration_seconds,
+            lock_timeout_seconds=lock_timeout_seconds,
+            artist_role_order=artist_role_order,
             ignore_release_directories=ignore_release_directories,
+            collages_dirname=collages_dirname,
+            playlists_dirname=playlists_dirname,
+            collage_missing_grace_updates=collage_missing_grace_updates,
+            collage_description_mark_new=collage_description_mark_new,
+            collage_playlist_parallel_threshold=collage_playlist_parallel_threshold,
             stored_metadata_rules=stored_metadata_rules,
         )
 
@@ -466,6 +878,22 @@ class Config:
     def valid_cover_arts(self) -> list[str]:
         return [s + "." + e for s in self.cover_art_stems for e in self.valid_art_exts]
 
+    def is_valid_cover_art(self, p: Path) -> bool:
+        """Whether the file is named like a cover art, compared case-insensitively."""
+        return p.name.lower() in self.valid_cover_arts
+
+    def is_valid_art_ext(self, p: Path) -> bool:
+        """Whether the file has a supported cover art extension, compared case-insensitively."""
+        return p.suffix.lower().removeprefix(".") in self.valid_art_exts
+
+    @functools.cached_property
+    def collages_dir(self) -> Path:
+        return self.music_source_dir / self.collages_dirname
+
+    @functools.cached_property
+    def playlists_dir(self) -> Path:
+        return self.music_source_dir / self.playlists_dirname
+
     @functools.cached_property
     def cache_database_path(self) -> Path:
         return self.cache_dir / "cache.sqlite3"
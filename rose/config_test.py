@@ -4,7 +4,14 @@ from pathlib import Path
 import click
 import pytest
 
-from rose.config import Config, ConfigNotFoundError, InvalidConfigValueError, MissingConfigKeyError
+from rose.config import (
+    Config,
+    ConfigDecodeError,
+    ConfigNotFoundError,
+    InvalidConfigValueError,
+    LockTimeouts,
+    MissingConfigKeyError,
+)
 from rose.rule_parser import (
     MatcherPattern,
     MetadataAction,
@@ -42,6 +49,7 @@ def test_config_full() -> None:
                 fuse_mount_dir = "~/music"
                 cache_dir = "{cache_dir}"
                 max_proc = 8
+                cache_migration_mode = "try_migrate"
                 artist_aliases = [
                   {{ artist = "Abakus", aliases = ["Cinnamon Chasers"] }},
                   {{ artist = "tripleS", aliases = ["EVOLution", "LOVElution", "+(KR)ystal Eyes", "Acid Angel From Asia", "Acid Eyes"] }},
@@ -51,8 +59,31 @@ def test_config_full() -> None:
                 fuse_labels_blacklist = [ "zzz" ]
                 cover_art_stems = [ "aa", "bb" ]
                 valid_art_exts = [ "tiff" ]
+                write_cover_art_stem = "bb"
+                convert_unsupported_covers = true
+                extract_embedded_covers = true
                 ignore_release_directories = [ "dummy boy" ]
+                collages_dirname = "_collages"
+                playlists_dirname = "_playlists"
+                collage_missing_grace_updates = 2
+                collage_description_mark_new = true
+                collage_playlist_parallel_threshold = 500
+                rename_source_dirs = false
                 rename_source_files = true
+                combined_artist_tag_fallback = true
+                derive_albumartist_from_tracks = true
+                va_artist_threshold = 5
+                seed_added_at_from_fs = true
+                added_at_format = "date"
+                id_uuid_version = 4
+                compute_audio_sha256 = true
+                follow_symlinks = true
+                min_track_duration_seconds = 30
+                artist_role_order = ["composer", "main", "producer", "guest"]
+
+                [lock_timeout_seconds]
+                release = 5
+                editor = 120.0
 
                 [[stored_metadata_rules]]
                 matcher = "tracktitle:lala"
@@ -85,6 +116,7 @@ def test_config_full() -> None:
             fuse_mount_dir=Path.home() / "music",
             cache_dir=cache_dir,
             max_proc=8,
+            cache_migration_mode="try_migrate",
             artist_aliases_map={
                 "Abakus": ["Cinnamon Chasers"],
                 "tripleS": [
@@ -111,7 +143,22 @@ def test_config_full() -> None:
             fuse_labels_blacklist=["zzz"],
             cover_art_stems=["aa", "bb"],
             valid_art_exts=["tiff"],
+            write_cover_art_stem="bb",
+            convert_unsupported_covers=True,
+            extract_embedded_covers=True,
+            rename_source_dirs=False,
             rename_source_files=True,
+            combined_artist_tag_fallback=True,
+            derive_albumartist_from_tracks=True,
+            va_artist_threshold=5,
+            seed_added_at_from_fs=True,
+            added_at_format="date",
+            id_uuid_version=4,
+            compute_audio_sha256=True,
+            follow_symlinks=True,
+            min_track_duration_seconds=30,
+            lock_timeout_seconds=LockTimeouts(release=5.0, editor=120.0),
+            artist_role_order=["composer", "main", "producer", "guest"],
             path_templates=PathTemplateConfig(
                 source=PathTemplatePair(
                     release=PathTemplate("{{ title }}"), track=PathTemplate("{{ title }}")
@@ -140,6 +187,11 @@ def test_config_full() -> None:
                 playlists=PathTemplate("{{ title }}"),
             ),
             ignore_release_directories=["dummy boy"],
+            collages_dirname="_collages",
+            playlists_dirname="_playlists",
+            collage_missing_grace_updates=2,
+            collage_description_mark_new=True,
+            collage_playlist_parallel_threshold=500,
             stored_metadata_rules=[
                 MetadataRule(
                     matcher=MetadataMatcher(tags=["tracktitle"], pattern=MatcherPattern("lala")),
@@ -251,6 +303,16 @@ def test_config_value_validation() -> None:
         )
         config += "\nmax_proc = 8"
 
+        # cache_migration_mode
+        write(config + '\ncache_migration_mode = "lalala"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for cache_migration_mode in configuration file ({path}): Must be nuke or try_migrate: got lalala"
+        )
+        config += '\ncache_migration_mode = "try_migrate"'
+
         # artist_aliases
         write(config + '\nartist_aliases = "lalala"')
         with pytest.raises(InvalidConfigValueError) as excinfo:
@@ -446,6 +508,40 @@ def test_config_value_validation() -> None:
         )
         config += '\nvalid_art_exts = [ "jpg" ]'
 
+        # write_cover_art_stem
+        write(config + "\nwrite_cover_art_stem = 123")
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for write_cover_art_stem in configuration file ({path}): Must be a str: got <class 'int'>"
+        )
+        write(config + '\nwrite_cover_art_stem = "folder"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for write_cover_art_stem in configuration file ({path}): Must be one of the cover_art_stems: cover"
+        )
+
+        # convert_unsupported_covers
+        write(config + '\nconvert_unsupported_covers = "lalala"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for convert_unsupported_covers in configuration file ({path}): Must be a bool: got <class 'str'>"
+        )
+
+        # extract_embedded_covers
+        write(config + '\nextract_embedded_covers = "lalala"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for extract_embedded_covers in configuration file ({path}): Must be a bool: got <class 'str'>"
+        )
+
         # ignore_release_directories
         write(config + '\nignore_release_directories = "lalala"')
         with pytest.raises(InvalidConfigValueError) as excinfo:
@@ -463,6 +559,131 @@ def test_config_value_validation() -> None:
         )
         config += '\nignore_release_directories = [ ".stversions" ]'
 
+        # collages_dirname
+        write(config + "\ncollages_dirname = 123")
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for collages_dirname in configuration file ({path}): Must be a str: got <class 'int'>"
+        )
+        write(config + '\ncollages_dirname = "a/b"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for collages_dirname in configuration file ({path}): Must be a non-empty directory name other than . or .. without slashes"
+        )
+
+        write(config + '\ncollages_dirname = ".."')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for collages_dirname in configuration file ({path}): Must be a non-empty directory name other than . or .. without slashes"
+        )
+        write(config + '\ncollages_dirname = "a\\\\b"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for collages_dirname in configuration file ({path}): Must be a non-empty directory name other than . or .. without slashes"
+        )
+        write(config + '\ncollages_dirname = "shared"\nplaylists_dirname = "shared"')
+        with pytest.raises(ConfigDecodeError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"collages_dirname and playlists_dirname must differ in configuration file ({path}): both are shared"
+        )
+
+        # artist_role_order
+        write(config + '\nartist_role_order = "main"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for artist_role_order in configuration file ({path}): Must be a list[str]: got <class 'str'>"
+        )
+        write(config + '\nartist_role_order = ["main", "lyricist"]')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for artist_role_order in configuration file ({path}): Unknown role lyricist: valid roles are main, guest, producer, remixer, composer, djmixer"
+        )
+        write(config + '\nartist_role_order = ["main", "guest", "guest"]')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for artist_role_order in configuration file ({path}): Each role may only appear once"
+        )
+        write(config + '\nartist_role_order = ["guest"]')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for artist_role_order in configuration file ({path}): Must contain the main role"
+        )
+        write(config + '\nartist_role_order = ["guest", "main"]')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for artist_role_order in configuration file ({path}): Role guest cannot come before main: only djmixer, composer can"
+        )
+
+        # playlists_dirname
+        write(config + "\nplaylists_dirname = 123")
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for playlists_dirname in configuration file ({path}): Must be a str: got <class 'int'>"
+        )
+        write(config + '\nplaylists_dirname = ""')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for playlists_dirname in configuration file ({path}): Must be a non-empty directory name other than . or .. without slashes"
+        )
+
+        # collage_missing_grace_updates
+        write(config + '\ncollage_missing_grace_updates = "lalala"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for collage_missing_grace_updates in configuration file ({path}): Must be a non-negative integer: got lalala"
+        )
+        write(config + "\ncollage_missing_grace_updates = -1")
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for collage_missing_grace_updates in configuration file ({path}): Must be a non-negative integer: got -1"
+        )
+
+        # collage_description_mark_new
+        write(config + '\ncollage_description_mark_new = "lalala"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for collage_description_mark_new in configuration file ({path}): Must be a bool: got <class 'str'>"
+        )
+
+        # collage_playlist_parallel_threshold
+        write(config + "\ncollage_playlist_parallel_threshold = 0")
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for collage_playlist_parallel_threshold in configuration file ({path}): Must be a positive integer: got 0"
+        )
+
         # stored_metadata_rules
         write(config + '\nstored_metadata_rules = ["lalala"]')
         with pytest.raises(InvalidConfigValueError) as excinfo:
@@ -505,3 +726,102 @@ Failed to parse stored_metadata_rules in configuration file ({path}): rule {{'ma
             str(excinfo.value)
             == f"Invalid value for rename_source_files in configuration file ({path}): Must be a bool: got <class 'str'>"
         )
+
+        # rename_source_dirs
+        write(config + '\nrename_source_dirs = "lalala"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for rename_source_dirs in configuration file ({path}): Must be a bool: got <class 'str'>"
+        )
+
+        # combined_artist_tag_fallback
+        write(config + '\ncombined_artist_tag_fallback = "lalala"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for combined_artist_tag_fallback in configuration file ({path}): Must be a bool: got <class 'str'>"
+        )
+
+        # derive_albumartist_from_tracks
+        write(config + '\nderive_albumartist_from_tracks = "lalala"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for derive_albumartist_from_tracks in configuration file ({path}): Must be a bool: got <class 'str'>"
+        )
+
+        # va_artist_threshold
+        write(config + "\nva_artist_threshold = 0")
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for va_artist_threshold in configuration file ({path}): Must be a positive integer: got 0"
+        )
+
+        # seed_added_at_from_fs
+        write(config + '\nseed_added_at_from_fs = "lalala"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for seed_added_at_from_fs in configuration file ({path}): Must be a bool: got <class 'str'>"
+        )
+
+        # added_at_format
+        write(config + '\nadded_at_format = "lalala"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for added_at_format in configuration file ({path}): Must be datetime or date: got lalala"
+        )
+
+        # id_uuid_version
+        write(config + "\nid_uuid_version = 5")
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for id_uuid_version in configuration file ({path}): Must be 4 or 7: got 5"
+        )
+
+        # compute_audio_sha256
+        write(config + '\ncompute_audio_sha256 = "lalala"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for compute_audio_sha256 in configuration file ({path}): Must be a bool: got <class 'str'>"
+        )
+
+        # follow_symlinks
+        write(config + '\nfollow_symlinks = "lalala"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for follow_symlinks in configuration file ({path}): Must be a bool: got <class 'str'>"
+        )
+
+        # min_track_duration_seconds
+        write(config + '\nmin_track_duration_seconds = "lalala"')
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for min_track_duration_seconds in configuration file ({path}): Must be a non-negative integer: got lalala"
+        )
+
+        # lock_timeout_seconds
+        write(config + "\n[lock_timeout_seconds]\nrelease = 0")
+        with pytest.raises(InvalidConfigValueError) as excinfo:
+            Config.parse(config_path_override=path)
+        assert (
+            str(excinfo.value)
+            == f"Invalid value for lock_timeout_seconds in configuration file ({path}): release must be a positive number: got 0"
+        )
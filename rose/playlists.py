@@ -50,8 +50,12 @@ class PlaylistAlreadyExistsError(RoseExpectedError):
     pass
 
 
+class TrackNotInPlaylistError(RoseExpectedError):
+    pass
+
+
 def create_playlist(c: Config, name: str) -> None:
-    (c.music_source_dir / "!playlists").mkdir(parents=True, exist_ok=True)
+    c.playlists_dir.mkdir(parents=True, exist_ok=True)
     path = playlist_path(c, name)
     with lock(c, playlist_lock_name(name)):
         if path.exists():
@@ -82,7 +86,7 @@ def rename_playlist(c: Config, old_name: str, new_name: str) -> None:
             raise PlaylistAlreadyExistsError(f"Playlist {new_name} already exists")
         old_path.rename(new_path)
         # And also rename all files with the same stem (e.g. cover arts).
-        for old_adjacent_file in (c.music_source_dir / "!playlists").iterdir():
+        for old_adjacent_file in c.playlists_dir.iterdir():
             if old_adjacent_file.stem != old_path.stem:
                 continue
             new_adjacent_file = old_adjacent_file.with_name(
@@ -124,6 +128,35 @@ def remove_track_from_playlist(
     update_cache_for_playlists(c, [playlist_name], force=True)
 
 
+def set_playlist_track_positions(
+    c: Config,
+    playlist_name: str,
+    ordered_track_ids: list[str],
+) -> None:
+    """
+    Rewrite the playlist's tracks in the order of `ordered_track_ids`. Tracks that are not in
+    `ordered_track_ids` are dropped from the playlist. The entries of the remaining tracks are kept
+    as-is, so their `description_meta` and `missing` flags are preserved.
+    """
+    path = playlist_path(c, playlist_name)
+    if not path.exists():
+        raise PlaylistDoesNotExistError(f"Playlist {playlist_name} does not exist")
+    with lock(c, playlist_lock_name(playlist_name)):
+        with path.open("rb") as fp:
+            data = tomllib.load(fp)
+        entries = {t["uuid"]: t for t in data.get("tracks", [])}
+        if unknown := [x for x in ordered_track_ids if x not in entries]:
+            raise TrackNotInPlaylistError(
+                f"Tracks {', '.join(unknown)} are not in playlist {playlist_name}"
+            )
+        # Dict keys are unique, so duplicates in the argument collapse to their first position.
+        data["tracks"] = [entries[x] for x in dict.fromkeys(ordered_track_ids)]
+        with path.open("wb") as fp:
+            tomli_w.dump(data, fp)
+    logger.info(f"Reordered tracks of playlist {playlist_name}")
+    update_cache_for_playlists(c, [playlist_name], force=True)
+
+
 def add_track_to_playlist(
     c: Config,
     playlist_name: str,
@@ -190,7 +223,7 @@ def edit_playlist_in_editor(c: Config, playlist_name: str) -> None:
     path = playlist_path(c, playlist_name)
     if not path.exists():
         raise PlaylistDoesNotExistError(f"Playlist {playlist_name} does not exist")
-    with lock(c, playlist_lock_name(playlist_name), timeout=60.0):
+    with lock(c, playlist_lock_name(playlist_name), timeout=c.lock_timeout_seconds.editor):
         with path.open("rb") as fp:
             data = tomllib.load(fp)
         raw_tracks = data.get("tracks", [])
@@ -237,7 +270,7 @@ def set_playlist_cover_art(c: Config, playlist_name: str, new_cover_art_path: Pa
     file located at the passed in path to be the playlist's art file.
     """
     suffix = new_cover_art_path.suffix.lower()
-    if suffix[1:] not in c.valid_art_exts:
+    if not c.is_valid_art_ext(new_cover_art_path):
         raise InvalidCoverArtFileError(
             f"File {new_cover_art_path.name}'s extension is not supported for cover images: "
             "To change this, please read the configuration documentation"
@@ -246,8 +279,8 @@ def set_playlist_cover_art(c: Config, playlist_name: str, new_cover_art_path: Pa
     path = playlist_path(c, playlist_name)
     if not path.exists():
         raise PlaylistDoesNotExistError(f"Playlist {playlist_name} does not exist")
-    for f in (c.music_source_dir / "!playlists").iterdir():
-        if f.stem == playlist_name and f.suffix[1:].lower() in c.valid_art_exts:
+    for f in c.playlists_dir.iterdir():
+        if f.stem == playlist_name and c.is_valid_art_ext(f):
             logger.debug(f"Deleting existing cover art {f.name} in playlists")
             f.unlink()
     shutil.copyfile(new_cover_art_path, path.with_suffix(suffix))
@@ -261,8 +294,8 @@ def delete_playlist_cover_art(c: Config, playlist_name: str) -> None:
     if not path.exists():
         raise PlaylistDoesNotExistError(f"Playlist {playlist_name} does not exist")
     found = False
-    for f in (c.music_source_dir / "!playlists").iterdir():
-        if f.stem == playlist_name and f.suffix[1:].lower() in c.valid_art_exts:
+    for f in c.playlists_dir.iterdir():
+        if f.stem == playlist_name and c.is_valid_art_ext(f):
             logger.debug(f"Deleting existing cover art {f.name} in playlists")
             f.unlink()
             found = True
@@ -274,4 +307,4 @@ def delete_playlist_cover_art(c: Config, playlist_name: str) -> None:
 
 
 def playlist_path(c: Config, name: str) -> Path:
-    return c.music_source_dir / "!playlists" / f"{name}.toml"
+    return c.playlists_dir / f"{name}.toml"
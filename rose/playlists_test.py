@@ -10,6 +10,7 @@ from conftest import TEST_PLAYLIST_1, TEST_RELEASE_1
 from rose.cache import connect, update_cache
 from rose.config import Config
 from rose.playlists import (
+    TrackNotInPlaylistError,
     add_track_to_playlist,
     create_playlist,
     delete_playlist,
@@ -20,6 +21,7 @@ from rose.playlists import (
     remove_track_from_playlist,
     rename_playlist,
     set_playlist_cover_art,
+    set_playlist_track_positions,
 )
 
 
@@ -41,6 +43,38 @@ def test_remove_track_from_playlist(config: Config, source_dir: Path) -> None:
         assert ids == ["ilovetwice"]
 
 
+def test_set_playlist_track_positions(config: Config, source_dir: Path) -> None:
+    set_playlist_track_positions(config, "Lala Lisa", ["ilovetwice", "iloveloona"])
+
+    # Assert file is updated, with the entries preserved.
+    with (source_dir / "!playlists" / "Lala Lisa.toml").open("rb") as fp:
+        diskdata = tomllib.load(fp)
+    assert [t["uuid"] for t in diskdata["tracks"]] == ["ilovetwice", "iloveloona"]
+    assert diskdata["tracks"][0]["description_meta"] == "hahaha"
+
+    # Assert cache is updated.
+    with connect(config) as conn:
+        cursor = conn.execute(
+            """
+            SELECT track_id FROM playlists_tracks
+            WHERE playlist_name = 'Lala Lisa' ORDER BY position
+            """
+        )
+        ids = [r["track_id"] for r in cursor]
+        assert ids == ["ilovetwice", "iloveloona"]
+
+    # Tracks left out are dropped.
+    set_playlist_track_positions(config, "Lala Lisa", ["iloveloona"])
+    with (source_dir / "!playlists" / "Lala Lisa.toml").open("rb") as fp:
+        diskdata = tomllib.load(fp)
+    assert [t["uuid"] for t in diskdata["tracks"]] == ["iloveloona"]
+
+    # Tracks not in the playlist are rejected.
+    with pytest.raises(TrackNotInPlaylistError) as e:
+        set_playlist_track_positions(config, "Lala Lisa", ["iloveloona", "ilovetwice", "lalala"])
+    assert str(e.value) == "Tracks ilovetwice, lalala are not in playlist Lala Lisa"
+
+
 def test_playlist_lifecycle(config: Config, source_dir: Path) -> None:
     filepath = source_dir / "!playlists" / "You & Me.toml"
 
@@ -136,9 +170,11 @@ def test_dump_playlist(config: Config) -> None:
                 "id": "t1",
                 "source_path": f"{config.music_source_dir}/r1/01.m4a",
                 "tracktitle": "Track 1",
+                "tracksubtitle": None,
                 "tracknumber": "01",
                 "tracktotal": 2,
                 "discnumber": "01",
+                "discsubtitle": None,
                 "disctotal": 1,
                 "duration_seconds": 120,
                 "trackartists": {
@@ -177,9 +213,11 @@ def test_dump_playlist(config: Config) -> None:
                 "id": "t3",
                 "source_path": f"{config.music_source_dir}/r2/01.m4a",
                 "tracktitle": "Track 1",
+                "tracksubtitle": None,
                 "tracknumber": "01",
                 "tracktotal": 1,
                 "discnumber": "01",
+                "discsubtitle": None,
                 "disctotal": 1,
                 "duration_seconds": 120,
                 "trackartists": {
@@ -224,9 +262,11 @@ def test_dump_playlists(config: Config) -> None:
                     "id": "t1",
                     "source_path": f"{config.music_source_dir}/r1/01.m4a",
                     "tracktitle": "Track 1",
+                    "tracksubtitle": None,
                     "tracknumber": "01",
                     "tracktotal": 2,
                     "discnumber": "01",
+                    "discsubtitle": None,
                     "disctotal": 1,
                     "duration_seconds": 120,
                     "trackartists": {
@@ -265,9 +305,11 @@ def test_dump_playlists(config: Config) -> None:
                     "id": "t3",
                     "source_path": f"{config.music_source_dir}/r2/01.m4a",
                     "tracktitle": "Track 1",
+                    "tracksubtitle": None,
                     "tracknumber": "01",
                     "tracktotal": 1,
                     "discnumber": "01",
+                    "discsubtitle": None,
                     "disctotal": 1,
                     "duration_seconds": 120,
                     "trackartists": {
@@ -4,36 +4,44 @@ The releases module provides functions for interacting with releases.
 
 from __future__ import annotations
 
+import contextlib
 import json
 import logging
 import re
 import shlex
 import shutil
 from dataclasses import asdict, dataclass
+from datetime import datetime, timedelta
 from pathlib import Path
+from typing import IO, Literal
 
 import click
 import tomli_w
 import tomllib
 from send2trash import send2trash
 
-from rose.audiotags import AudioTags
+from rose.audiotags import AudioTags, is_supported_audio_file
 from rose.cache import (
     STORED_DATA_FILE_REGEX,
     CachedRelease,
     CachedTrack,
     calculate_release_logtext,
+    extract_embedded_cover,
     get_release,
     get_tracks_associated_with_release,
     get_tracks_associated_with_releases,
+    iter_releases_with_tracks,
+    list_collages_of_release,
     list_releases,
     lock,
     release_lock_name,
     update_cache_evict_nonexistent_releases,
-    update_cache_for_collages,
     update_cache_for_releases,
+    wanted_release_dirname,
+    write_stored_data_file,
 )
-from rose.common import Artist, ArtistMapping, RoseError, RoseExpectedError
+from rose.collages import remove_release_from_collage
+from rose.common import Artist, ArtistMapping, RoseError, RoseExpectedError, sanitize_dirname
 from rose.config import Config
 from rose.rule_parser import MetadataAction, MetadataMatcher
 from rose.rules import (
@@ -58,6 +66,10 @@ class ReleaseEditFailedError(RoseExpectedError):
     pass
 
 
+class UnsplittableReleaseDirectoryError(RoseExpectedError):
+    pass
+
+
 class InvalidReleaseEditResumeFileError(RoseExpectedError):
     pass
 
@@ -66,6 +78,14 @@ class UnknownArtistRoleError(RoseExpectedError):
     pass
 
 
+class InvalidReleaseFieldError(RoseExpectedError):
+    pass
+
+
+class InvalidReleaseTomlError(RoseExpectedError):
+    pass
+
+
 def dump_release(c: Config, release_id: str) -> str:
     release = get_release(c, release_id)
     if not release:
@@ -80,7 +100,7 @@ def dump_releases(c: Config, matcher: MetadataMatcher | None = None) -> str:
     release_ids = None
     if matcher:
         release_ids = [x.id for x in fast_search_for_matching_releases(c, matcher)]
-    releases = list_releases(c, release_ids)
+    releases = list_releases(c, release_ids, include_hidden=True)
     if matcher:
         releases = filter_release_false_positives_using_read_cache(matcher, releases)
     rt_pairs = get_tracks_associated_with_releases(c, releases)
@@ -92,21 +112,61 @@ def dump_releases(c: Config, matcher: MetadataMatcher | None = None) -> str:
     )
 
 
-def delete_release(c: Config, release_id: str) -> None:
+def dump_library_jsonl(c: Config, fp: IO[str]) -> None:
+    """
+    Write every release with its tracks to `fp` as JSON lines, one release per line. The releases
+    are streamed from the cache, so this works for libraries too large to dump as one JSON array.
+    """
+    for release, tracks in iter_releases_with_tracks(c):
+        release_json = {
+            **release.dump(),
+            "tracks": [t.dump(with_release_info=False) for t in tracks],
+        }
+        fp.write(json.dumps(release_json) + "\n")
+
+
+def find_misnamed_releases(c: Config) -> list[tuple[CachedRelease, str]]:
+    """
+    Find the releases whose source directory name differs from the name that the source release
+    path template wants, and return each with its wanted name. A directory named with a collision
+    suffix, e.g. `Wanted Name [2]`, is considered correctly named, as the cache updater would
+    produce such a name when renaming.
+    """
+    misnamed: list[tuple[CachedRelease, str]] = []
+    for release in list_releases(c, include_hidden=True):
+        wanted_dirname = wanted_release_dirname(c, release)
+        dirname = release.source_path.name
+        if dirname == wanted_dirname or re.fullmatch(
+            re.escape(wanted_dirname) + r" \[\d+\]", dirname
+        ):
+            continue
+        misnamed.append((release, wanted_dirname))
+    return misnamed
+
+
+def delete_release(c: Config, release_id: str) -> list[str]:
+    """
+    Trash a release and remove it from the collages that contain it, so that the collages are not
+    left with dangling missing entries. Returns the names of the collages that were edited.
+    """
     release = get_release(c, release_id)
     if not release:
         raise ReleaseDoesNotExistError(f"Release {release_id} does not exist")
     with lock(c, release_lock_name(release_id)):
+        # Edit the collages before trashing the release, as they log the release from the cache.
+        collages = list_collages_of_release(c, release_id)
+        for collage_name in collages:
+            remove_release_from_collage(c, collage_name, release_id)
         send2trash(release.source_path)
     release_logtext = calculate_release_logtext(
         title=release.albumtitle,
         year=release.year,
         artists=release.albumartists,
+        artist_role_order=c.artist_role_order,
     )
     logger.info(f"Trashed release {release_logtext}")
     update_cache_evict_nonexistent_releases(c)
-    # Update all collages so that the release is removed from whichever collages it was in.
-    update_cache_for_collages(c, None, force=True)
+    return collages
 
 
 def toggle_release_new(c: Config, release_id: str) -> None:
@@ -118,6 +178,7 @@ def toggle_release_new(c: Config, release_id: str) -> None:
         title=release.albumtitle,
         year=release.year,
         artists=release.albumartists,
+        artist_role_order=c.artist_role_order,
     )
 
     for f in release.source_path.iterdir():
@@ -127,8 +188,7 @@ def toggle_release_new(c: Config, release_id: str) -> None:
             with f.open("rb") as fp:
                 data = tomllib.load(fp)
             data["new"] = not data["new"]
-            with f.open("wb") as fp:
-                tomli_w.dump(data, fp)
+            write_stored_data_file(f, data)
         logger.info(f'Toggled "new"-ness of release {release_logtext} to {data["new"]}')
         update_cache_for_releases(c, [release.source_path], force=True)
         return
@@ -136,6 +196,68 @@ def toggle_release_new(c: Config, release_id: str) -> None:
     logger.critical(f"Failed to find .rose.toml in {release.source_path}")
 
 
+def clear_new_older_than(c: Config, days: int) -> list[str]:
+    """
+    Clear the "new"-ness of every new release that was added to the library more than `days` days
+    ago. Returns the IDs of the releases that were cleared.
+    """
+    cutoff = datetime.now().astimezone() - timedelta(days=days)
+    cleared: list[CachedRelease] = []
+    for release in list_releases(c, include_hidden=True):
+        if not release.new:
+            continue
+        try:
+            added_at = datetime.fromisoformat(release.added_at).astimezone()
+        except ValueError:
+            logger.debug(f"Skipping release {release.source_path}: unparseable added_at")
+            continue
+        if added_at >= cutoff:
+            continue
+        for f in release.source_path.iterdir():
+            if not STORED_DATA_FILE_REGEX.match(f.name):
+                continue
+            with lock(c, release_lock_name(release.id)):
+                with f.open("rb") as fp:
+                    data = tomllib.load(fp)
+                data["new"] = False
+                write_stored_data_file(f, data)
+            cleared.append(release)
+            break
+        else:
+            logger.critical(f"Failed to find .rose.toml in {release.source_path}")
+    logger.info(f'Cleared the "new"-ness of {len(cleared)} releases older than {days} days')
+    if cleared:
+        update_cache_for_releases(c, [r.source_path for r in cleared], force=True)
+    return [r.id for r in cleared]
+
+
+def toggle_release_hidden(c: Config, release_id: str) -> None:
+    release = get_release(c, release_id)
+    if not release:
+        raise ReleaseDoesNotExistError(f"Release {release_id} does not exist")
+
+    release_logtext = calculate_release_logtext(
+        title=release.albumtitle,
+        year=release.year,
+        artists=release.albumartists,
+        artist_role_order=c.artist_role_order,
+    )
+
+    for f in release.source_path.iterdir():
+        if not STORED_DATA_FILE_REGEX.match(f.name):
+            continue
+        with lock(c, release_lock_name(release_id)):
+            with f.open("rb") as fp:
+                data = tomllib.load(fp)
+            data["hidden"] = not data.get("hidden", False)
+            write_stored_data_file(f, data)
+        logger.info(f'Toggled "hidden"-ness of release {release_logtext} to {data["hidden"]}')
+        update_cache_for_releases(c, [release.source_path], force=True)
+        return
+
+    logger.critical(f"Failed to find .rose.toml in {release.source_path}")
+
+
 def set_release_cover_art(
     c: Config,
     release_id: str,
@@ -143,10 +265,11 @@ def set_release_cover_art(
 ) -> None:
     """
     This function removes all potential cover arts in the release source directory and copies the
-    file located at the passed in path to `cover.{ext}` in the release source directory.
+    file located at the passed in path to `{write_cover_art_stem}.{ext}` in the release source
+    directory.
     """
     suffix = new_cover_art_path.suffix.lower()
-    if suffix[1:] not in c.valid_art_exts:
+    if not c.is_valid_art_ext(new_cover_art_path):
         raise InvalidCoverArtFileError(
             f"File {new_cover_art_path.name}'s extension is not supported for cover images: "
             "To change this, please read the configuration documentation"
@@ -160,13 +283,17 @@ def set_release_cover_art(
         title=release.albumtitle,
         year=release.year,
         artists=release.albumartists,
+        artist_role_order=c.artist_role_order,
     )
 
     for f in release.source_path.iterdir():
-        if f.name.lower() in c.valid_cover_arts:
+        if c.is_valid_cover_art(f):
             logger.debug(f"Deleting existing cover art {f.name} in {release_logtext}")
             send2trash(f)
-    shutil.copyfile(new_cover_art_path, release.source_path / f"cover{new_cover_art_path.suffix}")
+    shutil.copyfile(
+        new_cover_art_path,
+        release.source_path / f"{c.write_cover_art_stem}{suffix}",
+    )
     logger.info(f"Set the cover of release {release_logtext} to {new_cover_art_path.name}")
     update_cache_for_releases(c, [release.source_path])
 
@@ -181,11 +308,12 @@ def delete_release_cover_art(c: Config, release_id: str) -> None:
         title=release.albumtitle,
         year=release.year,
         artists=release.albumartists,
+        artist_role_order=c.artist_role_order,
     )
 
     found = False
     for f in release.source_path.iterdir():
-        if f.name.lower() in c.valid_cover_arts:
+        if c.is_valid_cover_art(f):
             logger.debug(f"Deleting existing cover art {f.name} in {release_logtext}")
             send2trash(f)
             found = True
@@ -341,64 +469,8 @@ def edit_release(
             return
 
         try:
-            release_meta = MetadataRelease.from_toml(toml)
-            for t in tracks:
-                track_meta = release_meta.tracks[t.id]
-                tags = AudioTags.from_file(t.source_path)
-
-                dirty = False
-
-                # Track tags.
-                if tags.tracknumber != track_meta.tracknumber:
-                    tags.tracknumber = track_meta.tracknumber
-                    dirty = True
-                    logger.debug(f"Modified tag detected for {t.source_path}: tracknumber")
-                if tags.discnumber != track_meta.discnumber:
-                    tags.discnumber = track_meta.discnumber
-                    dirty = True
-                    logger.debug(f"Modified tag detected for {t.source_path}: discnumber")
-                if tags.title != track_meta.title:
-                    tags.title = track_meta.title
-                    dirty = True
-                    logger.debug(f"Modified tag detected for {t.source_path}: title")
-                tart = MetadataArtist.to_mapping(track_meta.artists)
-                if tags.trackartists != tart:
-                    tags.trackartists = tart
-                    dirty = True
-                    logger.debug(f"Modified tag detected for {t.source_path}: artists")
-
-                # Album tags.
-                if tags.album != release_meta.title:
-                    tags.album = release_meta.title
-                    dirty = True
-                    logger.debug(f"Modified tag detected for {t.source_path}: album")
-                if tags.releasetype != release_meta.releasetype:
-                    tags.releasetype = release_meta.releasetype.lower()
-                    dirty = True
-                    logger.debug(f"Modified tag detected for {t.source_path}: releasetype")
-                if tags.year != release_meta.year:
-                    tags.year = release_meta.year
-                    dirty = True
-                    logger.debug(f"Modified tag detected for {t.source_path}: year")
-                if tags.genre != release_meta.genres:
-                    tags.genre = release_meta.genres
-                    dirty = True
-                    logger.debug(f"Modified tag detected for {t.source_path}: genre")
-                if tags.label != release_meta.labels:
-                    tags.label = release_meta.labels
-                    dirty = True
-                    logger.debug(f"Modified tag detected for {t.source_path}: label")
-                aart = MetadataArtist.to_mapping(release_meta.artists)
-                if tags.albumartists != aart:
-                    tags.albumartists = aart
-                    dirty = True
-                    logger.debug(f"Modified tag detected for {t.source_path}: album_artists")
-
-                if dirty:
-                    logger.info(
-                        f"Flushing changed tags to {str(t.source_path).removeprefix(str(c.music_source_dir) + '/')}"
-                    )
-                    tags.flush()
+            release_meta = _parse_release_toml(toml)
+            _write_release_metadata(c, release_meta, tracks)
 
             if release_meta.new != release.new:
                 toggle_release_new(c, release.id)
@@ -426,6 +498,190 @@ You can reattempt the release edit and fix the metadata file with the command:
     update_cache_for_releases(c, [release.source_path], force=True)
 
 
+def _parse_release_toml(toml: str) -> MetadataRelease:
+    """Parse a release metadata TOML, raising `InvalidReleaseTomlError` if it is malformed."""
+    try:
+        release_meta = MetadataRelease.from_toml(toml)
+    except tomllib.TOMLDecodeError as e:
+        raise InvalidReleaseTomlError(f"Failed to parse release metadata TOML: {e}") from e
+    except KeyError as e:
+        raise InvalidReleaseTomlError(f"Release metadata TOML is missing the field {e}") from e
+    except (TypeError, AttributeError) as e:
+        raise InvalidReleaseTomlError(f"Release metadata TOML is malformed: {e}") from e
+    return release_meta
+
+
+def _write_release_metadata(
+    c: Config,
+    release_meta: MetadataRelease,
+    tracks: list[CachedTrack],
+) -> None:
+    """Write the release metadata to the tags of the release's tracks, flushing changed files."""
+    for t in tracks:
+        track_meta = release_meta.tracks[t.id]
+        tags = AudioTags.from_file(
+            t.source_path, combined_artist_tag_fallback=c.combined_artist_tag_fallback
+        )
+
+        dirty = False
+
+        # Track tags.
+        if tags.tracknumber != track_meta.tracknumber:
+            tags.tracknumber = track_meta.tracknumber
+            dirty = True
+            logger.debug(f"Modified tag detected for {t.source_path}: tracknumber")
+        if tags.discnumber != track_meta.discnumber:
+            tags.discnumber = track_meta.discnumber
+            dirty = True
+            logger.debug(f"Modified tag detected for {t.source_path}: discnumber")
+        if tags.title != track_meta.title:
+            tags.title = track_meta.title
+            dirty = True
+            logger.debug(f"Modified tag detected for {t.source_path}: title")
+        tart = MetadataArtist.to_mapping(track_meta.artists)
+        if tags.trackartists != tart:
+            tags.trackartists = tart
+            dirty = True
+            logger.debug(f"Modified tag detected for {t.source_path}: artists")
+
+        # Album tags.
+        if tags.album != release_meta.title:
+            tags.album = release_meta.title
+            dirty = True
+            logger.debug(f"Modified tag detected for {t.source_path}: album")
+        if tags.releasetype != release_meta.releasetype:
+            tags.releasetype = release_meta.releasetype.lower()
+            dirty = True
+            logger.debug(f"Modified tag detected for {t.source_path}: releasetype")
+        if tags.year != release_meta.year:
+            tags.year = release_meta.year
+            dirty = True
+            logger.debug(f"Modified tag detected for {t.source_path}: year")
+        if tags.genre != release_meta.genres:
+            tags.genre = release_meta.genres
+            dirty = True
+            logger.debug(f"Modified tag detected for {t.source_path}: genre")
+        if tags.label != release_meta.labels:
+            tags.label = release_meta.labels
+            dirty = True
+            logger.debug(f"Modified tag detected for {t.source_path}: label")
+        aart = MetadataArtist.to_mapping(release_meta.artists)
+        if tags.albumartists != aart:
+            tags.albumartists = aart
+            dirty = True
+            logger.debug(f"Modified tag detected for {t.source_path}: album_artists")
+
+        if dirty:
+            logger.info(
+                f"Flushing changed tags to {str(t.source_path).removeprefix(str(c.music_source_dir) + '/')}"
+            )
+            tags.flush()
+
+
+def release_to_toml(c: Config, release_id: str) -> str:
+    """
+    Serialize a release's metadata into an editable TOML document, containing the release-level
+    fields and each track's fields keyed by track ID. Apply edits with `apply_release_toml`.
+    """
+    release = get_release(c, release_id)
+    if not release:
+        raise ReleaseDoesNotExistError(f"Release {release_id} does not exist")
+    tracks = get_tracks_associated_with_release(c, release)
+    return MetadataRelease.from_cache(release, tracks).serialize()
+
+
+def apply_release_toml(c: Config, release_id: str, toml: str) -> None:
+    """
+    Validate a release metadata TOML document, as produced by `release_to_toml`, and write it to the
+    release's tags. Only the files whose tags changed are written. Unlike `edit_release`, the
+    document is rejected unless it describes exactly the release's tracks.
+    """
+    release = get_release(c, release_id)
+    if not release:
+        raise ReleaseDoesNotExistError(f"Release {release_id} does not exist")
+    with lock(c, release_lock_name(release_id)):
+        tracks = get_tracks_associated_with_release(c, release)
+        release_meta = _parse_release_toml(toml)
+        track_ids = {t.id for t in tracks}
+        if set(release_meta.tracks) != track_ids:
+            raise InvalidReleaseTomlError(
+                f"Release metadata TOML must contain exactly the release's tracks: expected "
+                f"{', '.join(sorted(track_ids))}, got {', '.join(sorted(release_meta.tracks))}"
+            )
+        _write_release_metadata(c, release_meta, tracks)
+    if release_meta.new != release.new:
+        toggle_release_new(c, release.id)
+    update_cache_for_releases(c, [release.source_path], force=True)
+
+
+ReleaseField = Literal["albumtitle", "releasetype", "year", "genre", "label", "albumartist"]
+RELEASE_FIELDS: list[ReleaseField] = [
+    "albumtitle",
+    "releasetype",
+    "year",
+    "genre",
+    "label",
+    "albumartist",
+]
+
+
+def propagate_release_tags(c: Config, release_id: str, fields: list[ReleaseField]) -> None:
+    """
+    Copy the passed-in release-level fields from the release's first track onto the tags of every
+    other track in the release. This fixes releases where only the first track's tags are correct.
+    """
+    for field in fields:
+        if field not in RELEASE_FIELDS:
+            raise InvalidReleaseFieldError(
+                f"Invalid release field {field}: must be one of {', '.join(RELEASE_FIELDS)}"
+            )
+
+    release = get_release(c, release_id)
+    if not release:
+        raise ReleaseDoesNotExistError(f"Release {release_id} does not exist")
+
+    # Trigger a quick cache update to ensure we are reading the liveliest data.
+    update_cache_for_releases(c, [release.source_path])
+
+    with lock(c, release_lock_name(release_id)):
+        tracks = get_tracks_associated_with_release(c, release)
+        if not tracks:
+            return
+        canonical = AudioTags.from_file(
+            tracks[0].source_path, combined_artist_tag_fallback=c.combined_artist_tag_fallback
+        )
+        for t in tracks[1:]:
+            tags = AudioTags.from_file(
+                t.source_path, combined_artist_tag_fallback=c.combined_artist_tag_fallback
+            )
+            dirty = False
+            if "albumtitle" in fields and tags.album != canonical.album:
+                tags.album = canonical.album
+                dirty = True
+            if "releasetype" in fields and tags.releasetype != canonical.releasetype:
+                tags.releasetype = canonical.releasetype
+                dirty = True
+            if "year" in fields and tags.year != canonical.year:
+                tags.year = canonical.year
+                dirty = True
+            if "genre" in fields and tags.genre != canonical.genre:
+                tags.genre = canonical.genre
+                dirty = True
+            if "label" in fields and tags.label != canonical.label:
+                tags.label = canonical.label
+                dirty = True
+            if "albumartist" in fields and tags.albumartists != canonical.albumartists:
+                tags.albumartists = canonical.albumartists
+                dirty = True
+            if dirty:
+                logger.info(
+                    f"Propagating release tags to {str(t.source_path).removeprefix(str(c.music_source_dir) + '/')}"
+                )
+                tags.flush()
+
+    update_cache_for_releases(c, [release.source_path], force=True)
+
+
 def run_actions_on_release(
     c: Config,
     release_id: str,
@@ -433,14 +689,21 @@ def run_actions_on_release(
     *,
     dry_run: bool = False,
     confirm_yes: bool = False,
-) -> None:
-    """Run rule engine actions on a release."""
+) -> int:
+    """Run rule engine actions on a release. Returns the number of files that were modified."""
     release = get_release(c, release_id)
     if release is None:
         raise ReleaseDoesNotExistError(f"Release {release_id} does not exist")
     tracks = get_tracks_associated_with_release(c, release)
-    audiotags = [AudioTags.from_file(t.source_path) for t in tracks]
-    execute_metadata_actions(c, actions, audiotags, dry_run=dry_run, confirm_yes=confirm_yes)
+    audiotags = [
+        AudioTags.from_file(
+            t.source_path, combined_artist_tag_fallback=c.combined_artist_tag_fallback
+        )
+        for t in tracks
+    ]
+    return execute_metadata_actions(
+        c, actions, audiotags, dry_run=dry_run, confirm_yes=confirm_yes
+    )
 
 
 def create_single_release(c: Config, track_path: Path) -> None:
@@ -449,8 +712,10 @@ def create_single_release(c: Config, track_path: Path) -> None:
         raise FileNotFoundError(f"Failed to extract single: file {track_path} not found")
 
     # Step 1. Compute the new directory name for the single.
-    af = AudioTags.from_file(track_path)
-    dirname = f"{artistsfmt(af.trackartists)} - "
+    af = AudioTags.from_file(
+        track_path, combined_artist_tag_fallback=c.combined_artist_tag_fallback
+    )
+    dirname = f"{artistsfmt(af.trackartists, c.artist_role_order)} - "
     if af.year:
         dirname += f"{af.year}. "
     dirname += af.title or "Unknown Title"
@@ -463,17 +728,21 @@ def create_single_release(c: Config, track_path: Path) -> None:
         dirname = f"{original_dirname} [{collision_no}]"
         collision_no += 1
     # Step 2. Make the new directory and copy the track. If cover art is in track's current
-    # directory, copy that over too.
+    # directory, copy that over too. Otherwise, fall back to the cover art embedded in the track.
     source_path = c.music_source_dir / dirname
     source_path.mkdir()
     new_track_path = source_path / f"01. {af.title}{track_path.suffix}"
     shutil.copyfile(track_path, new_track_path)
     for f in track_path.parent.iterdir():
-        if f.name.lower() in c.valid_cover_arts:
+        if c.is_valid_cover_art(f):
             shutil.copyfile(f, source_path / f.name)
             break
+    else:
+        extract_embedded_cover(c, source_path, track_path)
     # Step 3. Update the tags of the new track. Clear the Rose IDs too: this is a brand new track.
-    af = AudioTags.from_file(new_track_path)
+    af = AudioTags.from_file(
+        new_track_path, combined_artist_tag_fallback=c.combined_artist_tag_fallback
+    )
     af.album = af.title
     af.releasetype = "single"
     af.albumartists = af.trackartists
@@ -482,7 +751,9 @@ def create_single_release(c: Config, track_path: Path) -> None:
     af.release_id = None
     af.id = None
     af.flush()
-    af = AudioTags.from_file(new_track_path)
+    af = AudioTags.from_file(
+        new_track_path, combined_artist_tag_fallback=c.combined_artist_tag_fallback
+    )
     logger.info(f"Created phony single release {source_path.name}")
     # Step 4: Update the cache!
     update_cache_for_releases(c, [source_path])
@@ -496,3 +767,106 @@ def create_single_release(c: Config, track_path: Path) -> None:
             f"Impossible: Failed to parse release ID from newly created single directory {source_path}"
         )
     toggle_release_new(c, release_id)
+
+
+def split_release_directory(c: Config, path: Path) -> list[Path]:
+    """
+    Split a directory whose audio files belong to multiple albums into one release directory per
+    album, and index each new directory as its own release. The new directories are created in the
+    music source directory and named per the source release path template. The cover art of the
+    original directory is copied into each new directory. Returns the paths of the new directories.
+
+    Directories with audio files in subdirectories (e.g. disc folders) are not split, as the albums
+    of the nested files would be ambiguous.
+    """
+    path = path.resolve()
+    if not path.is_dir():
+        raise FileNotFoundError(f"Failed to split directory: directory {path} not found")
+    for f in path.rglob("*"):
+        if f.parent != path and f.is_file() and is_supported_audio_file(f):
+            raise UnsplittableReleaseDirectoryError(
+                f"Failed to split directory {path}: it has nested audio file {f.relative_to(path)}"
+            )
+
+    release_id = _release_id_of_directory(path)
+    with lock(c, release_lock_name(release_id)) if release_id else contextlib.nullcontext():
+        # Step 1. Group the audio files by their album tag.
+        albums: dict[str, list[AudioTags]] = {}
+        for f in sorted(path.iterdir()):
+            if f.is_file() and is_supported_audio_file(f):
+                af = AudioTags.from_file(
+                    f, combined_artist_tag_fallback=c.combined_artist_tag_fallback
+                )
+                albums.setdefault(af.album or "Unknown Album", []).append(af)
+        if len(albums) < 2:
+            logger.info(
+                f"No-Op: Audio files in {path} all belong to the same album, nothing to split"
+            )
+            return []
+        cover_art = next((f for f in path.iterdir() if c.is_valid_cover_art(f)), None)
+
+        # Step 2. Move each album's files into a new directory, and clear the release ID tags so
+        # that each album is indexed as a new release. The track IDs are kept so that playlists
+        # still match. The directories are renamed per the path template once they are indexed.
+        new_dirs: list[Path] = []
+        for album, tracks in albums.items():
+            source_path = _unused_source_path(c, sanitize_dirname(album, True))
+            source_path.mkdir()
+            for af in tracks:
+                af.release_id = None
+                af.flush()
+                af.path.rename(source_path / af.path.name)
+            if cover_art:
+                shutil.copyfile(cover_art, source_path / cover_art.name)
+            new_dirs.append(source_path)
+
+        # Step 3. Remove the original directory's datafile, as it no longer holds a release, and
+        # remove the directory itself if nothing else is left in it.
+        for f in path.iterdir():
+            if STORED_DATA_FILE_REGEX.match(f.name):
+                f.unlink()
+        if cover_art and list(path.iterdir()) == [cover_art]:
+            cover_art.unlink()
+        if not any(path.iterdir()):
+            path.rmdir()
+
+    # Step 4. Update the cache, and then rename the new directories per the path template.
+    update_cache_evict_nonexistent_releases(c)
+    leftover = [path] if path.exists() and path.parent == c.music_source_dir.resolve() else []
+    update_cache_for_releases(c, [*new_dirs, *leftover])
+    renamed_dirs: list[Path] = []
+    for album, source_path in zip(albums, new_dirs, strict=True):
+        release_id = _release_id_of_directory(source_path)
+        release = get_release(c, release_id) if release_id else None
+        if not release:
+            raise RoseError(f"Impossible: Failed to index split release directory {source_path}")
+        with lock(c, release_lock_name(release.id)):
+            wanted_path = _unused_source_path(c, wanted_release_dirname(c, release), source_path)
+            if wanted_path != source_path:
+                source_path.rename(wanted_path)
+        logger.info(f"Split album {album} out of {path.name} into {wanted_path.name}")
+        renamed_dirs.append(wanted_path)
+    update_cache_evict_nonexistent_releases(c)
+    update_cache_for_releases(c, renamed_dirs)
+    return renamed_dirs
+
+
+def _unused_source_path(c: Config, dirname: str, current: Path | None = None) -> Path:
+    """
+    Return the path in the music source directory for `dirname`, suffixed with a collision number if
+    another directory already has the name. `current` is the directory being named, if it exists.
+    """
+    source_path = c.music_source_dir / dirname
+    collision_no = 2
+    while source_path.exists() and source_path != current:
+        source_path = c.music_source_dir / f"{dirname} [{collision_no}]"
+        collision_no += 1
+    return source_path
+
+
+def _release_id_of_directory(path: Path) -> str | None:
+    """Return the release ID in the datafile of the directory, or None if it has no datafile."""
+    for f in path.iterdir():
+        if m := STORED_DATA_FILE_REGEX.match(f.name):
+            return m[1]
+    return None
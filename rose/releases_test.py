@@ -1,3 +1,5 @@
+import dataclasses
+import io
 import json
 import re
 import shutil
@@ -5,9 +7,10 @@ from pathlib import Path
 from typing import Any
 
 import pytest
+import tomli_w
 import tomllib
 
-from conftest import TEST_RELEASE_1
+from conftest import TEST_RELEASE_1, TEST_RELEASE_2
 from rose.audiotags import AudioTags
 from rose.cache import (
     CachedRelease,
@@ -15,23 +18,36 @@ from rose.cache import (
     connect,
     get_release,
     get_tracks_associated_with_release,
+    list_releases,
+    list_tracks,
     update_cache,
 )
 from rose.common import Artist, ArtistMapping
 from rose.config import Config
 from rose.releases import (
+    InvalidReleaseTomlError,
     ReleaseEditFailedError,
+    UnsplittableReleaseDirectoryError,
+    apply_release_toml,
+    clear_new_older_than,
     create_single_release,
     delete_release,
     delete_release_cover_art,
+    dump_library_jsonl,
     dump_release,
     dump_releases,
     edit_release,
+    find_misnamed_releases,
+    propagate_release_tags,
+    release_to_toml,
     run_actions_on_release,
     set_release_cover_art,
+    split_release_directory,
+    toggle_release_hidden,
     toggle_release_new,
 )
 from rose.rule_parser import MetadataAction, MetadataMatcher
+from rose.tracks import dump_tracks
 
 
 def test_delete_release(config: Config) -> None:
@@ -47,6 +63,20 @@ def test_delete_release(config: Config) -> None:
         assert cursor.fetchone()[0] == 0
 
 
+def test_delete_release_removes_from_collages(config: Config, source_dir: Path) -> None:
+    assert delete_release(config, "ilovecarly") == ["Rose Gold"]
+    assert not (source_dir / "Test Release 2").exists()
+    # The release is removed from the collage, not left behind as a missing release.
+    with (source_dir / "!collages" / "Rose Gold.toml").open("rb") as fp:
+        diskdata = tomllib.load(fp)
+    assert [r["uuid"] for r in diskdata["releases"]] == ["ilovenewjeans"]
+    with connect(config) as conn:
+        cursor = conn.execute(
+            "SELECT release_id FROM collages_releases WHERE collage_name = 'Rose Gold'"
+        )
+        assert [r["release_id"] for r in cursor] == ["ilovenewjeans"]
+
+
 def test_toggle_release_new(config: Config) -> None:
     shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
     update_cache(config)
@@ -74,6 +104,47 @@ def test_toggle_release_new(config: Config) -> None:
         assert cursor.fetchone()["new"]
 
 
+def test_clear_new_older_than(config: Config, source_dir: Path) -> None:
+    # Test Release 2 was added in 2023. Test Release 1 was just added.
+    toggle_release_new(config, "ilovecarly")
+    assert clear_new_older_than(config, 30) == ["ilovecarly"]
+    with (source_dir / "Test Release 2" / ".rose.ilovecarly.toml").open("rb") as fp:
+        assert tomllib.load(fp)["new"] is False
+    releases = list_releases(config)
+    assert [r.albumtitle for r in releases if r.new] == ["I Love Blackpink"]
+    # Nothing is left to clear.
+    assert clear_new_older_than(config, 30) == []
+
+
+def test_toggle_release_hidden(config: Config) -> None:
+    shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
+    update_cache(config)
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT id FROM releases")
+        release_id = cursor.fetchone()["id"]
+    datafile = config.music_source_dir / TEST_RELEASE_1.name / f".rose.{release_id}.toml"
+
+    # Hide the release.
+    toggle_release_hidden(config, release_id)
+    with datafile.open("rb") as fp:
+        data = tomllib.load(fp)
+        assert data["hidden"] is True
+    assert list_releases(config) == []
+    assert list_tracks(config) == []
+    assert [r.id for r in list_releases(config, include_hidden=True)] == [release_id]
+    assert len(list_tracks(config, include_hidden=True)) == 2
+    # Hidden releases are still included in full dumps.
+    assert [r["id"] for r in json.loads(dump_releases(config))] == [release_id]
+    assert len(json.loads(dump_tracks(config))) == 2
+
+    # Unhide the release.
+    toggle_release_hidden(config, release_id)
+    with datafile.open("rb") as fp:
+        data = tomllib.load(fp)
+        assert data["hidden"] is False
+    assert [r.id for r in list_releases(config)] == [release_id]
+
+
 def test_set_release_cover_art(isolated_dir: Path, config: Config) -> None:
     imagepath = isolated_dir / "folder.jpg"
     with imagepath.open("w") as fp:
@@ -105,6 +176,28 @@ def test_set_release_cover_art(isolated_dir: Path, config: Config) -> None:
         assert Path(cursor.fetchone()["cover_image_path"]) == cover_image_path
 
 
+def test_set_release_cover_art_custom_stem(isolated_dir: Path, config: Config) -> None:
+    config = dataclasses.replace(config, write_cover_art_stem="folder")
+    imagepath = isolated_dir / "new.jpg"
+    with imagepath.open("w") as fp:
+        fp.write("lalala")
+
+    release_dir = config.music_source_dir / TEST_RELEASE_1.name
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    update_cache(config)
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT id FROM releases")
+        release_id = cursor.fetchone()["id"]
+
+    set_release_cover_art(config, release_id, imagepath)
+    cover_image_path = release_dir / "folder.jpg"
+    assert cover_image_path.is_file()
+    assert not (release_dir / "cover.jpg").exists()
+    with connect(config) as conn:
+        cursor = conn.execute("SELECT cover_image_path FROM releases")
+        assert Path(cursor.fetchone()["cover_image_path"]) == cover_image_path
+
+
 def test_remove_release_cover_art(config: Config) -> None:
     release_dir = config.music_source_dir / TEST_RELEASE_1.name
     shutil.copytree(TEST_RELEASE_1, release_dir)
@@ -174,13 +267,18 @@ def test_edit_release(monkeypatch: Any, config: Config, source_dir: Path) -> Non
         id=release_id,
         source_path=release_path,
         cover_image_path=None,
+        cover_hash=None,
+        cover_size=None,
+        cover_mtime=None,
         added_at=release.added_at,
         datafile_mtime=release.datafile_mtime,
         albumtitle="I Really Love Blackpink",
         releasetype="single",
         year=2222,
         new=False,
+        hidden=False,
         disctotal=1,
+        disc_titles={},
         genres=["J-Pop", "Pop-Rap"],
         labels=["YG Entertainment"],
         albumartists=ArtistMapping(main=[Artist("BLACKPINK"), Artist("JISOO")]),
@@ -193,11 +291,16 @@ def test_edit_release(monkeypatch: Any, config: Config, source_dir: Path) -> Non
             source_path=release_path / "01.m4a",
             source_mtime=tracks[0].source_mtime,
             tracktitle="I Do Like That",
+            tracksubtitle=None,
             tracknumber="1",
             tracktotal=2,
             discnumber="1",
+            discsubtitle=None,
             disctotal=1,
             duration_seconds=2,
+            audio_sha256=None,
+            isrc=None,
+            original_filename=None,
             trackartists=ArtistMapping(main=[Artist("BLACKPINK")]),
             metahash=tracks[0].metahash,
             release=release,
@@ -207,11 +310,16 @@ def test_edit_release(monkeypatch: Any, config: Config, source_dir: Path) -> Non
             source_path=release_path / "02.m4a",
             source_mtime=tracks[1].source_mtime,
             tracktitle="All Eyes On Me",
+            tracksubtitle=None,
             tracknumber="2",
             tracktotal=2,
             discnumber="1",
+            discsubtitle=None,
             disctotal=1,
             duration_seconds=2,
+            audio_sha256=None,
+            isrc=None,
+            original_filename=None,
             trackartists=ArtistMapping(main=[Artist("JISOO")]),
             metahash=tracks[1].metahash,
             release=release,
@@ -219,6 +327,56 @@ def test_edit_release(monkeypatch: Any, config: Config, source_dir: Path) -> Non
     ]
 
 
+def test_release_toml_roundtrip(config: Config, source_dir: Path) -> None:
+    data = tomllib.loads(release_to_toml(config, "ilovecarly"))
+    assert data["title"] == "I Love Carly"
+    assert len(data["tracks"]) == 2
+
+    data["title"] = "I Really Love Carly"
+    data["releasetype"] = "ep"
+    first_track_id = sorted(data["tracks"], key=lambda x: data["tracks"][x]["tracknumber"])[0]
+    data["tracks"][first_track_id]["title"] = "Run Away With Me"
+    apply_release_toml(config, "ilovecarly", tomli_w.dumps(data))
+
+    af = AudioTags.from_file(source_dir / "Test Release 2" / "01.m4a")
+    assert af.album == "I Really Love Carly"
+    assert af.releasetype == "ep"
+    assert af.title == "Run Away With Me"
+    af = AudioTags.from_file(source_dir / "Test Release 2" / "02.m4a")
+    assert af.album == "I Really Love Carly"
+    # The cache is updated, so serializing the release again produces the edited TOML.
+    assert tomllib.loads(release_to_toml(config, "ilovecarly")) == data
+
+
+@pytest.mark.usefixtures("source_dir")
+def test_apply_release_toml_invalid(config: Config) -> None:
+    data = tomllib.loads(release_to_toml(config, "ilovecarly"))
+    with pytest.raises(InvalidReleaseTomlError):
+        apply_release_toml(config, "ilovecarly", "title = ")
+    del data["title"]
+    with pytest.raises(InvalidReleaseTomlError):
+        apply_release_toml(config, "ilovecarly", tomli_w.dumps(data))
+    data = tomllib.loads(release_to_toml(config, "ilovecarly"))
+    data["tracks"].popitem()
+    with pytest.raises(InvalidReleaseTomlError):
+        apply_release_toml(config, "ilovecarly", tomli_w.dumps(data))
+
+
+def test_edit_release_ignores_unknown_tracks(
+    monkeypatch: Any, config: Config, source_dir: Path
+) -> None:
+    """Test that, unlike apply_release_toml, edit_release ignores tracks not on the release."""
+    data = tomllib.loads(release_to_toml(config, "ilovecarly"))
+    data["title"] = "I Really Love Carly"
+    data["tracks"]["lalala"] = next(iter(data["tracks"].values()))
+    new_toml = tomli_w.dumps(data)
+    monkeypatch.setattr("rose.collages.click.edit", lambda *_, **__: new_toml)
+
+    edit_release(config, "ilovecarly")
+    af = AudioTags.from_file(source_dir / "Test Release 2" / "01.m4a")
+    assert af.album == "I Really Love Carly"
+
+
 def test_edit_release_failure_and_resume(
     monkeypatch: Any, config: Config, source_dir: Path
 ) -> None:
@@ -325,13 +483,18 @@ def test_edit_release_failure_and_resume(
         id=release_id,
         source_path=release_path,
         cover_image_path=None,
+        cover_hash=None,
+        cover_size=None,
+        cover_mtime=None,
         added_at=release.added_at,
         datafile_mtime=release.datafile_mtime,
         albumtitle="I Really Love Blackpink",
         releasetype="single",
         year=2222,
         new=False,
+        hidden=False,
         disctotal=1,
+        disc_titles={},
         genres=["J-Pop", "Pop-Rap"],
         labels=["YG Entertainment"],
         albumartists=ArtistMapping(main=[Artist("BLACKPINK"), Artist("JISOO")]),
@@ -344,11 +507,16 @@ def test_edit_release_failure_and_resume(
             source_path=release_path / "01.m4a",
             source_mtime=tracks[0].source_mtime,
             tracktitle="I Do Like That",
+            tracksubtitle=None,
             tracknumber="1",
             tracktotal=2,
             discnumber="1",
+            discsubtitle=None,
             disctotal=1,
             duration_seconds=2,
+            audio_sha256=None,
+            isrc=None,
+            original_filename=None,
             trackartists=ArtistMapping(main=[Artist("BLACKPINK")]),
             metahash=tracks[0].metahash,
             release=release,
@@ -358,11 +526,16 @@ def test_edit_release_failure_and_resume(
             source_path=release_path / "02.m4a",
             source_mtime=tracks[1].source_mtime,
             tracktitle="All Eyes On Me",
+            tracksubtitle=None,
             tracknumber="2",
             tracktotal=2,
             discnumber="1",
+            discsubtitle=None,
             disctotal=1,
             duration_seconds=2,
+            audio_sha256=None,
+            isrc=None,
+            original_filename=None,
             trackartists=ArtistMapping(main=[Artist("JISOO")]),
             metahash=tracks[1].metahash,
             release=release,
@@ -392,6 +565,31 @@ def test_extract_single_release(config: Config) -> None:
     assert af.albumartists == af.trackartists
 
 
+def test_extract_single_release_embedded_cover_art(config: Config) -> None:
+    shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
+    update_cache(config)
+    create_single_release(config, config.music_source_dir / TEST_RELEASE_1.name / "02.m4a")
+    # Without cover art in the directory, the cover art embedded in the track is extracted.
+    source_path = config.music_source_dir / "BLACKPINK - 1990. Track 2"
+    assert (source_path / "cover.png").read_bytes().startswith(b"\x89PNG")
+
+
+def test_extract_single_release_embedded_cover_art_respects_config(config: Config) -> None:
+    shutil.copytree(TEST_RELEASE_1, config.music_source_dir / TEST_RELEASE_1.name)
+    update_cache(config)
+    config = dataclasses.replace(config, write_cover_art_stem="folder")
+    create_single_release(config, config.music_source_dir / TEST_RELEASE_1.name / "01.m4a")
+    source_path = config.music_source_dir / "BLACKPINK - 1990. Track 1"
+    assert (source_path / "folder.png").is_file()
+    assert not (source_path / "cover.png").exists()
+    # Embedded cover art in a format outside of `valid_art_exts` is not extracted.
+    config = dataclasses.replace(config, valid_art_exts=["jpg"])
+    create_single_release(config, config.music_source_dir / TEST_RELEASE_1.name / "02.m4a")
+    source_path = config.music_source_dir / "BLACKPINK - 1990. Track 2"
+    assert source_path.is_dir()
+    assert not any(f.suffix == ".png" for f in source_path.iterdir())
+
+
 @pytest.mark.usefixtures("seeded_cache")
 def test_dump_release(config: Config) -> None:
     assert json.loads(dump_release(config, "r1")) == {
@@ -403,7 +601,9 @@ def test_dump_release(config: Config) -> None:
         "releasetype": "album",
         "year": 2023,
         "new": False,
+        "hidden": False,
         "disctotal": 1,
+        "disc_titles": {},
         "genres": ["Techno", "Deep House"],
         "labels": ["Silk Music"],
         "albumartists": {
@@ -431,11 +631,13 @@ def test_dump_release(config: Config) -> None:
                     "remixer": [],
                 },
                 "discnumber": "01",
+                "discsubtitle": None,
                 "disctotal": 1,
                 "duration_seconds": 120,
                 "id": "t1",
                 "source_path": f"{config.music_source_dir}/r1/01.m4a",
                 "tracktitle": "Track 1",
+                "tracksubtitle": None,
                 "tracknumber": "01",
                 "tracktotal": 2,
             },
@@ -452,11 +654,13 @@ def test_dump_release(config: Config) -> None:
                     "remixer": [],
                 },
                 "discnumber": "01",
+                "discsubtitle": None,
                 "disctotal": 1,
                 "duration_seconds": 240,
                 "id": "t2",
                 "source_path": f"{config.music_source_dir}/r1/02.m4a",
                 "tracktitle": "Track 2",
+                "tracksubtitle": None,
                 "tracknumber": "02",
                 "tracktotal": 2,
             },
@@ -476,7 +680,9 @@ def test_dump_releases(config: Config) -> None:
             "releasetype": "album",
             "year": 2023,
             "new": False,
+            "hidden": False,
             "disctotal": 1,
+            "disc_titles": {},
             "genres": ["Techno", "Deep House"],
             "labels": ["Silk Music"],
             "albumartists": {
@@ -504,11 +710,13 @@ def test_dump_releases(config: Config) -> None:
                         "remixer": [],
                     },
                     "discnumber": "01",
+                    "discsubtitle": None,
                     "disctotal": 1,
                     "duration_seconds": 120,
                     "id": "t1",
                     "source_path": f"{config.music_source_dir}/r1/01.m4a",
                     "tracktitle": "Track 1",
+                    "tracksubtitle": None,
                     "tracknumber": "01",
                     "tracktotal": 2,
                 },
@@ -525,11 +733,13 @@ def test_dump_releases(config: Config) -> None:
                         "remixer": [],
                     },
                     "discnumber": "01",
+                    "discsubtitle": None,
                     "disctotal": 1,
                     "duration_seconds": 240,
                     "id": "t2",
                     "source_path": f"{config.music_source_dir}/r1/02.m4a",
                     "tracktitle": "Track 2",
+                    "tracksubtitle": None,
                     "tracknumber": "02",
                     "tracktotal": 2,
                 },
@@ -544,7 +754,9 @@ def test_dump_releases(config: Config) -> None:
             "releasetype": "album",
             "year": 2021,
             "new": False,
+            "hidden": False,
             "disctotal": 1,
+            "disc_titles": {},
             "genres": ["Classical"],
             "labels": ["Native State"],
             "albumartists": {
@@ -566,11 +778,13 @@ def test_dump_releases(config: Config) -> None:
                         "remixer": [],
                     },
                     "discnumber": "01",
+                    "discsubtitle": None,
                     "disctotal": 1,
                     "duration_seconds": 120,
                     "id": "t3",
                     "source_path": f"{config.music_source_dir}/r2/01.m4a",
                     "tracktitle": "Track 1",
+                    "tracksubtitle": None,
                     "tracknumber": "01",
                     "tracktotal": 1,
                 }
@@ -585,7 +799,9 @@ def test_dump_releases(config: Config) -> None:
             "releasetype": "album",
             "year": 2021,
             "new": True,
+            "hidden": False,
             "disctotal": 1,
+            "disc_titles": {},
             "genres": [],
             "labels": [],
             "albumartists": {
@@ -607,11 +823,13 @@ def test_dump_releases(config: Config) -> None:
                         "remixer": [],
                     },
                     "discnumber": "01",
+                    "discsubtitle": None,
                     "disctotal": 1,
                     "duration_seconds": 120,
                     "id": "t4",
                     "source_path": f"{config.music_source_dir}/r3/01.m4a",
                     "tracktitle": "Track 1",
+                    "tracksubtitle": None,
                     "tracknumber": "01",
                     "tracktotal": 1,
                 }
@@ -633,7 +851,9 @@ def test_dump_releases_matcher(config: Config) -> None:
             "releasetype": "album",
             "year": 2021,
             "new": False,
+            "hidden": False,
             "disctotal": 1,
+            "disc_titles": {},
             "genres": ["Classical"],
             "labels": ["Native State"],
             "albumartists": {
@@ -655,11 +875,13 @@ def test_dump_releases_matcher(config: Config) -> None:
                         "remixer": [],
                     },
                     "discnumber": "01",
+                    "discsubtitle": None,
                     "disctotal": 1,
                     "duration_seconds": 120,
                     "id": "t3",
                     "source_path": f"{config.music_source_dir}/r2/01.m4a",
                     "tracktitle": "Track 1",
+                    "tracksubtitle": None,
                     "tracknumber": "01",
                     "tracktotal": 1,
                 }
@@ -668,8 +890,101 @@ def test_dump_releases_matcher(config: Config) -> None:
     ]
 
 
+@pytest.mark.usefixtures("seeded_cache")
+def test_dump_library_jsonl(config: Config) -> None:
+    fp = io.StringIO()
+    dump_library_jsonl(config, fp)
+    lines = fp.getvalue().splitlines()
+    with connect(config) as conn:
+        num_releases = conn.execute("SELECT COUNT(*) FROM releases").fetchone()[0]
+    assert len(lines) == num_releases
+    # Each line is a complete release, matching the single-blob dump.
+    assert [json.loads(line) for line in lines] == sorted(
+        json.loads(dump_releases(config)), key=lambda r: r["id"]
+    )
+
+
+def test_find_misnamed_releases(config: Config) -> None:
+    release_dir = config.music_source_dir / "random"
+    shutil.copytree(TEST_RELEASE_1, release_dir)
+    update_cache(config)
+    misnamed = find_misnamed_releases(config)
+    assert [(r.source_path.name, wanted) for r, wanted in misnamed] == [
+        ("random", "BLACKPINK - 1990. I Love Blackpink [NEW]")
+    ]
+
+    # Once the directory carries its wanted name, it is no longer reported.
+    release_dir.rename(config.music_source_dir / "BLACKPINK - 1990. I Love Blackpink [NEW]")
+    update_cache(config)
+    assert find_misnamed_releases(config) == []
+
+
+def test_propagate_release_tags(config: Config, source_dir: Path) -> None:
+    canonical = AudioTags.from_file(source_dir / "Test Release 2" / "01.m4a")
+    af = AudioTags.from_file(source_dir / "Test Release 2" / "02.m4a")
+    af.album = "I Love Carly (Wrong)"
+    af.year = 2000
+    af.genre = ["Wrong Genre"]
+    af.flush()
+    update_cache(config)
+
+    propagate_release_tags(config, "ilovecarly", ["albumtitle", "year"])
+
+    af = AudioTags.from_file(source_dir / "Test Release 2" / "02.m4a")
+    assert af.album == canonical.album
+    assert af.year == canonical.year
+    # Fields that were not passed in should not be propagated.
+    assert af.genre == ["Wrong Genre"]
+    release = get_release(config, "ilovecarly")
+    assert release is not None
+    assert release.albumtitle == canonical.album
+    assert release.year == canonical.year
+
+
 def test_run_action_on_release(config: Config, source_dir: Path) -> None:
     action = MetadataAction.parse("tracktitle::replace:Bop")
-    run_actions_on_release(config, "ilovecarly", [action])
+    assert run_actions_on_release(config, "ilovecarly", [action]) == 2
     af = AudioTags.from_file(source_dir / "Test Release 2" / "01.m4a")
     assert af.title == "Bop"
+    # Files that the actions do not change are not counted.
+    assert run_actions_on_release(config, "ilovecarly", [action]) == 0
+
+
+def test_split_release_directory(config: Config) -> None:
+    dump_dir = config.music_source_dir / "Dump"
+    dump_dir.mkdir()
+    shutil.copyfile(TEST_RELEASE_1 / "01.m4a", dump_dir / "blackpink.m4a")
+    shutil.copyfile(TEST_RELEASE_2 / "01.m4a", dump_dir / "carly.m4a")
+    (dump_dir / "cover.jpg").touch()
+    update_cache(config)
+
+    new_dirs = split_release_directory(config, dump_dir)
+    # The new directories are named per the source release path template.
+    assert {d.name for d in new_dirs} == {
+        "BLACKPINK - 1990. I Love Blackpink [NEW]",
+        "Carly Rae Jepsen - 1990. I Love Carly [NEW]",
+    }
+    assert not dump_dir.exists()
+    blackpink_dir = config.music_source_dir / "BLACKPINK - 1990. I Love Blackpink [NEW]"
+    assert (blackpink_dir / "blackpink.m4a").is_file()
+    assert (blackpink_dir / "cover.jpg").is_file()
+    carly_dir = config.music_source_dir / "Carly Rae Jepsen - 1990. I Love Carly [NEW]"
+    assert (carly_dir / "carly.m4a").is_file()
+
+    releases = list_releases(config)
+    assert {r.albumtitle for r in releases} == {"I Love Blackpink", "I Love Carly"}
+    assert {r.source_path for r in releases} == {d.resolve() for d in new_dirs}
+
+
+def test_split_release_directory_refuses_nested_audio(config: Config) -> None:
+    dump_dir = config.music_source_dir / "Dump"
+    (dump_dir / "Disc 2").mkdir(parents=True)
+    shutil.copyfile(TEST_RELEASE_1 / "01.m4a", dump_dir / "blackpink.m4a")
+    shutil.copyfile(TEST_RELEASE_2 / "01.m4a", dump_dir / "Disc 2" / "carly.m4a")
+    update_cache(config)
+
+    with pytest.raises(UnsplittableReleaseDirectoryError):
+        split_release_directory(config, dump_dir)
+    # Nothing was moved.
+    assert (dump_dir / "blackpink.m4a").is_file()
+    assert (dump_dir / "Disc 2" / "carly.m4a").is_file()
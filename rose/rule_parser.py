@@ -55,6 +55,7 @@ Tag = Literal[
     "tracktotal",
     "discnumber",
     "disctotal",
+    "duration",
     "albumtitle",
     "albumartist[main]",
     "albumartist[guest]",
@@ -90,6 +91,7 @@ ALL_TAGS: dict[str, list[Tag]] = {
     "tracktotal": ["tracktotal"],
     "discnumber": ["discnumber"],
     "disctotal": ["disctotal"],
+    "duration": ["duration"],
     "albumtitle": ["albumtitle"],
     "albumartist": [
         "albumartist[main]",
@@ -154,11 +156,22 @@ SINGLE_VALUE_TAGS: list[Tag] = [
     "tracktotal",
     "discnumber",
     "disctotal",
+    "duration",
     "albumtitle",
     "releasetype",
     "year",
 ]
 
+# Tags whose values are integers. These tags support numeric comparisons in patterns.
+NUMERIC_TAGS: list[Tag] = [
+    "tracknumber",
+    "tracktotal",
+    "discnumber",
+    "disctotal",
+    "duration",
+    "year",
+]
+
 RELEASE_TAGS: list[Tag] = [
     "albumtitle",
     "albumartist[main]",
@@ -223,16 +236,55 @@ class DeleteAction:
     """
 
 
+@dataclass
+class NumericComparison:
+    """
+    Compares the integer value of a tag against `value`. The `-` operator is an inclusive range
+    from `value` to `upper`.
+    """
+
+    operator: Literal[">", ">=", "<", "<=", "-"]
+    value: int
+    upper: int | None = None
+
+
+NUMERIC_COMPARISON_REGEX = re.compile(r"^(>=|<=|>|<)(\d+)$")
+NUMERIC_RANGE_REGEX = re.compile(r"^(\d+)-(\d+)$")
+
+
+def parse_numeric_comparison(pattern: str, tags: list[Tag]) -> NumericComparison | None:
+    """
+    Parse a numeric comparison (`>2020`, `<=300`) or range (`120-130`) out of a pattern. Comparison
+    operators are only valid on numeric tags. A range on non-numeric tags is treated as a substring.
+    """
+    if m := NUMERIC_COMPARISON_REGEX.match(pattern):
+        if nonnumeric := [t for t in tags if t not in NUMERIC_TAGS]:
+            raise InvalidRuleError(
+                f"Numeric comparison {pattern} is not supported on non-numeric tags {', '.join(nonnumeric)}"
+            )
+        return NumericComparison(operator=m[1], value=int(m[2]))  # type: ignore
+    if (m := NUMERIC_RANGE_REGEX.match(pattern)) and all(t in NUMERIC_TAGS for t in tags):
+        return NumericComparison(operator="-", value=int(m[1]), upper=int(m[2]))
+    return None
+
+
 @dataclass
 class MatcherPattern:
     # Substring match with support for `^$` strict start / strict end matching.
     pattern: str
     case_insensitive: bool = False
+    # Set if the pattern is a numeric comparison, in which case it is evaluated against the tag's
+    # integer value instead of as a substring.
+    numeric: NumericComparison | None = None
+    # Set by the `absent` flag, in which case the pattern matches tags that have no value.
+    absent: bool = False
 
     def __str__(self) -> str:
         r = self.pattern.replace(":", r"\:")
         if self.case_insensitive:
             r += ":i"
+        if self.absent:
+            r += ":absent"
         return r
 
 
@@ -289,8 +341,10 @@ class MetadataMatcher:
         pattern, fwd = take(raw[idx:], ":", including=False)
         idx += fwd
 
-        # If more input is remaining, it should be optional single-character flags.
+        # If more input is remaining, it should be optional single-character flags, or the
+        # `absent` flag.
         case_insensitive = False
+        absent = False
         if idx < len(raw) and raw[idx] == ":":
             idx += 1
             flags, fwd = take(raw[idx:], ":")
@@ -298,8 +352,17 @@ class MetadataMatcher:
                 raise RuleSyntaxError(
                     **err,
                     index=idx,
-                    feedback="No flags specified: Please remove this section (by deleting the colon) or specify one of the supported flags: `i` (case insensitive).",
+                    feedback="No flags specified: Please remove this section (by deleting the colon) or specify one of the supported flags: `i` (case insensitive), `absent` (tag has no value).",
                 )
+            if flags == "absent":
+                if pattern:
+                    raise RuleSyntaxError(
+                        **err,
+                        index=idx,
+                        feedback="The `absent` flag matches tags without a value, so the pattern must be empty.",
+                    )
+                absent = True
+                flags = ""
             for i, flag in enumerate(flags):
                 if flag == "i":
                     case_insensitive = True
@@ -307,7 +370,7 @@ class MetadataMatcher:
                 raise RuleSyntaxError(
                     **err,
                     index=idx + i,
-                    feedback="Unrecognized flag: Please specify one of the supported flags: `i` (case insensitive).",
+                    feedback="Unrecognized flag: Please specify one of the supported flags: `i` (case insensitive), `absent` (tag has no value).",
                 )
             idx += fwd
 
@@ -318,9 +381,20 @@ class MetadataMatcher:
                 feedback="Extra input found after end of matcher. Perhaps you meant to escape this colon?",
             )
 
+        numeric = parse_numeric_comparison(pattern, tags)
+        if "duration" in tags and numeric is None and not absent:
+            raise InvalidRuleError(
+                f"The duration tag only supports numeric comparisons (e.g. >300), got {pattern}"
+            )
+
         matcher = MetadataMatcher(
             tags=tags,
-            pattern=MatcherPattern(pattern=pattern, case_insensitive=case_insensitive),
+            pattern=MatcherPattern(
+                pattern=pattern,
+                case_insensitive=case_insensitive,
+                numeric=numeric,
+                absent=absent,
+            ),
         )
         logger.debug(f"Parsed rule matcher {raw=} as {matcher=}")
         return matcher
@@ -608,7 +682,11 @@ class MetadataAction:
         action = MetadataAction(
             behavior=behavior,
             tags=tags,
-            pattern=MatcherPattern(pattern=pattern, case_insensitive=case_insensitive)
+            pattern=MatcherPattern(
+                pattern=pattern,
+                case_insensitive=case_insensitive,
+                numeric=parse_numeric_comparison(pattern, tags),
+            )
             if pattern
             else None,
         )
@@ -11,6 +11,7 @@ from rose.rule_parser import (
     MetadataAction,
     MetadataMatcher,
     MetadataRule,
+    NumericComparison,
     ReplaceAction,
     RuleSyntaxError,
     SedAction,
@@ -77,7 +78,7 @@ Failed to parse matcher, invalid syntax:
 
     tracknumber^Track$
     ^
-    Invalid tag: must be one of {tracktitle, trackartist, trackartist[main], trackartist[guest], trackartist[remixer], trackartist[producer], trackartist[composer], trackartist[djmixer], tracknumber, tracktotal, discnumber, disctotal, albumtitle, albumartist, albumartist[main], albumartist[guest], albumartist[remixer], albumartist[producer], albumartist[composer], albumartist[djmixer], releasetype, year, genre, label, artist}. The next character after a tag must be ':' or ','.
+    Invalid tag: must be one of {tracktitle, trackartist, trackartist[main], trackartist[guest], trackartist[remixer], trackartist[producer], trackartist[composer], trackartist[djmixer], tracknumber, tracktotal, discnumber, disctotal, duration, albumtitle, albumartist, albumartist[main], albumartist[guest], albumartist[remixer], albumartist[producer], albumartist[composer], albumartist[djmixer], releasetype, year, genre, label, artist}. The next character after a tag must be ':' or ','.
 """,
     )
 
@@ -99,7 +100,7 @@ Failed to parse matcher, invalid syntax:
 
     tracktitle:Tr:ck
                   ^
-                  Unrecognized flag: Please specify one of the supported flags: `i` (case insensitive).
+                  Unrecognized flag: Please specify one of the supported flags: `i` (case insensitive), `absent` (tag has no value).
 """,
     )
 
@@ -110,7 +111,18 @@ Failed to parse matcher, invalid syntax:
 
     tracktitle::
                 ^
-                No flags specified: Please remove this section (by deleting the colon) or specify one of the supported flags: `i` (case insensitive).
+                No flags specified: Please remove this section (by deleting the colon) or specify one of the supported flags: `i` (case insensitive), `absent` (tag has no value).
+""",
+    )
+
+    test_err(
+        "genre:Pop:absent",
+        """\
+Failed to parse matcher, invalid syntax:
+
+    genre:Pop:absent
+              ^
+              The `absent` flag matches tags without a value, so the pattern must be empty.
 """,
     )
 
@@ -126,6 +138,43 @@ Failed to parse matcher, invalid syntax:
     )
 
 
+def test_rule_parse_matcher_absent() -> None:
+    assert MetadataMatcher.parse("genre::absent") == MetadataMatcher(
+        tags=["genre"],
+        pattern=MatcherPattern("", absent=True),
+    )
+    assert str(MetadataMatcher.parse("genre::absent")) == "genre::absent"
+
+
+def test_rule_parse_matcher_numeric_comparison() -> None:
+    assert MetadataMatcher.parse("year:>=2020") == MetadataMatcher(
+        tags=["year"],
+        pattern=MatcherPattern(">=2020", numeric=NumericComparison(">=", 2020)),
+    )
+    assert MetadataMatcher.parse("duration:>300") == MetadataMatcher(
+        tags=["duration"],
+        pattern=MatcherPattern(">300", numeric=NumericComparison(">", 300)),
+    )
+    assert MetadataMatcher.parse("tracknumber,discnumber:1-3") == MetadataMatcher(
+        tags=["tracknumber", "discnumber"],
+        pattern=MatcherPattern("1-3", numeric=NumericComparison("-", 1, 3)),
+    )
+    # Ranges on non-numeric tags are substring matches.
+    assert MetadataMatcher.parse("tracktitle:1-3") == MetadataMatcher(
+        tags=["tracktitle"],
+        pattern=MatcherPattern("1-3"),
+    )
+    assert str(MetadataMatcher.parse("year:<2000")) == "year:<2000"
+
+    with pytest.raises(InvalidRuleError) as e:
+        MetadataMatcher.parse("tracktitle,year:>2020")
+    assert (
+        str(e.value) == "Numeric comparison >2020 is not supported on non-numeric tags tracktitle"
+    )
+    with pytest.raises(InvalidRuleError):
+        MetadataMatcher.parse("duration:300")
+
+
 def test_rule_parse_action() -> None:
     assert MetadataAction.parse(
         "replace:lalala",
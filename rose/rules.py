@@ -17,6 +17,7 @@ import logging
 import re
 import shlex
 import time
+from collections.abc import Sequence
 from dataclasses import dataclass
 from pathlib import Path
 
@@ -31,6 +32,7 @@ from rose.cache import (
     list_tracks,
     update_cache_for_releases,
 )
+from rose.collages import CollageDoesNotExistError
 from rose.common import Artist, RoseError, RoseExpectedError, uniq
 from rose.config import Config
 from rose.rule_parser import (
@@ -41,9 +43,11 @@ from rose.rule_parser import (
     MetadataAction,
     MetadataMatcher,
     MetadataRule,
+    NumericComparison,
     ReplaceAction,
     SedAction,
     SplitAction,
+    Tag,
 )
 
 logger = logging.getLogger(__name__)
@@ -57,15 +61,51 @@ class InvalidReplacementValueError(RoseExpectedError):
     pass
 
 
+@dataclass(frozen=True)
+class CollageScope:
+    """Restrict a rule to the releases in a collage."""
+
+    collage_name: str
+
+
+@dataclass(frozen=True)
+class ReleasesScope:
+    """Restrict a rule to the tracks of specific releases."""
+
+    release_ids: tuple[str, ...]
+
+
+# A scope limits the candidate tracks of a rule to a subset of the library. `None` means the whole
+# library.
+RuleScope = CollageScope | ReleasesScope
+
+
 def execute_stored_metadata_rules(
     c: Config,
     *,
     dry_run: bool = False,
     confirm_yes: bool = False,
-) -> None:
+) -> int:
+    """
+    Execute the stored metadata rules in order and print a combined report of the tracks modified
+    by each rule. Each rule's changes are written to the cache before the next rule runs, so later
+    rules match against the values written by earlier rules. Returns the total number of files
+    modified, or that would be modified on a dry run.
+    """
+    report: list[tuple[MetadataRule, int]] = []
     for rule in c.stored_metadata_rules:
         click.secho(f"Executing stored metadata rule {rule}", dim=True)
-        execute_metadata_rule(c, rule, dry_run=dry_run, confirm_yes=confirm_yes)
+        n = execute_metadata_rule(c, rule, dry_run=dry_run, confirm_yes=confirm_yes)
+        report.append((rule, n))
+
+    total = sum(n for _, n in report)
+    if report:
+        verb = "would modify" if dry_run else "modified"
+        click.secho(f"Stored metadata rules {verb} {total} tracks:", bold=True)
+        for rule, n in report:
+            click.echo(f"    {n} tracks: {rule}")
+        click.echo()
+    return total
 
 
 def execute_metadata_rule(
@@ -75,7 +115,8 @@ def execute_metadata_rule(
     dry_run: bool = False,
     confirm_yes: bool = False,
     enter_number_to_confirm_above_count: int = 25,
-) -> None:
+    scope: RuleScope | None = None,
+) -> int:
     """
     This function executes a metadata update rule. It runs in five parts:
 
@@ -87,20 +128,23 @@ def execute_metadata_rule(
        in-memory. No changes are written to disk.
     4. We then prompt the user to confirm the changes, assuming confirm_yes is True.
     5. We then flush the intended changes to disk.
+
+    If a scope is passed, only tracks in the releases of the scope are candidates for matching.
+    Returns the number of files that were modified, or that would be modified on a dry run.
     """
     # Newline for appearance.
     click.echo()
-    fast_search_results = fast_search_for_matching_tracks(c, rule.matcher)
+    fast_search_results = fast_search_for_matching_tracks(c, rule.matcher, scope=scope)
     if not fast_search_results:
         click.secho("No matching tracks found", dim=True, italic=True)
         click.echo()
-        return
+        return 0
     # If there are more than 400 tracks matched, first filter the matched tracks using the cache,
     # has a sublinear time complexity (but higher baseline). Only then run the tag filter, which has
     # linear time complexity.
     if len(fast_search_results) > 400:
         time_start = time.time()
-        tracks = list_tracks(c, [t.id for t in fast_search_results])
+        tracks = list_tracks(c, [t.id for t in fast_search_results], include_hidden=True)
         logger.debug(
             f"Fetched tracks from cache for filtering in {time.time() - time_start} seconds"
         )
@@ -110,14 +154,16 @@ def execute_metadata_rule(
     if not fast_search_results:
         click.secho("No matching tracks found", dim=True, italic=True)
         click.echo()
-        return
+        return 0
 
-    matcher_audiotags = filter_track_false_positives_using_tags(rule.matcher, fast_search_results)
+    matcher_audiotags = filter_track_false_positives_using_tags(
+        c, rule.matcher, fast_search_results
+    )
     if not matcher_audiotags:
         click.secho("No matching tracks found", dim=True, italic=True)
         click.echo()
-        return
-    execute_metadata_actions(
+        return 0
+    return execute_metadata_actions(
         c,
         rule.actions,
         matcher_audiotags,
@@ -127,6 +173,28 @@ def execute_metadata_rule(
     )
 
 
+def find_releases_matching_rule(c: Config, matcher: MetadataMatcher) -> list[CachedRelease]:
+    """
+    Return the releases that a rule's matcher selects, without modifying anything. This runs the
+    same Full Text Search candidate query as the rules engine and then removes the false positives
+    using the read cache. Hidden releases are included, since rules also apply to them.
+    """
+    release_ids = [x.id for x in fast_search_for_matching_releases(c, matcher)]
+    if not release_ids:
+        return []
+    releases = list_releases(c, release_ids, include_hidden=True)
+    return filter_release_false_positives_using_read_cache(matcher, releases)
+
+
+def find_tracks_matching_rule(c: Config, matcher: MetadataMatcher) -> list[CachedTrack]:
+    """Basically the same thing as find_releases_matching_rule but with tracks."""
+    track_ids = [x.id for x in fast_search_for_matching_tracks(c, matcher)]
+    if not track_ids:
+        return []
+    tracks = list_tracks(c, track_ids, include_hidden=True)
+    return filter_track_false_positives_using_read_cache(matcher, tracks)
+
+
 TAG_ROLE_REGEX = re.compile(r"\[[^\]]+\]$")
 
 
@@ -139,6 +207,8 @@ class FastSearchResult:
 def fast_search_for_matching_tracks(
     c: Config,
     matcher: MetadataMatcher,
+    *,
+    scope: RuleScope | None = None,
 ) -> list[FastSearchResult]:
     """
     Run a search for tracks with the matcher on the Full Text Search index. This is _fast_, but will
@@ -146,6 +216,29 @@ def fast_search_for_matching_tracks(
     results.
     """
     time_start = time.time()
+    scopesql, scopeargs = _convert_scope_to_sql(c, scope)
+    # Absent tags cannot be searched for with Full Text Search, so every track is a candidate.
+    if matcher.pattern.absent:
+        query = f"""
+            SELECT DISTINCT t.id, t.source_path
+            FROM tracks t
+            WHERE 1=1 {scopesql}
+            ORDER BY t.source_path
+        """
+        return _execute_fast_search(c, query, scopeargs, "tracks", time_start)
+    # Numeric comparisons cannot be expressed as a Full Text Search query, so they are evaluated
+    # against the numeric columns of the read cache instead.
+    if matcher.pattern.numeric is not None:
+        wheresql, whereargs = _convert_numeric_matcher_to_sql(matcher)
+        query = f"""
+            SELECT DISTINCT t.id, t.source_path
+            FROM tracks t
+            JOIN releases r ON r.id = t.release_id
+            WHERE ({wheresql}) {scopesql}
+            ORDER BY t.source_path
+        """
+        return _execute_fast_search(c, query, [*whereargs, *scopeargs], "tracks", time_start)
+
     matchsql = _convert_matcher_to_fts_query(matcher.pattern)
     logger.debug(f"Converted match {matcher=} to {matchsql=}")
 
@@ -163,28 +256,97 @@ def fast_search_for_matching_tracks(
         SELECT DISTINCT t.id, t.source_path
         FROM rules_engine_fts
         JOIN tracks t ON rules_engine_fts.rowid = t.rowid
-        WHERE rules_engine_fts MATCH '{ftsquery}'
+        WHERE rules_engine_fts MATCH '{ftsquery}' {scopesql}
         ORDER BY t.source_path
     """
-    logger.debug(f"Constructed matching query {query}")
     # And then execute the SQL query. Note that we don't pull the tag values here. This query is
     # only used to identify the matching tracks. Afterwards, we will read each track's tags from
     # disk and apply the action on those tag values.
+    return _execute_fast_search(c, query, scopeargs, "tracks", time_start)
+
+
+def _resolve_scope_release_ids(c: Config, scope: RuleScope) -> list[str]:
+    """
+    Resolve a rule scope into release IDs. Collages resolve to their non-missing releases. Raises
+    `CollageDoesNotExistError` if the scope's collage does not exist.
+    """
+    if isinstance(scope, ReleasesScope):
+        return list(scope.release_ids)
+    with connect(c) as conn:
+        cursor = conn.execute(
+            "SELECT EXISTS(SELECT * FROM collages WHERE name = ?)",
+            (scope.collage_name,),
+        )
+        if not cursor.fetchone()[0]:
+            raise CollageDoesNotExistError(f"Collage {scope.collage_name} does not exist")
+        cursor = conn.execute(
+            """
+            SELECT release_id FROM collages_releases
+            WHERE collage_name = ? AND NOT missing
+            """,
+            (scope.collage_name,),
+        )
+        return [row["release_id"] for row in cursor]
+
+
+def _convert_scope_to_sql(c: Config, scope: RuleScope | None) -> tuple[str, list[str]]:
+    """Convert a rule scope into an `AND ...` clause on the track's release ID."""
+    if scope is None:
+        return "", []
+    release_ids = _resolve_scope_release_ids(c, scope)
+    return f"AND t.release_id IN ({','.join(['?'] * len(release_ids))})", release_ids
+
+
+def _execute_fast_search(
+    c: Config,
+    query: str,
+    args: Sequence[int | str],
+    kind: str,
+    time_start: float,
+) -> list[FastSearchResult]:
+    logger.debug(f"Constructed matching query {query}")
     results: list[FastSearchResult] = []
     with connect(c) as conn:
-        for row in conn.execute(query):
-            results.append(
-                FastSearchResult(
-                    id=row["id"],
-                    path=Path(row["source_path"]).resolve(),
-                )
-            )
+        for row in conn.execute(query, args):
+            results.append(FastSearchResult(id=row["id"], path=Path(row["source_path"]).resolve()))
     logger.debug(
-        f"Matched {len(results)} tracks from the read cache in {time.time() - time_start} seconds"
+        f"Matched {len(results)} {kind} from the read cache in {time.time() - time_start} seconds"
     )
     return results
 
 
+# The read cache columns of the numeric tags. Numeric comparisons are evaluated with SQL against
+# these columns instead of against the Full Text Search index.
+NUMERIC_TAG_COLUMNS: dict[Tag, str] = {
+    "tracknumber": "t.tracknumber",
+    "tracktotal": "t.tracktotal",
+    "discnumber": "t.discnumber",
+    "disctotal": "t.disctotal",
+    "duration": "t.duration_seconds",
+    "year": "r.year",
+}
+
+
+def _convert_numeric_matcher_to_sql(matcher: MetadataMatcher) -> tuple[str, list[int]]:
+    # Tracknumber and discnumber are stored as text, so cast every column to an integer. SQLite's
+    # CAST is more lenient than Python's int(), so this may produce false positives (e.g. `1/2`
+    # casts to 1), which are filtered out afterwards like the Full Text Search false positives.
+    cmp = matcher.pattern.numeric
+    assert cmp is not None
+    clauses: list[str] = []
+    args: list[int] = []
+    for tag in uniq(matcher.tags):
+        column = f"CAST({NUMERIC_TAG_COLUMNS[tag]} AS INTEGER)"
+        if cmp.operator == "-":
+            assert cmp.upper is not None
+            clauses.append(f"{column} BETWEEN ? AND ?")
+            args.extend([cmp.value, cmp.upper])
+        else:
+            clauses.append(f"{column} {cmp.operator} ?")
+            args.append(cmp.value)
+    return " OR ".join(clauses), args
+
+
 def _convert_matcher_to_fts_query(pattern: MatcherPattern) -> str:
     # Convert the matcher to a SQL expression for SQLite FTS. We won't be doing the precise
     # prefix/suffix matching here: for performance, we abuse SQLite FTS by making every character
@@ -210,13 +372,20 @@ def _convert_matcher_to_fts_query(pattern: MatcherPattern) -> str:
 
 
 def filter_track_false_positives_using_tags(
+    c: Config,
     matcher: MetadataMatcher,
     fast_search_results: list[FastSearchResult],
 ) -> list[AudioTags]:
     time_start = time.time()
     rval = []
     for fsr in fast_search_results:
-        tags = AudioTags.from_file(fsr.path)
+        tags = AudioTags.from_file(
+            fsr.path, combined_artist_tag_fallback=c.combined_artist_tag_fallback
+        )
+        # Match the track artists the same way the cache stores them: fall back to the album
+        # artists when the track has no artist tags of its own.
+        trackartists = tags.trackartists if tags.trackartists.all else tags.albumartists
+        matches: list[bool] = []
         for field in matcher.tags:
             match = False
             # fmt: off
@@ -226,26 +395,27 @@ def filter_track_false_positives_using_tags(
             match = match or (field == "tracktotal" and matches_pattern(matcher.pattern, tags.tracktotal))  
             match = match or (field == "discnumber" and matches_pattern(matcher.pattern, tags.discnumber))  
             match = match or (field == "disctotal" and matches_pattern(matcher.pattern, tags.disctotal))  
+            match = match or (field == "duration" and matches_pattern(matcher.pattern, tags.duration_sec))  
             match = match or (field == "albumtitle" and matches_pattern(matcher.pattern, tags.album))  
             match = match or (field == "releasetype" and matches_pattern(matcher.pattern, tags.releasetype))  
-            match = match or (field == "genre" and any(matches_pattern(matcher.pattern, x) for x in tags.genre))  
-            match = match or (field == "label" and any(matches_pattern(matcher.pattern, x) for x in tags.label))  
-            match = match or (field == "trackartist[main]" and any(matches_pattern(matcher.pattern, x.name) for x in tags.albumartists.main))  
-            match = match or (field == "trackartist[guest]" and any(matches_pattern(matcher.pattern, x.name) for x in tags.albumartists.guest))  
-            match = match or (field == "trackartist[remixer]" and any(matches_pattern(matcher.pattern, x.name) for x in tags.albumartists.remixer))  
-            match = match or (field == "trackartist[producer]" and any(matches_pattern(matcher.pattern, x.name) for x in tags.albumartists.producer))  
-            match = match or (field == "trackartist[composer]" and any(matches_pattern(matcher.pattern, x.name) for x in tags.albumartists.composer))  
-            match = match or (field == "trackartist[djmixer]" and any(matches_pattern(matcher.pattern, x.name) for x in tags.albumartists.djmixer))  
-            match = match or (field == "albumartist[main]" and any(matches_pattern(matcher.pattern, x.name) for x in tags.albumartists.main))  
-            match = match or (field == "albumartist[guest]" and any(matches_pattern(matcher.pattern, x.name) for x in tags.albumartists.guest))  
-            match = match or (field == "albumartist[remixer]" and any(matches_pattern(matcher.pattern, x.name) for x in tags.albumartists.remixer))  
-            match = match or (field == "albumartist[producer]" and any(matches_pattern(matcher.pattern, x.name) for x in tags.albumartists.producer))  
-            match = match or (field == "albumartist[composer]" and any(matches_pattern(matcher.pattern, x.name) for x in tags.albumartists.composer))  
-            match = match or (field == "albumartist[djmixer]" and any(matches_pattern(matcher.pattern, x.name) for x in tags.albumartists.djmixer))  
+            match = match or (field == "genre" and matches_any(matcher.pattern, tags.genre))  
+            match = match or (field == "label" and matches_any(matcher.pattern, tags.label))  
+            match = match or (field == "trackartist[main]" and matches_any(matcher.pattern, [x.name for x in trackartists.main]))  
+            match = match or (field == "trackartist[guest]" and matches_any(matcher.pattern, [x.name for x in trackartists.guest]))  
+            match = match or (field == "trackartist[remixer]" and matches_any(matcher.pattern, [x.name for x in trackartists.remixer]))  
+            match = match or (field == "trackartist[producer]" and matches_any(matcher.pattern, [x.name for x in trackartists.producer]))  
+            match = match or (field == "trackartist[composer]" and matches_any(matcher.pattern, [x.name for x in trackartists.composer]))  
+            match = match or (field == "trackartist[djmixer]" and matches_any(matcher.pattern, [x.name for x in trackartists.djmixer]))  
+            match = match or (field == "albumartist[main]" and matches_any(matcher.pattern, [x.name for x in tags.albumartists.main]))  
+            match = match or (field == "albumartist[guest]" and matches_any(matcher.pattern, [x.name for x in tags.albumartists.guest]))  
+            match = match or (field == "albumartist[remixer]" and matches_any(matcher.pattern, [x.name for x in tags.albumartists.remixer]))  
+            match = match or (field == "albumartist[producer]" and matches_any(matcher.pattern, [x.name for x in tags.albumartists.producer]))  
+            match = match or (field == "albumartist[composer]" and matches_any(matcher.pattern, [x.name for x in tags.albumartists.composer]))  
+            match = match or (field == "albumartist[djmixer]" and matches_any(matcher.pattern, [x.name for x in tags.albumartists.djmixer]))  
             # fmt: on
-            if match:
-                rval.append(tags)
-                break
+            matches.append(match)
+        if _matches_tags(matcher, matches):
+            rval.append(tags)
     logger.debug(
         f"Filtered {len(fast_search_results)} tracks down to {len(rval)} tracks in {time.time() - time_start} seconds"
     )
@@ -263,10 +433,11 @@ def execute_metadata_actions(
     dry_run: bool = False,
     confirm_yes: bool = False,
     enter_number_to_confirm_above_count: int = 25,
-) -> None:
+) -> int:
     """
     This function executes steps 3-5 of the rule executor. See that function's docstring. This is
-    split out to enable running actions on known releases/tracks.
+    split out to enable running actions on known releases/tracks. Returns the number of files
+    that were modified, or that would be modified on a dry run.
     """
     # === Step 3: Prepare updates on in-memory tags ===
 
@@ -363,8 +534,33 @@ def execute_metadata_actions(
     if not actionable_audiotags:
         click.secho("No matching tracks found", dim=True, italic=True)
         click.echo()
-        return
+        return 0
+
+    return _apply_tag_changes(
+        c,
+        actionable_audiotags,
+        f"actions {' '.join([shlex.quote(str(a)) for a in actions])}",
+        dry_run=dry_run,
+        confirm_yes=confirm_yes,
+        enter_number_to_confirm_above_count=enter_number_to_confirm_above_count,
+    )
 
+
+def _apply_tag_changes(
+    c: Config,
+    actionable_audiotags: list[tuple[AudioTags, list[Changes]]],
+    description: str,
+    *,
+    dry_run: bool,
+    confirm_yes: bool,
+    enter_number_to_confirm_above_count: int,
+) -> int:
+    """
+    Display the planned tag changes, confirm them with the user, flush them to disk, and update
+    the cache. The description is logged to identify the source of the changes. Returns the number
+    of files written, or the number of planned changes on a dry run. Returns zero if the user
+    aborts.
+    """
     # === Step 4: Display changes and ask for user confirmation ===
 
     # Compute the text to display:
@@ -393,7 +589,7 @@ def execute_metadata_actions(
             f"This is a dry run, aborting. {len(actionable_audiotags)} tracks would have been modified.",
             dim=True,
         )
-        return
+        return len(actionable_audiotags)
 
     # And then let's go for the confirmation.
     if confirm_yes:
@@ -405,7 +601,7 @@ def execute_metadata_actions(
                 )
                 if userconfirmation == "no":
                     logger.debug("Aborting planned tag changes after user confirmation")
-                    return
+                    return 0
                 if userconfirmation == str(len(actionable_audiotags)):
                     click.echo()
                     break
@@ -416,14 +612,12 @@ def execute_metadata_actions(
                 prompt_suffix="",
             ):
                 logger.debug("Aborting planned tag changes after user confirmation")
-                return
+                return 0
             click.echo()
 
     # === Step 5: Flush writes to disk ===
 
-    logger.info(
-        f"Writing tag changes for actions {' '.join([shlex.quote(str(a)) for a in actions])}"
-    )
+    logger.info(f"Writing tag changes for {description}")
     changed_release_ids: set[str] = set()
     for tags, changes in actionable_audiotags:
         if tags.release_id:
@@ -441,11 +635,83 @@ def execute_metadata_actions(
     # == Step 6: Trigger cache update ===
 
     click.echo()
-    source_paths = [r.source_path for r in list_releases(c, list(changed_release_ids))]
+    source_paths = [
+        r.source_path for r in list_releases(c, list(changed_release_ids), include_hidden=True)
+    ]
     update_cache_for_releases(c, source_paths)
+    return len(actionable_audiotags)
+
+
+# Matches a trailing featured artists credit in a track title, e.g. `Song (feat. B)`, `Song [ft.
+# B & C]`, or `Song feat. B`.
+FEATURED_IN_TITLE_REGEX = re.compile(
+    r"\s+[\(\[]?(?:feat\.|ft\.|featuring)\s+([^\)\]]+?)[\)\]]?\s*$",
+    flags=re.IGNORECASE,
+)
+FEATURED_ARTIST_SPLITTER_REGEX = re.compile(r"\s*,\s*|\s+&\s+")
+
+
+def strip_featured_artists_from_titles(
+    c: Config,
+    *,
+    scope: RuleScope | None = None,
+    dry_run: bool = False,
+    confirm_yes: bool = False,
+    enter_number_to_confirm_above_count: int = 25,
+) -> None:
+    """
+    Remove featured artist credits (e.g. `(feat. B)`) from track titles and ensure that the credited
+    artists are tagged as guest artists of the track. If a scope is passed, only the tracks in the
+    releases of the scope are normalized.
+    """
+    click.echo()
+    tracks = list_tracks(c, include_hidden=True)
+    if scope is not None:
+        release_ids = set(_resolve_scope_release_ids(c, scope))
+        tracks = [t for t in tracks if t.release.id in release_ids]
+    # Filter with the read cache first so that we only read the tags of candidate tracks from disk.
+    candidates = [t for t in tracks if FEATURED_IN_TITLE_REGEX.search(t.tracktitle)]
+
+    actionable_audiotags: list[tuple[AudioTags, list[Changes]]] = []
+    for t in candidates:
+        tags = AudioTags.from_file(
+            t.source_path, combined_artist_tag_fallback=c.combined_artist_tag_fallback
+        )
+        origtitle = tags.title or ""
+        m = FEATURED_IN_TITLE_REGEX.search(origtitle)
+        if not m:
+            continue
+        origguests = [x.name for x in tags.trackartists.guest]
+        tags.title = origtitle[: m.start()]
+        for name in FEATURED_ARTIST_SPLITTER_REGEX.split(m[1].strip()):
+            if name and name not in [x.name for x in tags.trackartists.guest]:
+                tags.trackartists.guest.append(Artist(name))
+        changes: list[Changes] = [("title", origtitle, tags.title)]
+        newguests = [x.name for x in tags.trackartists.guest]
+        if newguests != origguests:
+            changes.append(("trackartist[guest]", origguests, newguests))
+        actionable_audiotags.append((tags, changes))
+    if not actionable_audiotags:
+        click.secho("No tracks with featured artists in their titles found", dim=True, italic=True)
+        click.echo()
+        return
+
+    _apply_tag_changes(
+        c,
+        actionable_audiotags,
+        "featured artist normalization",
+        dry_run=dry_run,
+        confirm_yes=confirm_yes,
+        enter_number_to_confirm_above_count=enter_number_to_confirm_above_count,
+    )
 
 
 def matches_pattern(pattern: MatcherPattern, value: str | int | None) -> bool:
+    if pattern.absent:
+        return value is None or value == ""
+    if pattern.numeric is not None:
+        return matches_numeric_comparison(pattern.numeric, value)
+
     value = str(value) if value is not None else ""
 
     needle = pattern.pattern
@@ -465,6 +731,41 @@ def matches_pattern(pattern: MatcherPattern, value: str | int | None) -> bool:
     return needle in haystack
 
 
+def matches_any(pattern: MatcherPattern, values: list[str]) -> bool:
+    """Match a multi-value tag: it matches if any value matches, or if it is empty when absent."""
+    if pattern.absent:
+        return not values
+    return any(matches_pattern(pattern, x) for x in values)
+
+
+def _matches_tags(matcher: MetadataMatcher, matches: list[bool]) -> bool:
+    """
+    Combine the per-tag results of a matcher. A matcher on multiple tags (e.g. the expanded
+    `trackartist`) matches if any tag matches, except for `:absent`, which requires every tag to be
+    empty. Otherwise `trackartist:absent` would match every track without a remixer.
+    """
+    if matcher.pattern.absent:
+        return bool(matches) and all(matches)
+    return any(matches)
+
+
+def matches_numeric_comparison(cmp: NumericComparison, value: str | int | None) -> bool:
+    try:
+        num = int(value)  # type: ignore
+    except (TypeError, ValueError):
+        return False
+    if cmp.operator == ">":
+        return num > cmp.value
+    if cmp.operator == ">=":
+        return num >= cmp.value
+    if cmp.operator == "<":
+        return num < cmp.value
+    if cmp.operator == "<=":
+        return num <= cmp.value
+    assert cmp.upper is not None
+    return cmp.value <= num <= cmp.upper
+
+
 # Factor out the logic for executing an action on a single-value tag and a multi-value tag.
 def execute_single_action(action: MetadataAction, value: str | int | None) -> str | None:
     if action.pattern and not matches_pattern(action.pattern, value):
@@ -545,6 +846,25 @@ def fast_search_for_matching_releases(
                 f"Track tags are not allowed when matching against releases: {', '.join(track_tags)}"
             )
 
+    if matcher.pattern.absent:
+        query = """
+            SELECT DISTINCT r.id, r.source_path
+            FROM tracks t
+            JOIN releases r ON r.id = t.release_id
+            ORDER BY r.source_path
+        """
+        return _execute_fast_search(c, query, [], "releases", time_start)
+    if matcher.pattern.numeric is not None:
+        wheresql, whereargs = _convert_numeric_matcher_to_sql(matcher)
+        query = f"""
+            SELECT DISTINCT r.id, r.source_path
+            FROM tracks t
+            JOIN releases r ON r.id = t.release_id
+            WHERE {wheresql}
+            ORDER BY r.source_path
+        """
+        return _execute_fast_search(c, query, whereargs, "releases", time_start)
+
     matchsql = _convert_matcher_to_fts_query(matcher.pattern)
     logger.debug(f"Converted match {matcher=} to {matchsql=}")
     columns = uniq([TAG_ROLE_REGEX.sub("", t) for t in matcher.tags])
@@ -557,15 +877,7 @@ def fast_search_for_matching_releases(
         WHERE rules_engine_fts MATCH '{ftsquery}'
         ORDER BY r.source_path
     """
-    logger.debug(f"Constructed matching query {query}")
-    results: list[FastSearchResult] = []
-    with connect(c) as conn:
-        for row in conn.execute(query):
-            results.append(FastSearchResult(id=row["id"], path=Path(row["source_path"]).resolve()))
-    logger.debug(
-        f"Matched {len(results)} releases from the read cache in {time.time() - time_start} seconds"
-    )
-    return results
+    return _execute_fast_search(c, query, [], "releases", time_start)
 
 
 def filter_track_false_positives_using_read_cache(
@@ -575,6 +887,7 @@ def filter_track_false_positives_using_read_cache(
     time_start = time.time()
     rval = []
     for t in tracks:
+        matches: list[bool] = []
         for field in matcher.tags:
             match = False
             # fmt: off
@@ -584,26 +897,27 @@ def filter_track_false_positives_using_read_cache(
             match = match or (field == "tracktotal" and matches_pattern(matcher.pattern, t.tracktotal))  
             match = match or (field == "discnumber" and matches_pattern(matcher.pattern, t.discnumber))  
             match = match or (field == "disctotal" and matches_pattern(matcher.pattern, t.disctotal))  
+            match = match or (field == "duration" and matches_pattern(matcher.pattern, t.duration_seconds))  
             match = match or (field == "albumtitle" and matches_pattern(matcher.pattern, t.release.albumtitle))  
             match = match or (field == "releasetype" and matches_pattern(matcher.pattern, t.release.releasetype))  
-            match = match or (field == "genre" and any(matches_pattern(matcher.pattern, x) for x in t.release.genres))  
-            match = match or (field == "label" and any(matches_pattern(matcher.pattern, x) for x in t.release.labels))  
-            match = match or (field == "trackartist[main]" and any(matches_pattern(matcher.pattern, x.name) for x in t.trackartists.main))  
-            match = match or (field == "trackartist[guest]" and any(matches_pattern(matcher.pattern, x.name) for x in t.trackartists.guest))  
-            match = match or (field == "trackartist[remixer]" and any(matches_pattern(matcher.pattern, x.name) for x in t.trackartists.remixer))  
-            match = match or (field == "trackartist[producer]" and any(matches_pattern(matcher.pattern, x.name) for x in t.trackartists.producer))  
-            match = match or (field == "trackartist[composer]" and any(matches_pattern(matcher.pattern, x.name) for x in t.trackartists.composer))  
-            match = match or (field == "trackartist[djmixer]" and any(matches_pattern(matcher.pattern, x.name) for x in t.trackartists.djmixer))  
-            match = match or (field == "albumartist[main]" and any(matches_pattern(matcher.pattern, x.name) for x in t.release.albumartists.main))  
-            match = match or (field == "albumartist[guest]" and any(matches_pattern(matcher.pattern, x.name) for x in t.release.albumartists.guest))  
-            match = match or (field == "albumartist[remixer]" and any(matches_pattern(matcher.pattern, x.name) for x in t.release.albumartists.remixer))  
-            match = match or (field == "albumartist[producer]" and any(matches_pattern(matcher.pattern, x.name) for x in t.release.albumartists.producer))  
-            match = match or (field == "albumartist[composer]" and any(matches_pattern(matcher.pattern, x.name) for x in t.release.albumartists.composer))  
-            match = match or (field == "albumartist[djmixer]" and any(matches_pattern(matcher.pattern, x.name) for x in t.release.albumartists.djmixer))  
+            match = match or (field == "genre" and matches_any(matcher.pattern, t.release.genres))  
+            match = match or (field == "label" and matches_any(matcher.pattern, t.release.labels))  
+            match = match or (field == "trackartist[main]" and matches_any(matcher.pattern, [x.name for x in t.trackartists.main]))  
+            match = match or (field == "trackartist[guest]" and matches_any(matcher.pattern, [x.name for x in t.trackartists.guest]))  
+            match = match or (field == "trackartist[remixer]" and matches_any(matcher.pattern, [x.name for x in t.trackartists.remixer]))  
+            match = match or (field == "trackartist[producer]" and matches_any(matcher.pattern, [x.name for x in t.trackartists.producer]))  
+            match = match or (field == "trackartist[composer]" and matches_any(matcher.pattern, [x.name for x in t.trackartists.composer]))  
+            match = match or (field == "trackartist[djmixer]" and matches_any(matcher.pattern, [x.name for x in t.trackartists.djmixer]))  
+            match = match or (field == "albumartist[main]" and matches_any(matcher.pattern, [x.name for x in t.release.albumartists.main]))  
+            match = match or (field == "albumartist[guest]" and matches_any(matcher.pattern, [x.name for x in t.release.albumartists.guest]))  
+            match = match or (field == "albumartist[remixer]" and matches_any(matcher.pattern, [x.name for x in t.release.albumartists.remixer]))  
+            match = match or (field == "albumartist[producer]" and matches_any(matcher.pattern, [x.name for x in t.release.albumartists.producer]))  
+            match = match or (field == "albumartist[composer]" and matches_any(matcher.pattern, [x.name for x in t.release.albumartists.composer]))  
+            match = match or (field == "albumartist[djmixer]" and matches_any(matcher.pattern, [x.name for x in t.release.albumartists.djmixer]))  
             # fmt: on
-            if match:
-                rval.append(t)
-                break
+            matches.append(match)
+        if _matches_tags(matcher, matches):
+            rval.append(t)
     logger.debug(
         f"Filtered {len(tracks)} tracks down to {len(rval)} tracks in {time.time() - time_start} seconds"
     )
@@ -617,25 +931,27 @@ def filter_release_false_positives_using_read_cache(
     time_start = time.time()
     rval = []
     for r in releases:
-        for field in matcher.tags:
+        matches: list[bool] = []
+        # Only attempt to match the release tags; ignore track tags.
+        for field in [t for t in matcher.tags if t in RELEASE_TAGS]:
             match = False
-            # Only attempt to match the release tags; ignore track tags.
             # fmt: off
             match = match or (field == "year" and matches_pattern(matcher.pattern, r.year))  
+            match = match or (field == "disctotal" and matches_pattern(matcher.pattern, r.disctotal))  
             match = match or (field == "albumtitle" and matches_pattern(matcher.pattern, r.albumtitle))  
             match = match or (field == "releasetype" and matches_pattern(matcher.pattern, r.releasetype))  
-            match = match or (field == "genre" and any(matches_pattern(matcher.pattern, x) for x in r.genres))  
-            match = match or (field == "label" and any(matches_pattern(matcher.pattern, x) for x in r.labels))  
-            match = match or (field == "albumartist[main]" and any(matches_pattern(matcher.pattern, x.name) for x in r.albumartists.main))  
-            match = match or (field == "albumartist[guest]" and any(matches_pattern(matcher.pattern, x.name) for x in r.albumartists.guest))  
-            match = match or (field == "albumartist[remixer]" and any(matches_pattern(matcher.pattern, x.name) for x in r.albumartists.remixer))  
-            match = match or (field == "albumartist[producer]" and any(matches_pattern(matcher.pattern, x.name) for x in r.albumartists.producer))  
-            match = match or (field == "albumartist[composer]" and any(matches_pattern(matcher.pattern, x.name) for x in r.albumartists.composer))  
-            match = match or (field == "albumartist[djmixer]" and any(matches_pattern(matcher.pattern, x.name) for x in r.albumartists.djmixer))  
+            match = match or (field == "genre" and matches_any(matcher.pattern, r.genres))  
+            match = match or (field == "label" and matches_any(matcher.pattern, r.labels))  
+            match = match or (field == "albumartist[main]" and matches_any(matcher.pattern, [x.name for x in r.albumartists.main]))  
+            match = match or (field == "albumartist[guest]" and matches_any(matcher.pattern, [x.name for x in r.albumartists.guest]))  
+            match = match or (field == "albumartist[remixer]" and matches_any(matcher.pattern, [x.name for x in r.albumartists.remixer]))  
+            match = match or (field == "albumartist[producer]" and matches_any(matcher.pattern, [x.name for x in r.albumartists.producer]))  
+            match = match or (field == "albumartist[composer]" and matches_any(matcher.pattern, [x.name for x in r.albumartists.composer]))  
+            match = match or (field == "albumartist[djmixer]" and matches_any(matcher.pattern, [x.name for x in r.albumartists.djmixer]))  
             # fmt: on
-            if match:
-                rval.append(r)
-                break
+            matches.append(match)
+        if _matches_tags(matcher, matches):
+            rval.append(r)
     logger.debug(
         f"Filtered {len(releases)} releases down to {len(rval)} releases in {time.time() - time_start} seconds"
     )
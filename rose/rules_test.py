@@ -7,15 +7,19 @@ import pytest
 
 from rose.audiotags import AudioTags
 from rose.cache import (
+    connect,
     list_releases,
     list_tracks,
     update_cache,
 )
-from rose.common import Artist
+from rose.collages import CollageDoesNotExistError
+from rose.common import Artist, ArtistMapping
 from rose.config import Config
 from rose.rule_parser import MetadataMatcher, MetadataRule
 from rose.rules import (
+    CollageScope,
     FastSearchResult,
+    ReleasesScope,
     TrackTagNotAllowedError,
     execute_metadata_rule,
     execute_stored_metadata_rules,
@@ -23,6 +27,10 @@ from rose.rules import (
     fast_search_for_matching_tracks,
     filter_release_false_positives_using_read_cache,
     filter_track_false_positives_using_read_cache,
+    filter_track_false_positives_using_tags,
+    find_releases_matching_rule,
+    find_tracks_matching_rule,
+    strip_featured_artists_from_titles,
 )
 
 
@@ -152,6 +160,41 @@ def test_rules_fields_match_genre(config: Config, source_dir: Path) -> None:
     assert af.genre == ["8", "Pop"]
 
 
+def test_rules_scoped_to_collage(config: Config, source_dir: Path) -> None:
+    rule = MetadataRule.parse("genre:K-Pop", ["replace:8"])
+    execute_metadata_rule(config, rule, confirm_yes=False, scope=CollageScope("Rose Gold"))
+    # Test Release 1 is not in the collage, so it is left untouched.
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    assert af.genre == ["K-Pop", "Pop"]
+    af = AudioTags.from_file(source_dir / "Test Release 3" / "01.m4a")
+    assert af.genre == ["8", "R&B"]
+
+
+def test_rules_scoped_to_releases(config: Config, source_dir: Path) -> None:
+    rule = MetadataRule.parse("tracktitle:rack", ["replace:lalala"])
+    execute_metadata_rule(config, rule, confirm_yes=False, scope=ReleasesScope(("ilovecarly",)))
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    assert af.title != "lalala"
+    af = AudioTags.from_file(source_dir / "Test Release 2" / "01.m4a")
+    assert af.title == "lalala"
+
+
+def test_strip_featured_artists_from_titles(config: Config, source_dir: Path) -> None:
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    af.title = "Song (feat. B)"
+    af.flush()
+    update_cache(config)
+
+    strip_featured_artists_from_titles(config)
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    assert af.title == "Song"
+    assert Artist("B") in af.trackartists.guest
+    # The cache is updated too.
+    tracks = list_tracks(config)
+    track = next(t for t in tracks if t.source_path.name == "01.m4a" and t.tracktitle == "Song")
+    assert Artist("B") in track.trackartists.guest
+
+
 def test_rules_fields_match_label(config: Config, source_dir: Path) -> None:
     rule = MetadataRule.parse("label:Cool", ["replace:8"])
     execute_metadata_rule(config, rule, confirm_yes=False)
@@ -336,6 +379,33 @@ def test_run_stored_rules(config: Config, source_dir: Path) -> None:
     assert af.title == "lalala"
 
 
+def test_run_stored_rules_chained(config: Config, source_dir: Path) -> None:
+    # The second rule matches the values written by the first rule.
+    config = dataclasses.replace(
+        config,
+        stored_metadata_rules=[
+            MetadataRule.parse("tracktitle:Track", ["replace:lalala"]),
+            MetadataRule.parse("tracktitle:lalala", ["replace:hahaha"]),
+        ],
+    )
+
+    assert execute_stored_metadata_rules(config) == 12
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    assert af.title == "hahaha"
+
+
+def test_run_stored_rules_dry_run(config: Config, source_dir: Path) -> None:
+    config = dataclasses.replace(
+        config,
+        stored_metadata_rules=[MetadataRule.parse("tracktitle:Track", ["replace:lalala"])],
+    )
+
+    # A dry run reports the planned changes without writing them.
+    assert execute_stored_metadata_rules(config, dry_run=True) == 6
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    assert af.title != "lalala"
+
+
 @pytest.mark.usefixtures("seeded_cache")
 def test_fast_search_for_matching_releases(config: Config) -> None:
     results = fast_search_for_matching_releases(
@@ -354,6 +424,106 @@ def test_fast_search_for_matching_releases_invalid_tag(config: Config) -> None:
     fast_search_for_matching_releases(config, MetadataMatcher.parse("artist:x"))
 
 
+@pytest.mark.usefixtures("seeded_cache")
+def test_fast_search_for_matching_releases_numeric_comparison(config: Config) -> None:
+    matcher = MetadataMatcher.parse("year:>2021")
+    results = fast_search_for_matching_releases(config, matcher)
+    assert results == [FastSearchResult(id="r1", path=config.music_source_dir / "r1")]
+    releases = filter_release_false_positives_using_read_cache(
+        matcher, list_releases(config, [r.id for r in results])
+    )
+    assert [r.id for r in releases] == ["r1"]
+
+    results = fast_search_for_matching_releases(config, MetadataMatcher.parse("year:2020-2021"))
+    assert [r.id for r in results] == ["r2", "r3"]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_fast_search_for_matching_releases_absent(config: Config) -> None:
+    matcher = MetadataMatcher.parse("genre::absent")
+    results = fast_search_for_matching_releases(config, matcher)
+    releases = filter_release_false_positives_using_read_cache(
+        matcher, list_releases(config, [r.id for r in results])
+    )
+    assert [r.id for r in releases] == ["r3"]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_fast_search_for_matching_tracks_numeric_comparison(config: Config) -> None:
+    with connect(config) as conn:
+        conn.execute("UPDATE tracks SET duration_seconds = 360 WHERE id = 't2'")
+    matcher = MetadataMatcher.parse("duration:>300")
+    results = fast_search_for_matching_tracks(config, matcher)
+    assert [t.id for t in results] == ["t2"]
+    tracks = filter_track_false_positives_using_read_cache(
+        matcher, list_tracks(config, [t.id for t in results])
+    )
+    assert [t.id for t in tracks] == ["t2"]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_fast_search_for_matching_tracks_numeric_comparison_scoped(config: Config) -> None:
+    """Test that the scope holds for numeric matchers with multiple tags."""
+    matcher = MetadataMatcher.parse("tracknumber,discnumber:>=1")
+    results = fast_search_for_matching_tracks(config, matcher, scope=ReleasesScope(("r2",)))
+    assert [t.id for t in results] == ["t3"]
+    results = fast_search_for_matching_tracks(config, matcher, scope=CollageScope("Rose Gold"))
+    assert [t.id for t in results] == ["t1", "t2", "t3"]
+    with pytest.raises(CollageDoesNotExistError):
+        fast_search_for_matching_tracks(config, matcher, scope=CollageScope("lalala"))
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_fast_search_for_matching_tracks_absent_expanded_tag(config: Config) -> None:
+    """Test that an absent matcher on an expanded tag requires every expanded tag to be empty."""
+    matcher = MetadataMatcher.parse("trackartist::absent")
+    results = fast_search_for_matching_tracks(config, matcher)
+    tracks = filter_track_false_positives_using_read_cache(
+        matcher, list_tracks(config, [t.id for t in results])
+    )
+    assert [t.id for t in tracks] == ["t4"]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_find_releases_matching_rule(config: Config) -> None:
+    releases = find_releases_matching_rule(config, MetadataMatcher.parse("albumartist:Techno Man"))
+    assert [r.id for r in releases] == ["r1"]
+    # The fast search returns candidates, but no artist name starts with "Man".
+    assert find_releases_matching_rule(config, MetadataMatcher.parse("albumartist:^Man")) == []
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_find_tracks_matching_rule(config: Config) -> None:
+    tracks = find_tracks_matching_rule(config, MetadataMatcher.parse("trackartist:Violin"))
+    assert [t.id for t in tracks] == ["t3"]
+    assert find_tracks_matching_rule(config, MetadataMatcher.parse("trackartist:^Man")) == []
+
+
+def test_rules_numeric_comparison(config: Config, source_dir: Path) -> None:
+    rule = MetadataRule.parse("year:>1990", ["albumtitle::replace:Recent"])
+    execute_metadata_rule(config, rule, confirm_yes=False)
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    assert af.album != "Recent"
+    rule = MetadataRule.parse("year:<=1990", ["albumtitle::replace:Old"])
+    execute_metadata_rule(config, rule, confirm_yes=False)
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    assert af.album == "Old"
+
+
+def test_rules_absent(config: Config, source_dir: Path) -> None:
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    af.genre = []
+    af.flush()
+    update_cache(config)
+
+    rule = MetadataRule.parse("genre::absent", ["genre::add:Unknown"])
+    execute_metadata_rule(config, rule, confirm_yes=False)
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "01.m4a")
+    assert af.genre == ["Unknown"]
+    af = AudioTags.from_file(source_dir / "Test Release 1" / "02.m4a")
+    assert "Unknown" not in af.genre
+
+
 @pytest.mark.usefixtures("seeded_cache")
 def test_filter_release_false_positives_with_read_cache(config: Config) -> None:
     matcher = MetadataMatcher.parse("albumartist:^Man")
@@ -374,3 +544,22 @@ def test_filter_track_false_positives_with_read_cache(config: Config) -> None:
     assert len(tracks) == 3
     filteredresults = filter_track_false_positives_using_read_cache(matcher, tracks)
     assert not filteredresults
+
+
+def test_filter_track_false_positives_using_tags_trackartists(
+    config: Config, source_dir: Path
+) -> None:
+    path = source_dir / "Test Release 1" / "01.m4a"
+    af = AudioTags.from_file(path)
+    af.trackartists = ArtistMapping(main=[Artist("Solo")])
+    af.flush()
+    fsresults = [FastSearchResult(id=af.id or "", path=path)]
+    matcher = MetadataMatcher.parse("trackartist[main]:Solo")
+    assert len(filter_track_false_positives_using_tags(config, matcher, fsresults)) == 1
+    matcher = MetadataMatcher.parse("trackartist[main]:BLACKPINK")
+    assert not filter_track_false_positives_using_tags(config, matcher, fsresults)
+
+    # Without track artist tags, the album artists stand in for them, as in the cache.
+    af.trackartists = ArtistMapping()
+    af.flush()
+    assert len(filter_track_false_positives_using_tags(config, matcher, fsresults)) == 1
@@ -0,0 +1,152 @@
+"""
+The snapshot module exports the library as a self-contained SQLite database for external tools.
+
+The read cache is not suitable for external consumption: its schema is an implementation detail,
+and the cache is deleted and rebuilt whenever its schema changes. The snapshot instead has its own
+versioned schema (`snapshot.sql`), which is populated through the cache's public accessors so that
+it is decoupled from the cache schema. See `docs/SNAPSHOTS.md` for the schema documentation.
+"""
+
+import contextlib
+import logging
+import sqlite3
+from datetime import datetime
+from pathlib import Path
+
+from rose.cache import (
+    get_collage,
+    get_playlist,
+    list_collages,
+    list_playlists,
+    list_releases,
+    list_tracks,
+)
+from rose.common import VERSION, ArtistMapping
+from rose.config import Config
+
+logger = logging.getLogger(__name__)
+
+SNAPSHOT_SCHEMA_PATH = Path(__file__).resolve().parent / "snapshot.sql"
+# Increment this whenever the snapshot schema changes.
+SNAPSHOT_SCHEMA_VERSION = 1
+
+
+def export_snapshot(c: Config, dest: Path) -> None:
+    """
+    Export the library to a SQLite database at `dest`. If a file already exists at `dest`, it is
+    replaced. The snapshot is written to a temporary file first, so that an interrupted export does
+    not leave a partial snapshot behind.
+    """
+    tmp = dest.with_name(dest.name + ".tmp")
+    tmp.unlink(missing_ok=True)
+    try:
+        with contextlib.closing(sqlite3.connect(tmp)) as conn:
+            with SNAPSHOT_SCHEMA_PATH.open("r") as fp:
+                conn.executescript(fp.read())
+            with conn:
+                _populate_snapshot(c, conn)
+        tmp.replace(dest)
+    finally:
+        tmp.unlink(missing_ok=True)
+    logger.info(f"Exported library snapshot to {dest}")
+
+
+def _populate_snapshot(c: Config, conn: sqlite3.Connection) -> None:
+    conn.execute(
+        "INSERT INTO snapshot (schema_version, rose_version, created_at) VALUES (?, ?, ?)",
+        (
+            SNAPSHOT_SCHEMA_VERSION,
+            VERSION,
+            datetime.now().astimezone().replace(microsecond=0).isoformat(),
+        ),
+    )
+
+    for release in list_releases(c, include_hidden=True):
+        conn.execute(
+            """
+            INSERT INTO releases
+            (id, source_path, cover_image_path, added_at, title, releasetype, year, disctotal, new)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            """,
+            (
+                release.id,
+                str(release.source_path),
+                str(release.cover_image_path) if release.cover_image_path else None,
+                release.added_at,
+                release.albumtitle,
+                release.releasetype,
+                release.year,
+                release.disctotal,
+                release.new,
+            ),
+        )
+        conn.executemany(
+            "INSERT INTO releases_genres (release_id, genre, position) VALUES (?, ?, ?)",
+            [(release.id, genre, pos) for pos, genre in enumerate(release.genres)],
+        )
+        conn.executemany(
+            "INSERT INTO releases_labels (release_id, label, position) VALUES (?, ?, ?)",
+            [(release.id, label, pos) for pos, label in enumerate(release.labels)],
+        )
+        conn.executemany(
+            "INSERT INTO releases_artists (release_id, artist, role, position) VALUES (?, ?, ?, ?)",
+            [(release.id, *x) for x in _flatten_artists(release.albumartists)],
+        )
+
+    for track in list_tracks(c, include_hidden=True):
+        conn.execute(
+            """
+            INSERT INTO tracks
+            ( id, source_path, release_id, title, tracknumber, tracktotal, discnumber, disctotal
+            , duration_seconds
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            """,
+            (
+                track.id,
+                str(track.source_path),
+                track.release.id,
+                track.tracktitle,
+                track.tracknumber,
+                track.tracktotal,
+                track.discnumber,
+                track.disctotal,
+                track.duration_seconds,
+            ),
+        )
+        conn.executemany(
+            "INSERT INTO tracks_artists (track_id, artist, role, position) VALUES (?, ?, ?, ?)",
+            [(track.id, *x) for x in _flatten_artists(track.trackartists)],
+        )
+
+    for name in list_collages(c):
+        cdata = get_collage(c, name)
+        if cdata is None:
+            continue
+        collage, _ = cdata
+        conn.execute("INSERT INTO collages (name) VALUES (?)", (collage.name,))
+        conn.executemany(
+            "INSERT INTO collages_releases (collage_name, release_id, position) VALUES (?, ?, ?)",
+            [(collage.name, rid, pos) for pos, rid in enumerate(collage.release_ids)],
+        )
+
+    for name in list_playlists(c):
+        pdata = get_playlist(c, name)
+        if pdata is None:
+            continue
+        playlist, _ = pdata
+        conn.execute("INSERT INTO playlists (name) VALUES (?)", (playlist.name,))
+        conn.executemany(
+            "INSERT INTO playlists_tracks (playlist_name, track_id, position) VALUES (?, ?, ?)",
+            [(playlist.name, tid, pos) for pos, tid in enumerate(playlist.track_ids)],
+        )
+
+
+def _flatten_artists(mapping: ArtistMapping) -> list[tuple[str, str, int]]:
+    """Flatten an artist mapping into (artist, role, position) tuples, excluding aliases."""
+    rval: list[tuple[str, str, int]] = []
+    for role, artists in mapping.items():
+        for art in artists:
+            if not art.alias:
+                rval.append((art.name, role, len(rval)))
+    return rval
@@ -0,0 +1,90 @@
+-- The schema of the library snapshot exported by `export_snapshot`. Unlike the cache schema, this
+-- schema is a stable interface for external tools. Any change to it must increment
+-- SNAPSHOT_SCHEMA_VERSION in snapshot.py and be documented in docs/SNAPSHOTS.md.
+
+CREATE TABLE snapshot (
+    schema_version INTEGER NOT NULL,
+    rose_version TEXT NOT NULL,
+    -- ISO8601 timestamp.
+    created_at TEXT NOT NULL
+);
+
+CREATE TABLE releases (
+    id TEXT PRIMARY KEY,
+    source_path TEXT NOT NULL,
+    cover_image_path TEXT,
+    -- ISO8601 timestamp.
+    added_at TEXT NOT NULL,
+    title TEXT NOT NULL,
+    releasetype TEXT NOT NULL,
+    year INTEGER,
+    disctotal INTEGER NOT NULL,
+    new BOOLEAN NOT NULL
+);
+
+CREATE TABLE releases_genres (
+    release_id TEXT NOT NULL REFERENCES releases(id),
+    genre TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (release_id, position)
+);
+
+CREATE TABLE releases_labels (
+    release_id TEXT NOT NULL REFERENCES releases(id),
+    label TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (release_id, position)
+);
+
+CREATE TABLE releases_artists (
+    release_id TEXT NOT NULL REFERENCES releases(id),
+    artist TEXT NOT NULL,
+    -- One of: main, guest, remixer, producer, composer, djmixer.
+    role TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (release_id, position)
+);
+
+CREATE TABLE tracks (
+    id TEXT PRIMARY KEY,
+    source_path TEXT NOT NULL,
+    release_id TEXT NOT NULL REFERENCES releases(id),
+    title TEXT NOT NULL,
+    tracknumber TEXT NOT NULL,
+    tracktotal INTEGER NOT NULL,
+    discnumber TEXT NOT NULL,
+    disctotal INTEGER NOT NULL,
+    duration_seconds INTEGER NOT NULL
+);
+CREATE INDEX tracks_release_id ON tracks(release_id);
+
+CREATE TABLE tracks_artists (
+    track_id TEXT NOT NULL REFERENCES tracks(id),
+    artist TEXT NOT NULL,
+    -- One of: main, guest, remixer, producer, composer, djmixer.
+    role TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (track_id, position)
+);
+
+CREATE TABLE collages (
+    name TEXT PRIMARY KEY
+);
+
+CREATE TABLE collages_releases (
+    collage_name TEXT NOT NULL REFERENCES collages(name),
+    release_id TEXT NOT NULL REFERENCES releases(id),
+    position INTEGER NOT NULL,
+    PRIMARY KEY (collage_name, position)
+);
+
+CREATE TABLE playlists (
+    name TEXT PRIMARY KEY
+);
+
+CREATE TABLE playlists_tracks (
+    playlist_name TEXT NOT NULL REFERENCES playlists(name),
+    track_id TEXT NOT NULL REFERENCES tracks(id),
+    position INTEGER NOT NULL,
+    PRIMARY KEY (playlist_name, position)
+);
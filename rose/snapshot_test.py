@@ -0,0 +1,48 @@
+import contextlib
+import sqlite3
+from pathlib import Path
+
+import pytest
+
+from rose.config import Config
+from rose.snapshot import SNAPSHOT_SCHEMA_VERSION, export_snapshot
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_export_snapshot(config: Config, isolated_dir: Path) -> None:
+    dest = isolated_dir / "snapshot.sqlite3"
+    export_snapshot(config, dest)
+
+    with contextlib.closing(sqlite3.connect(dest)) as conn:
+        cursor = conn.execute("SELECT schema_version FROM snapshot")
+        assert cursor.fetchone()[0] == SNAPSHOT_SCHEMA_VERSION
+        assert conn.execute("SELECT COUNT(*) FROM releases").fetchone()[0] == 3
+        assert conn.execute("SELECT COUNT(*) FROM tracks").fetchone()[0] == 4
+        cursor = conn.execute(
+            "SELECT artist, role FROM releases_artists WHERE release_id = 'r2' ORDER BY position"
+        )
+        assert cursor.fetchall() == [("Violin Woman", "main"), ("Conductor Woman", "guest")]
+        cursor = conn.execute(
+            """
+            SELECT release_id FROM collages_releases
+            WHERE collage_name = 'Rose Gold' ORDER BY position
+            """
+        )
+        assert cursor.fetchall() == [("r1",), ("r2",)]
+        cursor = conn.execute(
+            """
+            SELECT track_id FROM playlists_tracks
+            WHERE playlist_name = 'Lala Lisa' ORDER BY position
+            """
+        )
+        assert cursor.fetchall() == [("t1",), ("t3",)]
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_export_snapshot_replaces_existing(config: Config, isolated_dir: Path) -> None:
+    dest = isolated_dir / "snapshot.sqlite3"
+    dest.write_text("not a database")
+    export_snapshot(config, dest)
+    with contextlib.closing(sqlite3.connect(dest)) as conn:
+        assert conn.execute("SELECT COUNT(*) FROM releases").fetchone()[0] == 3
+    assert not (isolated_dir / "snapshot.sqlite3.tmp").exists()
@@ -9,14 +9,23 @@ from __future__ import annotations
 import dataclasses
 import re
 import typing
+from collections import defaultdict
+from collections.abc import Callable
 from copy import deepcopy
 from functools import cached_property
+from pathlib import Path
 from typing import Any
 
 import click
 import jinja2
 
-from rose.common import Artist, ArtistMapping, RoseExpectedError
+from rose.common import (
+    Artist,
+    ArtistMapping,
+    RoseExpectedError,
+    sanitize_dirname,
+    sanitize_filename,
+)
 
 if typing.TYPE_CHECKING:
     from rose.cache import CachedRelease, CachedTrack
@@ -57,27 +66,50 @@ def artistsarrayfmt(xs: list[Artist]) -> str:
     return arrayfmt([x.name for x in xs if not x.alias])
 
 
-def artistsfmt(a: ArtistMapping) -> str:
-    """Format a mapping of artists."""
+# The order in which `artistsfmt` renders the artist roles. Roles that are not in the order are not
+# rendered. The first role before `main` with artists is rendered as a prefix, and the roles after
+# `main` are rendered as suffixes.
+DEFAULT_ARTIST_ROLE_ORDER = ["djmixer", "composer", "main", "guest", "producer"]
+ARTIST_ROLE_PREFIXES = {
+    "djmixer": " pres. ",
+    "composer": " performed by ",
+}
+ARTIST_ROLE_SUFFIXES = {
+    "guest": "feat.",
+    "producer": "prod.",
+    "remixer": "remixed by",
+    "composer": "composed by",
+    "djmixer": "mixed by",
+}
+
 
+def artistsfmt(a: ArtistMapping, role_order: list[str] = DEFAULT_ARTIST_ROLE_ORDER) -> str:
+    """Format a mapping of artists."""
+    main_idx = role_order.index("main")
     r = artistsarrayfmt(a.main)
-    if a.djmixer:
-        r = artistsarrayfmt(a.djmixer) + " pres. " + r
-    elif a.composer:
-        r = artistsarrayfmt(a.composer) + " performed by " + r
-    if a.guest:
-        r += " (feat. " + artistsarrayfmt(a.guest) + ")"
-    if a.producer:
-        r += " (prod. " + artistsarrayfmt(a.producer) + ")"
+    # Only the first prefix role with artists is rendered; chaining prefixes reads poorly.
+    for role in role_order[:main_idx]:
+        if artists := getattr(a, role):
+            r = artistsarrayfmt(artists) + ARTIST_ROLE_PREFIXES[role] + r
+            break
+    for role in role_order[main_idx + 1 :]:
+        if artists := getattr(a, role):
+            r += f" ({ARTIST_ROLE_SUFFIXES[role]} {artistsarrayfmt(artists)})"
     if r == "":
         return "Unknown Artists"
     return r
 
 
+@jinja2.pass_context
+def _artistsfmt_filter(ctx: jinja2.runtime.Context, a: ArtistMapping) -> str:
+    # The role order is configured by the user, so we pass it through the template context.
+    return artistsfmt(a, ctx.get("artist_role_order") or DEFAULT_ARTIST_ROLE_ORDER)
+
+
 ENVIRONMENT = jinja2.Environment()
 ENVIRONMENT.filters["arrayfmt"] = arrayfmt
 ENVIRONMENT.filters["artistsarrayfmt"] = artistsarrayfmt
-ENVIRONMENT.filters["artistsfmt"] = artistsfmt
+ENVIRONMENT.filters["artistsfmt"] = _artistsfmt_filter
 ENVIRONMENT.filters["releasetypefmt"] = releasetypefmt
 
 
@@ -232,21 +264,148 @@ def eval_release_template(
     template: PathTemplate,
     release: CachedRelease,
     position: str | None = None,
+    artist_role_order: list[str] = DEFAULT_ARTIST_ROLE_ORDER,
 ) -> str:
-    return _collapse_spacing(template.compiled.render(**_calc_release_variables(release, position)))
+    return _collapse_spacing(
+        template.compiled.render(
+            **_calc_release_variables(release, position),
+            artist_role_order=artist_role_order,
+        )
+    )
 
 
 def eval_track_template(
     template: PathTemplate,
     track: CachedTrack,
     position: str | None = None,
+    artist_role_order: list[str] = DEFAULT_ARTIST_ROLE_ORDER,
 ) -> str:
     return (
-        _collapse_spacing(template.compiled.render(**_calc_track_variables(track, position)))
+        _collapse_spacing(
+            template.compiled.render(
+                **_calc_track_variables(track, position),
+                artist_role_order=artist_role_order,
+            )
+        )
         + track.source_path.suffix
     )
 
 
+@dataclasses.dataclass
+class VirtualTreeEntry:
+    # The relative virtual path of the track, of the form `{release}/{track}`.
+    virtual_path: Path
+    source_path: Path
+    release_id: str
+    track_id: str
+
+
+def virtual_tree(c: Config, template: PathTemplatePair) -> list[VirtualTreeEntry]:
+    """
+    Compute the virtual path of every track in the library under the given templates. This is the
+    same computation the virtual filesystem performs, exposed as data for external tools that index
+    files. Distinct releases and tracks may share a virtual path; see `resolve_collisions`.
+    """
+    # Import cycle trick :)
+    from rose.cache import list_tracks
+
+    release_dirnames: dict[str, str] = {}
+    rval: list[VirtualTreeEntry] = []
+    for track in list_tracks(c):
+        try:
+            release_dirname = release_dirnames[track.release.id]
+        except KeyError:
+            release_dirname = eval_release_template(
+                template.release, track.release, artist_role_order=c.artist_role_order
+            )
+            release_dirname = sanitize_dirname(release_dirname, False)
+            release_dirnames[track.release.id] = release_dirname
+        track_filename = eval_track_template(
+            template.track, track, artist_role_order=c.artist_role_order
+        )
+        track_filename = sanitize_filename(track_filename, False)
+        rval.append(
+            VirtualTreeEntry(
+                virtual_path=Path(release_dirname) / track_filename,
+                source_path=track.source_path,
+                release_id=track.release.id,
+                track_id=track.id,
+            )
+        )
+    return rval
+
+
+def resolve_collisions(tree: list[VirtualTreeEntry]) -> list[VirtualTreeEntry]:
+    """
+    Make the virtual paths of a virtual tree unique. When releases share a directory name, or tracks
+    in a release share a filename, all but the first of them (ordered by ID) have a collision number
+    appended, like the virtual filesystem does. The output is thus stable for a given library.
+    """
+    release_dirnames = _number_name_collisions(
+        {e.release_id: e.virtual_path.parent.name for e in tree},
+        lambda name, no: f"{name} [{no}]",
+    )
+    release_track_filenames: dict[str, dict[str, str]] = defaultdict(dict)
+    for e in tree:
+        release_track_filenames[e.release_id][e.track_id] = e.virtual_path.name
+    track_filenames: dict[str, str] = {}
+    for filenames in release_track_filenames.values():
+        track_filenames.update(
+            _number_name_collisions(
+                filenames,
+                # Write the collision number before the file extension.
+                lambda name, no: f"{Path(name).stem} [{no}]{Path(name).suffix}",
+            )
+        )
+    return [
+        dataclasses.replace(
+            e,
+            virtual_path=Path(release_dirnames[e.release_id]) / track_filenames[e.track_id],
+        )
+        for e in tree
+    ]
+
+
+def check_template_collisions(c: Config, template: PathTemplate) -> list[list[CachedRelease]]:
+    """
+    Evaluate a release template for every release and return the clusters of distinct releases
+    that map to the same directory name. Use this to check that a template is not lossy before
+    adopting it. The clusters are ordered by directory name, and releases by source path.
+    """
+    # Import cycle trick :)
+    from rose.cache import list_releases
+
+    clusters: dict[str, list[CachedRelease]] = defaultdict(list)
+    for release in list_releases(c, include_hidden=True):
+        dirname = eval_release_template(template, release, artist_role_order=c.artist_role_order)
+        clusters[sanitize_dirname(dirname, False)].append(release)
+    return [releases for _, releases in sorted(clusters.items()) if len(releases) > 1]
+
+
+def _number_name_collisions(
+    names: dict[str, str],
+    add_collision_number: Callable[[str, int], str],
+) -> dict[str, str]:
+    """Takes a map of ID -> name and returns a map of ID -> unique name."""
+    ids_by_name: dict[str, list[str]] = defaultdict(list)
+    for id_, name in names.items():
+        ids_by_name[name].append(id_)
+    # Reserve all the original names, so that a numbered name never takes another item's name.
+    taken = set(ids_by_name)
+    rval: dict[str, str] = {}
+    for name, ids in sorted(ids_by_name.items()):
+        ids.sort()
+        rval[ids[0]] = name
+        collision_no = 2
+        for id_ in ids[1:]:
+            while add_collision_number(name, collision_no) in taken:
+                collision_no += 1
+            numbered_name = add_collision_number(name, collision_no)
+            taken.add(numbered_name)
+            rval[id_] = numbered_name
+    return rval
+
+
 def _calc_release_variables(release: CachedRelease, position: str | None) -> dict[str, Any]:
     return {
         "added_at": release.added_at,
@@ -258,19 +417,34 @@ def _calc_release_variables(release: CachedRelease, position: str | None) -> dic
         "genres": release.genres,
         "labels": release.labels,
         "artists": release.albumartists,
+        "single_track": is_single_track_release(release),
         "position": position,
     }
 
 
+def is_single_track_release(release: CachedRelease, track_count: int | None = None) -> bool:
+    """
+    Whether the release holds a single track: either its release type is a single or loose track, or
+    it is known to have exactly one track. The track count is only known in track templates.
+    """
+    return release.releasetype in ("single", "loosetrack") or track_count == 1
+
+
 def _calc_track_variables(track: CachedTrack, position: str | None) -> dict[str, Any]:
     return {
         "title": track.tracktitle,
+        "subtitle": track.tracksubtitle,
         "tracknumber": track.tracknumber,
         "tracktotal": track.tracktotal,
         "discnumber": track.discnumber,
         "disctotal": track.disctotal,
         "duration_seconds": track.duration_seconds,
         "artists": track.trackartists,
+        # Tracktotal is computed per disc from the tracks in the cache, so it is the release's track
+        # count when the release has one disc.
+        "single_track": is_single_track_release(
+            track.release, track.tracktotal if track.disctotal == 1 else None
+        ),
         "position": position,
     }
 
@@ -320,13 +494,18 @@ def _get_preview_releases(c: Config) -> tuple[CachedRelease, CachedRelease]:
         id="018b268e-ff1e-7a0c-9ac8-7bbb282761f2",
         source_path=c.music_source_dir / "LOONA - 2017. Kim Lip",
         cover_image_path=None,
+        cover_hash=None,
+        cover_size=None,
+        cover_mtime=None,
         added_at="2023-04-20:23:45Z",
         datafile_mtime="999",
         albumtitle="Kim Lip",
         releasetype="single",
         year=2017,
         new=True,
+        hidden=False,
         disctotal=1,
+        disc_titles={},
         genres=["K-Pop", "Dance-Pop", "Contemporary R&B"],
         labels=["BlockBerryCreative"],
         albumartists=ArtistMapping(main=[Artist("Kim Lip")]),
@@ -337,13 +516,18 @@ def _get_preview_releases(c: Config) -> tuple[CachedRelease, CachedRelease]:
         id="018b6021-f1e5-7d4b-b796-440fbbea3b13",
         source_path=c.music_source_dir / "BTS - 2016. Young Forever (花樣年華)",
         cover_image_path=None,
+        cover_hash=None,
+        cover_size=None,
+        cover_mtime=None,
         added_at="2023-06-09:23:45Z",
         datafile_mtime="999",
         albumtitle="Young Forever (花樣年華)",
         releasetype="album",
         year=2016,
         new=False,
+        hidden=False,
         disctotal=2,
+        disc_titles={},
         genres=["K-Pop"],
         labels=["BIGHIT"],
         albumartists=ArtistMapping(main=[Artist("BTS")]),
@@ -358,9 +542,9 @@ def _preview_release_template(c: Config, label: str, template: PathTemplate) ->
     kimlip, youngforever = _get_preview_releases(c)
     click.secho(f"{label}:", dim=True, underline=True)
     click.secho("  Sample 1: ", dim=True, nl=False)
-    click.secho(eval_release_template(template, kimlip, "1"))
+    click.secho(eval_release_template(template, kimlip, "1", c.artist_role_order))
     click.secho("  Sample 2: ", dim=True, nl=False)
-    click.secho(eval_release_template(template, youngforever, "2"))
+    click.secho(eval_release_template(template, youngforever, "2", c.artist_role_order))
 
 
 def _preview_track_template(c: Config, label: str, template: PathTemplate) -> None:
@@ -377,16 +561,21 @@ def _preview_track_template(c: Config, label: str, template: PathTemplate) -> No
         source_path=c.music_source_dir / "LOONA - 2017. Kim Lip" / "01. Eclipse.opus",
         source_mtime="999",
         tracktitle="Eclipse",
+        tracksubtitle=None,
         tracknumber="1",
         tracktotal=2,
         discnumber="1",
+        discsubtitle=None,
         disctotal=1,
         duration_seconds=230,
+        audio_sha256=None,
+        isrc=None,
+        original_filename=None,
         trackartists=ArtistMapping(main=[Artist("Kim Lip")]),
         metahash="0",
         release=kimlip,
     )
-    click.secho(eval_track_template(template, track, "1"))
+    click.secho(eval_track_template(template, track, "1", c.artist_role_order))
 
     click.secho("  Sample 2: ", dim=True, nl=False)
     track = CachedTrack(
@@ -396,13 +585,18 @@ def _preview_track_template(c: Config, label: str, template: PathTemplate) -> No
         / "House of Cards.opus",
         source_mtime="999",
         tracktitle="House of Cards",
+        tracksubtitle=None,
         tracknumber="5",
         tracktotal=8,
         discnumber="2",
+        discsubtitle=None,
         disctotal=2,
         duration_seconds=226,
+        audio_sha256=None,
+        isrc=None,
+        original_filename=None,
         trackartists=ArtistMapping(main=[Artist("BTS")]),
         metahash="0",
         release=youngforever,
     )
-    click.secho(eval_track_template(template, track, "2"))
+    click.secho(eval_track_template(template, track, "2", c.artist_role_order))
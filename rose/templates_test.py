@@ -2,29 +2,42 @@ from copy import deepcopy
 from pathlib import Path
 
 import click
+import pytest
 from click.testing import CliRunner
 
-from rose.cache import CachedRelease, CachedTrack
+from rose.cache import CachedRelease, CachedTrack, connect
 from rose.common import Artist, ArtistMapping
 from rose.config import Config
 from rose.templates import (
+    PathTemplate,
     PathTemplateConfig,
+    PathTemplatePair,
+    artistsfmt,
+    check_template_collisions,
     eval_release_template,
     eval_track_template,
+    is_single_track_release,
     preview_path_templates,
+    resolve_collisions,
+    virtual_tree,
 )
 
 EMPTY_CACHED_RELEASE = CachedRelease(
     id="",
     source_path=Path(),
     cover_image_path=None,
+    cover_hash=None,
+    cover_size=None,
+    cover_mtime=None,
     added_at="0000-01-01T00:00:00Z",
     datafile_mtime="999",
     albumtitle="",
     releasetype="unknown",
     year=None,
     new=False,
+    hidden=False,
     disctotal=1,
+    disc_titles={},
     genres=[],
     labels=[],
     albumartists=ArtistMapping(),
@@ -36,11 +49,16 @@ EMPTY_CACHED_TRACK = CachedTrack(
     source_path=Path("hi.m4a"),
     source_mtime="",
     tracktitle="",
+    tracksubtitle=None,
     tracknumber="",
     tracktotal=1,
     discnumber="",
+    discsubtitle=None,
     disctotal=1,
     duration_seconds=0,
+    audio_sha256=None,
+    isrc=None,
+    original_filename=None,
     trackartists=ArtistMapping(),
     metahash="0",
     release=EMPTY_CACHED_RELEASE,
@@ -101,6 +119,76 @@ def test_default_templates() -> None:
     )
 
 
+def test_track_subtitle_template() -> None:
+    template = PathTemplate("{{ title }}{% if subtitle %} ({{ subtitle }}){% endif %}")
+    track = deepcopy(EMPTY_CACHED_TRACK)
+    track.tracktitle = "Trick"
+    assert eval_track_template(template, track) == "Trick.m4a"
+    track.tracksubtitle = "Radio Edit"
+    assert eval_track_template(template, track) == "Trick (Radio Edit).m4a"
+
+
+def test_single_track_release_template() -> None:
+    template = PathTemplate(
+        """
+        {% if single_track %}{{ artists | artistsfmt }} - {{ title }}
+        {% else %}{{ tracknumber }}. {{ title }}{% endif %}
+        """
+    )
+    track = deepcopy(EMPTY_CACHED_TRACK)
+    track.tracktitle = "Trick"
+    track.tracknumber = "1"
+    track.tracktotal = 2
+    track.trackartists = ArtistMapping(main=[Artist("Main")])
+    # An album with several tracks.
+    track.release.releasetype = "album"
+    assert not is_single_track_release(track.release, track.tracktotal)
+    assert eval_track_template(template, track) == "1. Trick.m4a"
+    # A single or loose track, regardless of its track count.
+    for releasetype in ["single", "loosetrack"]:
+        track.release.releasetype = releasetype
+        assert is_single_track_release(track.release)
+        assert eval_track_template(template, track) == "Main - Trick.m4a"
+    # A release of any type with one track.
+    track.release.releasetype = "album"
+    track.tracktotal = 1
+    assert is_single_track_release(track.release, track.tracktotal)
+    assert eval_track_template(template, track) == "Main - Trick.m4a"
+    # The track count of a multi-disc release is unknown.
+    track.disctotal = 2
+    assert eval_track_template(template, track) == "1. Trick.m4a"
+
+
+def test_single_track_release_release_template() -> None:
+    template = PathTemplate("{{ title }}{% if single_track %} - Single{% endif %}")
+    release = deepcopy(EMPTY_CACHED_RELEASE)
+    release.albumtitle = "Trick"
+    release.releasetype = "album"
+    assert eval_release_template(template, release) == "Trick"
+    release.releasetype = "single"
+    assert eval_release_template(template, release) == "Trick - Single"
+    release.releasetype = "loosetrack"
+    assert eval_release_template(template, release) == "Trick - Single"
+
+
+def test_artist_role_order() -> None:
+    artists = ArtistMapping(main=[Artist("Main")], composer=[Artist("Composer")])
+    assert artistsfmt(artists) == "Composer performed by Main"
+    assert artistsfmt(artists, ["main", "composer"]) == "Main (composed by Composer)"
+    # Roles that are not in the order are not rendered.
+    assert artistsfmt(artists, ["main"]) == "Main"
+
+    # The order is threaded through the template evaluation.
+    release = deepcopy(EMPTY_CACHED_RELEASE)
+    release.albumartists = artists
+    template = PathTemplate("{{ albumartists | artistsfmt }}")
+    assert eval_release_template(template, release) == "Composer performed by Main"
+    assert (
+        eval_release_template(template, release, artist_role_order=["main", "composer"])
+        == "Main (composed by Composer)"
+    )
+
+
 def test_preview_templates(config: Config) -> None:
     runner = CliRunner()
     with runner.isolated_filesystem(), runner.isolation() as out_streams:
@@ -172,3 +260,64 @@ Source Directory - Track:
   Sample 2: 2. BTS - House of Cards.opus
 """
     )
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_virtual_tree(config: Config) -> None:
+    tree = virtual_tree(config, config.path_templates.all_releases)
+    assert sorted((e.virtual_path, e.source_path) for e in tree) == [
+        (
+            Path("Techno Man & Bass Man - 2023. Release 1") / "01. Track 1.m4a",
+            config.music_source_dir / "r1" / "01.m4a",
+        ),
+        (
+            Path("Techno Man & Bass Man - 2023. Release 1") / "02. Track 2.m4a",
+            config.music_source_dir / "r1" / "02.m4a",
+        ),
+        (
+            Path("Unknown Artists - 2021. Release 3 [NEW]") / "01. Track 1.m4a",
+            config.music_source_dir / "r3" / "01.m4a",
+        ),
+        (
+            Path("Violin Woman (feat. Conductor Woman) - 2021. Release 2")
+            / "01. Track 1 (feat. Conductor Woman).m4a",
+            config.music_source_dir / "r2" / "01.m4a",
+        ),
+    ]
+    # Every track maps to a distinct virtual path.
+    assert len({e.virtual_path for e in tree}) == len(tree)
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_virtual_tree_resolve_collisions(config: Config) -> None:
+    # Every release and every track has the same name under this template.
+    template = PathTemplatePair(release=PathTemplate("Release"), track=PathTemplate("Track"))
+    raw_tree = virtual_tree(config, template)
+    tree = resolve_collisions(raw_tree)
+    assert sorted((e.track_id, e.virtual_path) for e in tree) == [
+        ("t1", Path("Release") / "Track.m4a"),
+        ("t2", Path("Release") / "Track [2].m4a"),
+        ("t3", Path("Release [2]") / "Track.m4a"),
+        ("t4", Path("Release [3]") / "Track.m4a"),
+    ]
+    # The resolution does not depend on the order of the input.
+    assert resolve_collisions(list(reversed(raw_tree))) == list(reversed(tree))
+
+
+@pytest.mark.usefixtures("seeded_cache")
+def test_check_template_collisions(config: Config) -> None:
+    assert check_template_collisions(config, config.path_templates.all_releases.release) == []
+
+    # Credit Release 2 to the same artists as Release 1.
+    with connect(config) as conn:
+        conn.execute(
+            """
+            UPDATE releases_artists
+            SET artist = CASE role WHEN 'main' THEN 'Techno Man' ELSE 'Bass Man' END
+              , role = 'main'
+            WHERE release_id = 'r2'
+            """
+        )
+    # An artist-only template is lossy, so it clusters the releases of the same artists.
+    clusters = check_template_collisions(config, PathTemplate("{{ artists | artistsfmt }}"))
+    assert [[r.id for r in releases] for releases in clusters] == [["r1", "r2"]]
@@ -39,7 +39,7 @@ def dump_tracks(c: Config, matcher: MetadataMatcher | None = None) -> str:
     track_ids = None
     if matcher:
         track_ids = [t.id for t in fast_search_for_matching_tracks(c, matcher)]
-    tracks = list_tracks(c, track_ids)
+    tracks = list_tracks(c, track_ids, include_hidden=True)
     if matcher:
         tracks = filter_track_false_positives_using_read_cache(matcher, tracks)
     return json.dumps([t.dump() for t in tracks])
@@ -52,10 +52,14 @@ def run_actions_on_track(
     *,
     dry_run: bool = False,
     confirm_yes: bool = False,
-) -> None:
-    """Run rule engine actions on a release."""
+) -> int:
+    """Run rule engine actions on a track. Returns the number of files that were modified."""
     track = get_track(c, track_id)
     if track is None:
         raise TrackDoesNotExistError(f"Track {track_id} does not exist")
-    audiotag = AudioTags.from_file(track.source_path)
-    execute_metadata_actions(c, actions, [audiotag], dry_run=dry_run, confirm_yes=confirm_yes)
+    audiotag = AudioTags.from_file(
+        track.source_path, combined_artist_tag_fallback=c.combined_artist_tag_fallback
+    )
+    return execute_metadata_actions(
+        c, actions, [audiotag], dry_run=dry_run, confirm_yes=confirm_yes
+    )
@@ -13,9 +13,10 @@ def test_run_action_on_track(config: Config, source_dir: Path) -> None:
     action = MetadataAction.parse("tracktitle::replace:Bop")
     af = AudioTags.from_file(source_dir / "Test Release 2" / "01.m4a")
     assert af.id is not None
-    run_actions_on_track(config, af.id, [action])
+    assert run_actions_on_track(config, af.id, [action]) == 1
     af = AudioTags.from_file(source_dir / "Test Release 2" / "01.m4a")
     assert af.title == "Bop"
+    assert run_actions_on_track(config, af.id, [action]) == 0
 
 
 @pytest.mark.usefixtures("seeded_cache")
@@ -34,11 +35,13 @@ def test_dump_tracks(config: Config) -> None:
                 "remixer": [],
             },
             "discnumber": "01",
+            "discsubtitle": None,
             "disctotal": 1,
             "duration_seconds": 120,
             "id": "t1",
             "source_path": f"{config.music_source_dir}/r1/01.m4a",
             "tracktitle": "Track 1",
+            "tracksubtitle": None,
             "tracknumber": "01",
             "tracktotal": 2,
             "added_at": "0000-01-01T00:00:00+00:00",
@@ -74,11 +77,13 @@ def test_dump_tracks(config: Config) -> None:
                 "remixer": [],
             },
             "discnumber": "01",
+            "discsubtitle": None,
             "disctotal": 1,
             "duration_seconds": 240,
             "id": "t2",
             "source_path": f"{config.music_source_dir}/r1/02.m4a",
             "tracktitle": "Track 2",
+            "tracksubtitle": None,
             "tracknumber": "02",
             "tracktotal": 2,
             "added_at": "0000-01-01T00:00:00+00:00",
@@ -111,11 +116,13 @@ def test_dump_tracks(config: Config) -> None:
                 "remixer": [],
             },
             "discnumber": "01",
+            "discsubtitle": None,
             "disctotal": 1,
             "duration_seconds": 120,
             "id": "t3",
             "source_path": f"{config.music_source_dir}/r2/01.m4a",
             "tracktitle": "Track 1",
+            "tracksubtitle": None,
             "tracknumber": "01",
             "tracktotal": 1,
             "added_at": "0000-01-01T00:00:00+00:00",
@@ -145,11 +152,13 @@ def test_dump_tracks(config: Config) -> None:
                 "remixer": [],
             },
             "discnumber": "01",
+            "discsubtitle": None,
             "disctotal": 1,
             "duration_seconds": 120,
             "id": "t4",
             "source_path": f"{config.music_source_dir}/r3/01.m4a",
             "tracktitle": "Track 1",
+            "tracksubtitle": None,
             "tracknumber": "01",
             "tracktotal": 1,
             "added_at": "0000-01-01T00:00:00+00:00",
@@ -189,11 +198,13 @@ def test_dump_tracks_with_matcher(config: Config) -> None:
                 "remixer": [],
             },
             "discnumber": "01",
+            "discsubtitle": None,
             "disctotal": 1,
             "duration_seconds": 120,
             "id": "t1",
             "source_path": f"{config.music_source_dir}/r1/01.m4a",
             "tracktitle": "Track 1",
+            "tracksubtitle": None,
             "tracknumber": "01",
             "tracktotal": 2,
             "added_at": "0000-01-01T00:00:00+00:00",
@@ -229,11 +240,13 @@ def test_dump_tracks_with_matcher(config: Config) -> None:
                 "remixer": [],
             },
             "discnumber": "01",
+            "discsubtitle": None,
             "disctotal": 1,
             "duration_seconds": 240,
             "id": "t2",
             "source_path": f"{config.music_source_dir}/r1/02.m4a",
             "tracktitle": "Track 2",
+            "tracksubtitle": None,
             "tracknumber": "02",
             "tracktotal": 2,
             "added_at": "0000-01-01T00:00:00+00:00",
@@ -274,11 +287,13 @@ def test_dump_track(config: Config) -> None:
             "remixer": [],
         },
         "discnumber": "01",
+        "discsubtitle": None,
         "disctotal": 1,
         "duration_seconds": 120,
         "id": "t1",
         "source_path": f"{config.music_source_dir}/r1/01.m4a",
         "tracktitle": "Track 1",
+        "tracksubtitle": None,
         "tracknumber": "01",
         "tracktotal": 2,
         "added_at": "0000-01-01T00:00:00+00:00",
@@ -52,7 +52,7 @@ from typing import Any, Generic, Literal, TypeVar
 
 import llfuse
 
-from rose.audiotags import SUPPORTED_AUDIO_EXTENSIONS, AudioTags
+from rose.audiotags import AudioTags, is_supported_audio_file
 from rose.cache import (
     STORED_DATA_FILE_REGEX,
     CachedRelease,
@@ -385,6 +385,7 @@ class VirtualNameGenerator:
                 title=release.albumtitle,
                 year=release.year,
                 artists=release.albumartists,
+                artist_role_order=self._config.artist_role_order,
             )
 
             # Generate a position if we're in a collage.
@@ -401,7 +402,9 @@ class VirtualNameGenerator:
                     f"VNAMES: Reused cached virtual dirname {vname} for release {logtext} in {time.time()-time_start} seconds"
                 )
             except KeyError:
-                vname = eval_release_template(template, release, position)
+                vname = eval_release_template(
+                    template, release, position, self._config.artist_role_order
+                )
                 vname = sanitize_dirname(vname, False)
                 self._release_template_eval_cache[cachekey] = vname
                 logger.debug(
@@ -466,6 +469,7 @@ class VirtualNameGenerator:
                 title=track.tracktitle,
                 artists=track.trackartists,
                 suffix=track.source_path.suffix,
+                artist_role_order=self._config.artist_role_order,
             )
 
             # Generate a position if we're in a playlist.
@@ -478,7 +482,9 @@ class VirtualNameGenerator:
             try:
                 vname = self._track_template_eval_cache[cachekey]
             except KeyError:
-                vname = eval_track_template(template, track, position)
+                vname = eval_track_template(
+                    template, track, position, self._config.artist_role_order
+                )
                 vname = sanitize_filename(vname, False)
                 logger.debug(
                     f"VNAMES: Generated virtual filename {vname} for track {logtext} in {time.time() - time_start} seconds"
@@ -919,7 +925,7 @@ class RoseLogicalCore:
             p.view == "Playlists"
             and p.playlist
             and p.file
-            and p.file.lower() in self.config.valid_cover_arts
+            and self.config.is_valid_cover_art(Path(p.file))
             and (pdata := get_playlist(self.config, p.playlist))
         ):
             delete_playlist_cover_art(self.config, pdata[0].name)
@@ -1055,12 +1061,16 @@ class RoseLogicalCore:
                 return self.fhandler.wrap_host(os.open(str(release.cover_image_path), flags))
             # Otherwise, if we are writing a brand new cover image, initiate the "new-cover-art"
             # sequence.
-            if p.file.lower() in self.config.valid_cover_arts and flags & os.O_CREAT == os.O_CREAT:
+            if (
+                self.config.is_valid_cover_art(Path(p.file))
+                and flags & os.O_CREAT == os.O_CREAT
+            ):
                 fh = self.fhandler.next()
                 logtext = calculate_release_logtext(
                     title=release.albumtitle,
                     year=release.year,
                     artists=release.albumartists,
+                    artist_role_order=self.config.artist_role_order,
                 )
                 logger.debug(
                     f"LOGICAL: Begin new cover art sequence for release "
@@ -1082,7 +1092,7 @@ class RoseLogicalCore:
             # If we are trying to create an audio file in the playlist, enter the
             # "add-track-to-playlist" operation sequence. See the __init__ for more details.
             pf = Path(p.file)
-            if pf.suffix.lower() in SUPPORTED_AUDIO_EXTENSIONS and flags & os.O_CREAT == os.O_CREAT:
+            if is_supported_audio_file(pf) and flags & os.O_CREAT == os.O_CREAT:
                 fh = self.fhandler.next()
                 logger.debug(
                     f"LOGICAL: Begin playlist addition operation sequence for "
@@ -1097,7 +1107,10 @@ class RoseLogicalCore:
                 return fh
             # If we are trying to create a cover image in the playlist, enter the "new-cover-art"
             # sequence for the playlist.
-            if p.file.lower() in self.config.valid_cover_arts and flags & os.O_CREAT == os.O_CREAT:
+            if (
+                self.config.is_valid_cover_art(Path(p.file))
+                and flags & os.O_CREAT == os.O_CREAT
+            ):
                 fh = self.fhandler.next()
                 logger.debug(
                     f"LOGICAL: Begin new cover art sequence for playlist"
@@ -82,19 +82,21 @@ class EventHandler(FileSystemEventHandler):  # pragma: no cover
 
         # Collage event.
         relative_path = path.removeprefix(str(self.config.music_source_dir) + "/")
-        if relative_path.startswith("!collages/"):
+        collages_prefix = self.config.collages_dirname + "/"
+        if relative_path.startswith(collages_prefix):
             if not relative_path.endswith(".toml"):
                 return
-            collage = relative_path.removeprefix("!collages/").removesuffix(".toml")
+            collage = relative_path.removeprefix(collages_prefix).removesuffix(".toml")
             logger.debug(f"Queueing {etype} event on collage {collage}")
             self.queue.put(WatchdogEvent(collage=collage, type=etype))
             return
 
         # Playlist event.
-        if relative_path.startswith("!playlists/"):
+        playlists_prefix = self.config.playlists_dirname + "/"
+        if relative_path.startswith(playlists_prefix):
             if not relative_path.endswith(".toml"):
                 return
-            playlist = relative_path.removeprefix("!playlists/").removesuffix(".toml")
+            playlist = relative_path.removeprefix(playlists_prefix).removesuffix(".toml")
             logger.debug(f"Queueing {etype} event on playlist {playlist}")
             self.queue.put(WatchdogEvent(playlist=playlist, type=etype))
             return
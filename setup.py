@@ -19,6 +19,7 @@ setuptools.setup(
         "jinja2",
         "llfuse",
         "mutagen",
+        "pillow",
         "send2trash",
         "tomli-w",
         "uuid6",